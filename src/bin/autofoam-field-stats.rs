@@ -0,0 +1,218 @@
+use std::error::Error;
+
+use autofoam::coordinates::vector::format_vector;
+use autofoam::json;
+use autofoam::statistics::weighted_percentiles;
+use autofoam::statistics::weighted_statistics;
+use autofoam::statistics::FieldStatistics;
+use autofoam::vtk::clip::ClipMode;
+use autofoam::vtk::field_manager::Component;
+use autofoam::vtk::field_manager::FieldLocation;
+use autofoam::vtk::region::Region;
+use autofoam::vtk::VtpProcessor;
+use clap::Parser;
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Parser)]
+#[command(about = "Prints area-weighted statistics of surface fields")]
+pub struct Args {
+    #[arg(long, help = "Path to .vtp, legacy .vtk or .vtm file", value_hint = clap::ValueHint::FilePath)]
+    pub file: String,
+
+    #[arg(
+        long,
+        help = "Block of a .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub block: Option<String>,
+
+    #[arg(
+        long = "field",
+        help = "Field name, may be repeated (default: every field at --location)"
+    )]
+    pub fields: Vec<String>,
+
+    #[arg(
+        long,
+        default_value = "cell",
+        help = "Attribute location of the fields (cell or point)"
+    )]
+    pub location: FieldLocation,
+
+    #[arg(
+        long,
+        help = "Component of vector or tensor fields (x, y, z, mag or an index; default: mag)"
+    )]
+    pub component: Option<Component>,
+
+    #[arg(
+        long = "clip",
+        help = "Only use the surface inside this region, may be repeated: plane:ORIGIN:NORMAL \
+                (behind the plane), box:MIN:MAX, sphere:CENTRE:RADIUS or \
+                cylinder:POINT:AXIS:RADIUS, with vectors as x,y,z"
+    )]
+    pub clips: Vec<Region>,
+
+    #[arg(
+        long,
+        default_value = "centroid",
+        help = "Keep whole cells by their centroid, or cut cells at the region boundary (exact)"
+    )]
+    pub clip_mode: ClipMode,
+
+    #[arg(long, help = "Use the surface outside all --clip regions instead")]
+    pub clip_invert: bool,
+
+    #[arg(
+        long = "percentile",
+        value_delimiter = ',',
+        default_values_t = [5.0, 50.0, 95.0],
+        help = "Area-weighted percentiles (0-100) to report"
+    )]
+    pub percentiles: Vec<f64>,
+
+    #[arg(long, value_enum, default_value = "text", help = "Output format")]
+    pub format: OutputFormat,
+}
+
+struct FieldReport {
+    name: String,
+    stats: FieldStatistics,
+    min_location: [f64; 3],
+    max_location: [f64; 3],
+    percentiles: Vec<f64>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    let vtp = match &args.block {
+        Some(block) => VtpProcessor::from_block(&args.file, block)?,
+        None => VtpProcessor::from_file(&args.file)?,
+    };
+    let vtp = if args.clips.is_empty() {
+        vtp
+    } else {
+        vtp.clip(&args.clips, args.clip_mode, args.clip_invert)?
+    };
+    let mesh = vtp.geometry()?;
+
+    let fields = if args.fields.is_empty() {
+        vtp.list_fields_at(args.location)?
+    } else {
+        args.fields.clone()
+    };
+    if fields.is_empty() {
+        return Err(format!("No {} fields found", args.location).into());
+    }
+
+    let mut reports = Vec::with_capacity(fields.len());
+    for field in fields {
+        // Multi-component fields default to their magnitude
+        let component = match args.component {
+            Some(component) => Some(component),
+            None if vtp.num_components_at(&field, args.location)? > 1 => Some(Component::Magnitude),
+            None => None,
+        };
+        let values = vtp.cell_field(&field, component, args.location)?;
+
+        let stats = weighted_statistics(&values, mesh.areas())
+            .map_err(|e| format!("Field '{}': {}", field, e))?;
+        let percentiles = weighted_percentiles(&values, mesh.areas(), &args.percentiles)
+            .map_err(|e| format!("Field '{}': {}", field, e))?;
+
+        reports.push(FieldReport {
+            name: match component {
+                Some(component) => format!("{}_{}", field, component),
+                None => field,
+            },
+            stats,
+            min_location: mesh.centroids()[stats.min_cell],
+            max_location: mesh.centroids()[stats.max_cell],
+            percentiles,
+        });
+    }
+
+    match args.format {
+        OutputFormat::Text => print_text(&reports, &args.percentiles),
+        OutputFormat::Json => print_json(&reports, &args.percentiles),
+    }
+
+    Ok(())
+}
+
+fn print_text(reports: &[FieldReport], percentiles: &[f64]) {
+    for (i, report) in reports.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let stats = &report.stats;
+        println!("Field    : {}", report.name);
+        println!("Area     : {:.6e}", stats.area);
+        println!("Integral : {:.6e}", stats.integral);
+        println!("Mean     : {:.6e}", stats.mean);
+        println!("RMS      : {:.6e}", stats.rms);
+        println!("Std dev  : {:.6e}", stats.std_dev);
+        println!(
+            "Min      : {:.6e} at cell {} {}",
+            stats.min,
+            stats.min_cell,
+            format_vector(report.min_location)
+        );
+        println!(
+            "Max      : {:.6e} at cell {} {}",
+            stats.max,
+            stats.max_cell,
+            format_vector(report.max_location)
+        );
+        for (p, value) in percentiles.iter().zip(&report.percentiles) {
+            println!("{:<9}: {:.6e}", format!("P{}", p), value);
+        }
+    }
+}
+
+fn print_json(reports: &[FieldReport], percentiles: &[f64]) {
+    let fields: Vec<String> = reports
+        .iter()
+        .map(|report| {
+            let stats = &report.stats;
+            let percentiles: Vec<String> = percentiles
+                .iter()
+                .zip(&report.percentiles)
+                .map(|(p, value)| {
+                    format!("{}: {}", json::string(&p.to_string()), json::number(*value))
+                })
+                .collect();
+            format!(
+                "    {{\"name\": {}, \"area\": {}, \"integral\": {}, \"mean\": {}, \"rms\": {}, \
+                 \"std_dev\": {}, \"min\": {}, \"max\": {}, \"percentiles\": {{{}}}}}",
+                json::string(&report.name),
+                json::number(stats.area),
+                json::number(stats.integral),
+                json::number(stats.mean),
+                json::number(stats.rms),
+                json::number(stats.std_dev),
+                json_extremum(stats.min, stats.min_cell, report.min_location),
+                json_extremum(stats.max, stats.max_cell, report.max_location),
+                percentiles.join(", ")
+            )
+        })
+        .collect();
+    println!("{{\n  \"fields\": [\n{}\n  ]\n}}", fields.join(",\n"));
+}
+
+fn json_extremum(value: f64, cell: usize, location: [f64; 3]) -> String {
+    format!(
+        "{{\"value\": {}, \"cell\": {}, \"location\": [{}, {}, {}]}}",
+        json::number(value),
+        cell,
+        json::number(location[0]),
+        json::number(location[1]),
+        json::number(location[2])
+    )
+}
@@ -1,11 +1,37 @@
 use std::error::Error;
 
-use autofoam::histogram::weighted_histogram;
-use autofoam::interpolation::interpolate;
-use autofoam::vtk::calculate_polygon_areas;
+use autofoam::json;
+use autofoam::statistics::area_thresholds;
+use autofoam::statistics::Binning;
+use autofoam::statistics::Direction;
+use autofoam::statistics::ThresholdMethod;
+use autofoam::vtk::atomic_write::backup_file;
+use autofoam::vtk::clip::ClipMode;
+use autofoam::vtk::data_type::DataType;
+use autofoam::vtk::field_manager::Component;
+use autofoam::vtk::field_manager::FieldLocation;
+use autofoam::vtk::format::VtkFormat;
+use autofoam::vtk::region::inside_all;
+use autofoam::vtk::region::Region;
 use autofoam::vtk::VtpProcessor;
 use clap::ArgGroup;
 use clap::Parser;
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum Method {
+    /// Sort the values and accumulate their areas
+    Exact,
+    /// Interpolate a cumulative area histogram
+    Histogram,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// The threshold alone for a single target, otherwise a table
+    Text,
+    Json,
+}
 
 #[derive(Parser)]
 #[command(
@@ -14,61 +40,321 @@ use clap::Parser;
 #[command(group(
     ArgGroup::new("mode")
         .required(true)
-        .args(["percentile", "area"]),
+        .multiple(true)
+        .args(["percentiles", "areas"]),
 ))]
 pub struct Args {
-    #[arg(long, help = "Path to .vtp file")]
+    #[arg(long, help = "Path to .vtp, legacy .vtk or .vtm file")]
     pub file: String,
 
+    #[arg(
+        long,
+        help = "Block of a .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub block: Option<String>,
+
     #[arg(long, help = "Scalar field name")]
     pub field: String,
 
-    #[arg(long, help = "Percentile threshold (0-100)")]
-    pub percentile: Option<f64>,
+    #[arg(
+        long,
+        default_value = "cell",
+        help = "Attribute location of the field (cell or point)"
+    )]
+    pub location: FieldLocation,
+
+    #[arg(
+        long,
+        help = "Component of a vector or tensor field (x, y, z, mag or an index)"
+    )]
+    pub component: Option<Component>,
+
+    #[arg(
+        long = "clip",
+        help = "Only use the surface inside this region, may be repeated: plane:ORIGIN:NORMAL \
+                (behind the plane), box:MIN:MAX, sphere:CENTRE:RADIUS or \
+                cylinder:POINT:AXIS:RADIUS, with vectors as x,y,z"
+    )]
+    pub clips: Vec<Region>,
+
+    #[arg(
+        long,
+        default_value = "centroid",
+        help = "Keep whole cells by their centroid, or cut cells at the region boundary (exact)"
+    )]
+    pub clip_mode: ClipMode,
+
+    #[arg(long, help = "Use the surface outside all --clip regions instead")]
+    pub clip_invert: bool,
+
+    #[arg(
+        long = "percentile",
+        value_delimiter = ',',
+        help = "Percentile threshold (0-100), may be repeated or comma separated"
+    )]
+    pub percentiles: Vec<f64>,
+
+    #[arg(
+        long = "area",
+        value_delimiter = ',',
+        help = "Absolute area threshold, may be repeated or comma separated"
+    )]
+    pub areas: Vec<f64>,
+
+    #[arg(
+        long,
+        default_value = "bottom",
+        help = "Accumulate the area from the lowest (bottom) or highest (top) values"
+    )]
+    pub from: Direction,
+
+    #[arg(long, value_enum, default_value = "text", help = "Output format")]
+    pub format: OutputFormat,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value = "exact",
+        help = "How the threshold is computed"
+    )]
+    pub method: Method,
+
+    #[arg(
+        long,
+        conflicts_with = "bins",
+        help = "Histogram bin width, in the units of the field [default: 0.1]"
+    )]
+    pub bin_width: Option<f64>,
+
+    #[arg(long, help = "Number of histogram bins spanning the field range")]
+    pub bins: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Decimal places of the printed threshold (default: as many as needed)"
+    )]
+    pub precision: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Add a 0/1 cell field of this name marking the cells inside the area and write the \
+                file"
+    )]
+    pub mask: Option<String>,
+
+    #[arg(
+        long,
+        requires = "mask",
+        help = "Replace an existing field named --mask"
+    )]
+    pub overwrite: bool,
+
+    #[arg(
+        long,
+        requires = "mask",
+        help = "Path to output file for --mask (default: overwrite --file)",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub output: Option<String>,
+
+    #[arg(
+        long,
+        requires = "mask",
+        help = "Keep a copy of an existing output file as <output>.bak"
+    )]
+    pub backup: bool,
+}
 
-    #[arg(long, help = "Absolute area threshold")]
-    pub area: Option<f64>,
+struct Row {
+    label: String,
+    target_area: f64,
+    threshold: f64,
+    achieved_area: f64,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
 
-    let vtp = VtpProcessor::from_file(&args.file)?;
-    let (points, connectivity, offsets) = vtp.geometry()?;
+    let num_targets = args.percentiles.len() + args.areas.len();
+    if args.mask.is_some() && num_targets > 1 {
+        return Err("--mask requires a single --percentile or --area".into());
+    }
+    if args.mask.is_some() && args.output.is_none() {
+        if args.block.is_some() {
+            return Err("--output is required with --block".into());
+        }
+        if VtkFormat::is_multiblock(&args.file) {
+            return Err("--output is required with a .vtm --file".into());
+        }
+    }
 
-    let scalar_vec = vtp.field(&args.field)?;
+    let full_vtp = match &args.block {
+        Some(block) => VtpProcessor::from_block(&args.file, block)?,
+        None => VtpProcessor::from_file(&args.file)?,
+    };
+    if let Some(mask_name) = &args.mask {
+        if !args.overwrite && full_vtp.field_exists_at(mask_name, FieldLocation::Cell)? {
+            return Err(format!(
+                "Field '{}' already exists, use --overwrite to replace it",
+                mask_name
+            )
+            .into());
+        }
+    }
+    let clipped_vtp = if args.clips.is_empty() {
+        None
+    } else {
+        Some(full_vtp.clip(&args.clips, args.clip_mode, args.clip_invert)?)
+    };
+    let vtp = clipped_vtp.as_ref().unwrap_or(&full_vtp);
+    let mesh = vtp.geometry()?;
 
-    let area_vec = calculate_polygon_areas(&points, &connectivity, &offsets);
+    let scalar_vec = vtp.cell_field(&args.field, args.component, args.location)?;
 
-    let bin_width = 0.1;
+    let area_vec = mesh.areas();
+    let total_area = mesh.total_area();
 
-    let histogram = weighted_histogram(&scalar_vec, &area_vec, &bin_width);
+    let mut labels = Vec::with_capacity(num_targets);
+    let mut area_targets = Vec::with_capacity(num_targets);
+    for &percentile in &args.percentiles {
+        labels.push(format!("P{}", percentile));
+        area_targets.push(percentile * 0.01 * total_area);
+    }
+    for &area in &args.areas {
+        labels.push(format!("A{}", area));
+        area_targets.push(area);
+    }
 
-    let area_binned = &histogram.heights;
-    let bins = &histogram.bin_edges;
+    let method = match (args.method, args.bins) {
+        (Method::Exact, _) if args.bin_width.is_some() || args.bins.is_some() => {
+            return Err("--bin-width and --bins require --method histogram".into());
+        }
+        (Method::Exact, _) => ThresholdMethod::Exact,
+        (Method::Histogram, Some(bins)) => ThresholdMethod::Histogram(Binning::Count(bins)),
+        (Method::Histogram, None) => {
+            ThresholdMethod::Histogram(Binning::Width(args.bin_width.unwrap_or(0.1)))
+        }
+    };
+    let thresholds = area_thresholds(&scalar_vec, area_vec, &area_targets, method, args.from)?;
 
-    let mut area_cumsum: Vec<f64> = area_binned
-        .iter()
-        .scan(0.0, |sum, &val| {
-            *sum += val;
-            Some(*sum)
+    let rows: Vec<Row> = labels
+        .into_iter()
+        .zip(area_targets)
+        .zip(&thresholds)
+        .map(|((label, target_area), &threshold)| {
+            // The region beyond the threshold, whose area approximates the
+            // target
+            let inside = args.from.region(threshold).select(&scalar_vec);
+            Row {
+                label,
+                target_area,
+                threshold,
+                achieved_area: achieved_area(&inside, area_vec),
+            }
         })
         .collect();
-    if let Some(&last) = area_cumsum.last() {
-        area_cumsum.push(last);
-    }
 
-    let area_target = if let Some(percentile) = args.percentile {
-        (percentile * 0.01) * area_vec.iter().sum::<f64>()
-    } else if let Some(area) = args.area {
-        area
-    } else {
-        panic!("Either percentile or area must be provided");
-    };
+    match args.format {
+        OutputFormat::Text if rows.len() == 1 => {
+            let row = &rows[0];
+            println!("{}", format_value(row.threshold, args.precision));
+            eprintln!(
+                "Achieved area {:.6e} for target {:.6e} ({:+.2}%)",
+                row.achieved_area,
+                row.target_area,
+                (row.achieved_area - row.target_area)
+                    / row.target_area.abs().max(f64::MIN_POSITIVE)
+                    * 100.0
+            );
+        }
+        OutputFormat::Text => {
+            println!(
+                "{:<12} {:>14} {:>24} {:>14}",
+                "Target", "Area", "Threshold", "Achieved area"
+            );
+            for row in &rows {
+                println!(
+                    "{:<12} {:>14.6e} {:>24} {:>14.6e}",
+                    row.label,
+                    row.target_area,
+                    format_value(row.threshold, args.precision),
+                    row.achieved_area
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let entries: Vec<String> = rows
+                .iter()
+                .map(|row| {
+                    format!(
+                        "    {{\"target\": {}, \"area\": {}, \"threshold\": {}, \
+                         \"achieved_area\": {}}}",
+                        json::string(&row.label),
+                        json::number(row.target_area),
+                        json::number(row.threshold),
+                        json::number(row.achieved_area)
+                    )
+                })
+                .collect();
+            println!(
+                "{{\n  \"field\": {},\n  \"from\": \"{}\",\n  \"total_area\": {},\n  \
+                 \"thresholds\": [\n{}\n  ]\n}}",
+                json::string(&args.field),
+                args.from,
+                json::number(total_area),
+                entries.join(",\n")
+            );
+        }
+    }
 
-    let scalar_target = interpolate(&area_cumsum, bins, area_target);
+    if let Some(mask_name) = &args.mask {
+        // The mask goes on the full surface, with cells clipped away by
+        // their centroid
+        let full_mesh = full_vtp.geometry()?;
+        let full_values = full_vtp.cell_field(&args.field, args.component, args.location)?;
+        let region = args.from.region(rows[0].threshold);
+        let mask: Vec<f64> = full_values
+            .iter()
+            .zip(full_mesh.centroids())
+            .map(|(&value, &centroid)| {
+                let inside =
+                    region.contains(value) && inside_all(&args.clips, centroid, args.clip_invert);
+                if inside {
+                    1.0
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        let vtp = if full_vtp.field_exists_at(mask_name, FieldLocation::Cell)? {
+            full_vtp.remove_field_at(mask_name, FieldLocation::Cell)?
+        } else {
+            full_vtp
+        };
+        let vtp = vtp.add_field_as(mask_name, &mask, DataType::UInt8, FieldLocation::Cell)?;
 
-    println!("{:.1}", scalar_target);
+        let output = args.output.as_deref().unwrap_or(&args.file);
+        if args.backup {
+            backup_file(output)?;
+        }
+        vtp.write_to_file(output)?;
+    }
 
     Ok(())
 }
+
+fn achieved_area(inside: &[bool], areas: &[f64]) -> f64 {
+    inside
+        .iter()
+        .zip(areas)
+        .filter(|(&k, _)| k)
+        .map(|(_, area)| area)
+        .sum()
+}
+
+fn format_value(value: f64, precision: Option<usize>) -> String {
+    match precision {
+        Some(precision) => format!("{:.*}", precision, value),
+        None => format!("{}", value),
+    }
+}
@@ -1,56 +1,229 @@
 use std::error::Error;
 
-use autofoam::vtk::calculate_polygon_areas;
+use autofoam::statistics::weighted_statistics;
+use autofoam::statistics::FieldStatistics;
+use autofoam::statistics::Normalisation;
+use autofoam::vtk::atomic_write::backup_file;
+use autofoam::vtk::data_type::DataType;
+use autofoam::vtk::field_manager::Component;
+use autofoam::vtk::field_manager::FieldLocation;
+use autofoam::vtk::format::VtkFormat;
 use autofoam::vtk::VtpProcessor;
+use clap::ArgGroup;
 use clap::Parser;
 
 #[derive(Parser)]
 #[command(about = "Computes and writes normalized deviation of a scalar field")]
+#[command(group(
+    ArgGroup::new("reference")
+        .args(["reference_value", "reference_file", "u_inf"]),
+))]
 pub struct Args {
-    #[arg(long, help = "Path to .vtp file", value_hint = clap::ValueHint::FilePath)]
+    #[arg(long, help = "Path to .vtp, legacy .vtk or .vtm file", value_hint = clap::ValueHint::FilePath)]
     pub file: String,
 
+    #[arg(
+        long,
+        help = "Block of a .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub block: Option<String>,
+
+    #[arg(
+        long,
+        help = "Path to output file (default: overwrite --file)",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub output: Option<String>,
+
+    #[arg(long, help = "Keep a copy of an existing output file as <output>.bak")]
+    pub backup: bool,
+
     #[arg(long, help = "Scalar field name to process")]
     pub field: String,
+
+    #[arg(
+        long,
+        default_value = "cell",
+        help = "Attribute location of the field (cell or point)"
+    )]
+    pub location: FieldLocation,
+
+    #[arg(
+        long,
+        help = "Component of a vector or tensor field (x, y, z, mag or an index)"
+    )]
+    pub component: Option<Component>,
+
+    #[arg(
+        long,
+        default_value = "Float64",
+        help = "Data type of the written deviation field (e.g. Float32, Float64)"
+    )]
+    pub output_type: DataType,
+
+    #[arg(
+        long,
+        help = "Name of the written field (default: <field>_deviation, with the component if \
+                given)"
+    )]
+    pub output_field: Option<String>,
+
+    #[arg(
+        long,
+        allow_negative_numbers = true,
+        help = "Reference value to take the deviation from (default: the area-weighted mean of \
+                the field)"
+    )]
+    pub reference_value: Option<f64>,
+
+    #[arg(
+        long,
+        help = "Take the deviation from the area-weighted mean of the field in this file",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub reference_file: Option<String>,
+
+    #[arg(
+        long,
+        requires = "reference_file",
+        help = "Block of a --reference-file .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub reference_block: Option<String>,
+
+    #[arg(
+        long,
+        help = "Freestream velocity magnitude; the deviation is then taken from --p-ref relative \
+                to the dynamic pressure, as for a pressure coefficient"
+    )]
+    pub u_inf: Option<f64>,
+
+    #[arg(
+        long,
+        default_value_t = 1.0,
+        requires = "u_inf",
+        help = "Freestream density (1 for kinematic pressure)"
+    )]
+    pub rho_inf: f64,
+
+    #[arg(
+        long,
+        default_value_t = 0.0,
+        allow_negative_numbers = true,
+        requires = "u_inf",
+        help = "Freestream reference pressure"
+    )]
+    pub p_ref: f64,
+
+    #[arg(
+        long,
+        default_value = "relative",
+        help = "Divide the deviation by the reference magnitude (relative), leave it as is \
+                (absolute), or divide by the area-weighted standard deviation about the reference \
+                (z-score)"
+    )]
+    pub normalisation: Normalisation,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
 
-    let vtp = VtpProcessor::from_file(&args.file)?;
+    if args.output.is_none() {
+        if args.block.is_some() {
+            return Err("--output is required with --block".into());
+        }
+        if VtkFormat::is_multiblock(&args.file) {
+            return Err("--output is required with a .vtm --file".into());
+        }
+    }
 
-    let (points, connectivity, offsets) = vtp.geometry()?;
-    let field_values_vec = vtp.field(&args.field)?;
+    let vtp = match &args.block {
+        Some(block) => VtpProcessor::from_block(&args.file, block)?,
+        None => VtpProcessor::from_file(&args.file)?,
+    };
 
-    let areas_vec = calculate_polygon_areas(&points, &connectivity, &offsets);
+    let mesh = vtp.geometry()?;
+    let field_values_vec = vtp.scalar_field_at(&args.field, args.component, args.location)?;
+    let cell_values_vec = vtp.cell_field(&args.field, args.component, args.location)?;
 
-    let (weighted_sum, total_area): (f64, f64) = field_values_vec
-        .iter()
-        .zip(areas_vec.iter())
-        .fold((0.0, 0.0), |(sum, area_sum), (val, area)| {
-            (sum + val * area, area_sum + area)
-        });
-    if total_area == 0.0 {
-        return Err("Total area of the reference .vtp is zero, cannot use it".into());
-    }
+    let stats = weighted_statistics(&cell_values_vec, mesh.areas())
+        .map_err(|e| format!("Field '{}': {}", args.field, e))?;
 
-    let area_weighted_avg = weighted_sum / total_area;
+    // The value the deviation is taken from, the magnitude relative
+    // deviations are taken against, and the spread for z-scores. The spread
+    // is taken about the reference, which a reference file supplies with its
+    // own and a given value widens by its offset from the field's mean.
+    let (reference, scale, std_dev) = if let Some(value) = args.reference_value {
+        (value, value, spread_about(&stats, value))
+    } else if let Some(reference_file) = &args.reference_file {
+        let reference_vtp = match &args.reference_block {
+            Some(block) => VtpProcessor::from_block(reference_file, block)?,
+            None => VtpProcessor::from_file(reference_file)?,
+        };
+        let reference_mesh = reference_vtp.geometry()?;
+        let reference_values =
+            reference_vtp.cell_field(&args.field, args.component, args.location)?;
+        let reference_stats = weighted_statistics(&reference_values, reference_mesh.areas())
+            .map_err(|e| format!("Field '{}' of {}: {}", args.field, reference_file, e))?;
+        (
+            reference_stats.mean,
+            reference_stats.mean,
+            reference_stats.std_dev,
+        )
+    } else if let Some(u_inf) = args.u_inf {
+        let dynamic_pressure = 0.5 * args.rho_inf * u_inf * u_inf;
+        (
+            args.p_ref,
+            dynamic_pressure,
+            spread_about(&stats, args.p_ref),
+        )
+    } else {
+        (stats.mean, stats.mean, stats.std_dev)
+    };
+    eprintln!("Reference : {:.6e}", reference);
+    match args.normalisation {
+        Normalisation::Relative => eprintln!("Scale     : {:.6e}", scale),
+        Normalisation::Absolute => {}
+        Normalisation::ZScore => eprintln!("Std dev   : {:.6e}", std_dev),
+    }
 
     let deviation_vec: Vec<f64> = field_values_vec
         .iter()
-        .map(|f| (f - area_weighted_avg) / (area_weighted_avg.abs() + 1e-15))
+        .map(|&f| args.normalisation.deviation(f, reference, scale, std_dev))
         .collect();
 
-    let deviation_field_name = format!("{}_deviation", args.field);
+    let deviation_field_name = match (&args.output_field, args.component) {
+        (Some(name), _) => name.clone(),
+        (None, Some(component)) => format!("{}_{}_deviation", args.field, component),
+        (None, None) => format!("{}_deviation", args.field),
+    };
 
-    let updated_vtp = if vtp.field_exists(&deviation_field_name)? {
-        vtp.remove_field(&deviation_field_name)?
-            .add_field(&deviation_field_name, &deviation_vec)?
+    let updated_vtp = if vtp.field_exists_at(&deviation_field_name, args.location)? {
+        vtp.remove_field_at(&deviation_field_name, args.location)?
+            .add_field_as(
+                &deviation_field_name,
+                &deviation_vec,
+                args.output_type,
+                args.location,
+            )?
     } else {
-        vtp.add_field(&deviation_field_name, &deviation_vec)?
+        vtp.add_field_as(
+            &deviation_field_name,
+            &deviation_vec,
+            args.output_type,
+            args.location,
+        )?
     };
 
-    updated_vtp.write_to_file(&args.file)?;
+    let output = args.output.as_deref().unwrap_or(&args.file);
+    if args.backup {
+        backup_file(output)?;
+    }
+    updated_vtp.write_to_file(output)?;
 
     Ok(())
 }
+
+/// Area-weighted root mean square deviation of a field from `reference`
+fn spread_about(stats: &FieldStatistics, reference: f64) -> f64 {
+    (stats.std_dev.powi(2) + (stats.mean - reference).powi(2)).sqrt()
+}
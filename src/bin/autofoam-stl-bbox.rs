@@ -1,16 +1,11 @@
-use std::fs::File;
-use std::io::Seek;
-
 use autofoam::coordinates::update_coordinate_bounds;
-use autofoam::stl::is_ascii;
-use autofoam::stl::process_ascii_iter;
-use autofoam::stl::process_binary_iter;
+use autofoam::surface::process_surface_iter;
 use clap::Parser;
 
 #[derive(Parser)]
-#[command(about = "Prints the bbox of input stl file(s)")]
+#[command(about = "Prints the bbox of input surface file(s) (.stl, .obj, .ply, .off)")]
 pub struct Args {
-    #[arg(help = "Path(s) to .vtp file(s)", required = true, value_hint = clap::ValueHint::FilePath)]
+    #[arg(help = "Path(s) to surface file(s)", required = true, value_hint = clap::ValueHint::FilePath)]
     pub files: Vec<String>,
 }
 
@@ -21,20 +16,12 @@ fn main() {
     let mut max = [f32::NEG_INFINITY; 3];
 
     for path in &args.files {
-        let mut file = File::open(path).unwrap_or_else(|e| {
+        let iter = process_surface_iter(path).unwrap_or_else(|e| {
             eprintln!("Failed to open {}: {}", path, e);
             std::process::exit(1);
         });
 
         let mut count = 0;
-        let is_ascii_file = is_ascii(&mut file);
-        file.seek(std::io::SeekFrom::Start(0)).unwrap();
-
-        let iter: Box<dyn Iterator<Item = Result<[f32; 3], _>>> = if is_ascii_file {
-            Box::new(process_ascii_iter(file))
-        } else {
-            Box::new(process_binary_iter(file))
-        };
 
         for vertex_result in iter {
             match vertex_result {
@@ -0,0 +1,51 @@
+use std::error::Error;
+
+use autofoam::surface::process_surface_iter;
+use autofoam::vtk::VtpProcessor;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(
+    about = "Converts surface file(s) (.stl, .obj, .ply, .off) into a single .vtp or legacy .vtk"
+)]
+pub struct Args {
+    #[arg(help = "Path(s) to surface file(s)", required = true, value_hint = clap::ValueHint::FilePath)]
+    pub files: Vec<String>,
+
+    #[arg(long, help = "Path to output .vtp or legacy .vtk file", value_hint = clap::ValueHint::FilePath)]
+    pub output: String,
+
+    #[arg(
+        long,
+        help = "Add a 'regionId' cell field holding the index of the input file"
+    )]
+    pub region_ids: bool,
+
+    #[arg(long, help = "Add a 'normals' cell field with the unit facet normals")]
+    pub normals: bool,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    let mut vertices = Vec::new();
+    let mut region_ids = Vec::new();
+
+    for (region, path) in args.files.iter().enumerate() {
+        let file_vertices = process_surface_iter(path)?
+            .collect::<Result<Vec<[f32; 3]>, _>>()
+            .map_err(|e| format!("Error processing file {}: {}", path, e))?;
+        if file_vertices.is_empty() {
+            eprintln!("No vertices found in file {}", path);
+        }
+
+        region_ids.extend(std::iter::repeat_n(region as i32, file_vertices.len() / 3));
+        vertices.extend(file_vertices);
+    }
+
+    let region_ids = args.region_ids.then_some(region_ids.as_slice());
+    VtpProcessor::from_triangles(&vertices, region_ids, args.normals)?
+        .write_to_file(&args.output)?;
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use autofoam::calculator::parse_expression;
+use autofoam::calculator::Value;
+use autofoam::vtk::atomic_write::backup_file;
+use autofoam::vtk::field_manager::FieldLocation;
+use autofoam::vtk::format::VtkFormat;
+use autofoam::vtk::VtpProcessor;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(
+    about = "Evaluates an expression over the cells of a surface and adds the result as a field",
+    after_help = "Expressions support + - * / ^, parentheses, vectors [x, y, z], components U.x, \
+                  field names in braces such as {grad(p)}, the constants pi and e and the \
+                  functions sqrt, abs, exp, log, log10, sin, cos, tan, asin, acos, atan, atan2, \
+                  pow, min, max, mag, magSqr, dot, cross and normalize.\n\nExample: --expression \
+                  '(p - pInf)/(0.5*magSqr(U))' --const pInf=0 --result Cp"
+)]
+pub struct Args {
+    #[arg(long, help = "Path to .vtp, legacy .vtk or .vtm file", value_hint = clap::ValueHint::FilePath)]
+    pub file: String,
+
+    #[arg(
+        long,
+        help = "Block of a .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub block: Option<String>,
+
+    #[arg(
+        long,
+        help = "Path to output file (default: overwrite --file)",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub output: Option<String>,
+
+    #[arg(long, help = "Keep a copy of an existing output file as <output>.bak")]
+    pub backup: bool,
+
+    #[arg(long, allow_hyphen_values = true, help = "Expression to evaluate")]
+    pub expression: String,
+
+    #[arg(long, help = "Name of the result field")]
+    pub result: String,
+
+    #[arg(
+        long = "const",
+        value_parser = parse_constant,
+        help = "Scalar constant as NAME=VALUE, may be repeated"
+    )]
+    pub constants: Vec<(String, f64)>,
+
+    #[arg(
+        long,
+        default_value = "cell",
+        help = "Attribute location of the fields and the result (cell or point)"
+    )]
+    pub location: FieldLocation,
+
+    #[arg(long, help = "Replace an existing field named --result")]
+    pub overwrite: bool,
+}
+
+fn parse_constant(s: &str) -> Result<(String, f64), String> {
+    let (name, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected NAME=VALUE, got '{}'", s))?;
+    let value = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid value for constant '{}'", name.trim()))?;
+    Ok((name.trim().to_string(), value))
+}
+
+/// Values of a field at every cell or point
+enum Column {
+    Scalars(Vec<f64>),
+    Vectors(Vec<[f64; 3]>),
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    if args.output.is_none() {
+        if args.block.is_some() {
+            return Err("--output is required with --block".into());
+        }
+        if VtkFormat::is_multiblock(&args.file) {
+            return Err("--output is required with a .vtm --file".into());
+        }
+    }
+
+    let mut vtp = match &args.block {
+        Some(block) => VtpProcessor::from_block(&args.file, block)?,
+        None => VtpProcessor::from_file(&args.file)?,
+    };
+    let expr = parse_expression(&args.expression)?;
+
+    let mut constants: HashMap<&str, f64> =
+        HashMap::from([("pi", std::f64::consts::PI), ("e", std::f64::consts::E)]);
+    let mut columns = HashMap::new();
+    for name in expr.variables() {
+        if let Some((_, value)) = args.constants.iter().find(|(n, _)| n == name) {
+            constants.insert(name, *value);
+        } else if vtp.field_exists_at(name, args.location)? {
+            let column = match vtp.num_components_at(name, args.location)? {
+                1 => Column::Scalars(vtp.field_at(name, args.location)?),
+                3 => Column::Vectors(vtp.vectors_at(name, args.location)?),
+                n => {
+                    return Err(format!(
+                        "Field '{}' has {} components, only scalars and vectors are supported",
+                        name, n
+                    )
+                    .into())
+                }
+            };
+            columns.insert(name, column);
+        }
+    }
+
+    let mesh = vtp.geometry()?;
+    let size = match args.location {
+        FieldLocation::Cell => mesh.num_cells(),
+        FieldLocation::Point => mesh.num_points(),
+    };
+
+    let mut scalars = Vec::new();
+    let mut vectors = Vec::new();
+    for i in 0..size {
+        let variables = |name: &str| match columns.get(name) {
+            Some(Column::Scalars(values)) => values.get(i).map(|&v| Value::Scalar(v)),
+            Some(Column::Vectors(values)) => values.get(i).map(|&v| Value::Vector(v)),
+            None => constants.get(name).map(|&v| Value::Scalar(v)),
+        };
+        match expr.evaluate(&variables)? {
+            Value::Scalar(v) if vectors.is_empty() => scalars.push(v),
+            Value::Vector(v) if scalars.is_empty() => vectors.push(v),
+            _ => {
+                return Err("Expression gives scalars for some cells and vectors for others".into())
+            }
+        }
+    }
+
+    if vtp.field_exists_at(&args.result, args.location)? {
+        if !args.overwrite {
+            return Err(format!(
+                "Field '{}' already exists, use --overwrite to replace it",
+                args.result
+            )
+            .into());
+        }
+        vtp = vtp.remove_field_at(&args.result, args.location)?;
+    }
+    vtp = if vectors.is_empty() {
+        vtp.add_field_at(&args.result, &scalars, args.location)?
+    } else {
+        vtp.add_vector_field_at(&args.result, &vectors, args.location)?
+    };
+
+    let output = args.output.as_deref().unwrap_or(&args.file);
+    if args.backup {
+        backup_file(output)?;
+    }
+    vtp.write_to_file(output)?;
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use std::error::Error;
+
+use autofoam::vtk::atomic_write::backup_file;
+use autofoam::vtk::field_manager::FieldLocation;
+use autofoam::vtk::format::VtkFormat;
+use autofoam::vtk::VtpProcessor;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(about = "Adds cell areas, unit normals and centroids as cell fields")]
+pub struct Args {
+    #[arg(long, help = "Path to .vtp, legacy .vtk or .vtm file", value_hint = clap::ValueHint::FilePath)]
+    pub file: String,
+
+    #[arg(
+        long,
+        help = "Block of a .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub block: Option<String>,
+
+    #[arg(
+        long,
+        help = "Path to output file (default: overwrite --file)",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub output: Option<String>,
+
+    #[arg(long, help = "Keep a copy of an existing output file as <output>.bak")]
+    pub backup: bool,
+
+    #[arg(long, help = "Add the cell area field")]
+    pub area: bool,
+
+    #[arg(long, help = "Add the cell normal field")]
+    pub normals: bool,
+
+    #[arg(long, help = "Add the cell centroid field")]
+    pub centroids: bool,
+
+    #[arg(long, default_value = "area", help = "Name of the area field")]
+    pub area_name: String,
+
+    #[arg(long, default_value = "normals", help = "Name of the normal field")]
+    pub normals_name: String,
+
+    #[arg(long, default_value = "centroids", help = "Name of the centroid field")]
+    pub centroids_name: String,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    if args.output.is_none() {
+        if args.block.is_some() {
+            return Err("--output is required with --block".into());
+        }
+        if VtkFormat::is_multiblock(&args.file) {
+            return Err("--output is required with a .vtm --file".into());
+        }
+    }
+
+    let mut vtp = match &args.block {
+        Some(block) => VtpProcessor::from_block(&args.file, block)?,
+        None => VtpProcessor::from_file(&args.file)?,
+    };
+    let mesh = vtp.geometry()?;
+
+    // Without a selection, add everything
+    let all = !(args.area || args.normals || args.centroids);
+
+    if all || args.area {
+        vtp = remove_existing(vtp, &args.area_name)?;
+        vtp = vtp.add_field(&args.area_name, mesh.areas())?;
+    }
+    if all || args.normals {
+        vtp = remove_existing(vtp, &args.normals_name)?;
+        vtp = vtp.add_vector_field_at(&args.normals_name, mesh.normals(), FieldLocation::Cell)?;
+    }
+    if all || args.centroids {
+        vtp = remove_existing(vtp, &args.centroids_name)?;
+        vtp =
+            vtp.add_vector_field_at(&args.centroids_name, mesh.centroids(), FieldLocation::Cell)?;
+    }
+
+    let output = args.output.as_deref().unwrap_or(&args.file);
+    if args.backup {
+        backup_file(output)?;
+    }
+    vtp.write_to_file(output)?;
+
+    Ok(())
+}
+
+fn remove_existing(vtp: VtpProcessor, name: &str) -> Result<VtpProcessor, Box<dyn Error>> {
+    if vtp.field_exists(name)? {
+        vtp.remove_field(name)
+    } else {
+        Ok(vtp)
+    }
+}
@@ -0,0 +1,58 @@
+use std::error::Error;
+
+use autofoam::vtk::xml_writer::Compression;
+use autofoam::vtk::xml_writer::Encoding;
+use autofoam::vtk::xml_writer::WriteOptions;
+use autofoam::vtk::VtpProcessor;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(about = "Re-encodes a .vtp or legacy .vtk file")]
+pub struct Args {
+    #[arg(long, help = "Path to .vtp, legacy .vtk or .vtm file", value_hint = clap::ValueHint::FilePath)]
+    pub file: String,
+
+    #[arg(
+        long,
+        help = "Block of a .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub block: Option<String>,
+
+    #[arg(long, help = "Path to output .vtp or .vtk file", value_hint = clap::ValueHint::FilePath)]
+    pub output: String,
+
+    #[arg(
+        long,
+        default_value = "binary",
+        help = "Data encoding (ascii, binary or appended)"
+    )]
+    pub encoding: Encoding,
+
+    #[arg(
+        long,
+        default_value = "none",
+        help = "Compression of binary or appended data (none, zlib or lz4)"
+    )]
+    pub compression: Compression,
+
+    #[arg(long, default_value_t = 6, help = "zlib compression level (0-9)")]
+    pub level: u32,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    let vtp = match &args.block {
+        Some(block) => VtpProcessor::from_block(&args.file, block)?,
+        None => VtpProcessor::from_file(&args.file)?,
+    };
+
+    let options = WriteOptions {
+        encoding: args.encoding,
+        compression: args.compression,
+        level: args.level,
+    };
+    vtp.write_to_file_with(&args.output, &options)?;
+
+    Ok(())
+}
@@ -0,0 +1,373 @@
+use std::error::Error;
+
+use autofoam::json;
+use autofoam::statistics::weighted_statistics;
+use autofoam::statistics::Difference;
+use autofoam::statistics::FieldStatistics;
+use autofoam::vtk::atomic_write::backup_file;
+use autofoam::vtk::data_type::DataType;
+use autofoam::vtk::field_manager::Component;
+use autofoam::vtk::field_manager::FieldLocation;
+use autofoam::vtk::field_mapping::FieldMapping;
+use autofoam::vtk::field_mapping::MapMethod;
+use autofoam::vtk::format::VtkFormat;
+use autofoam::vtk::surface_mesh::SurfaceMesh;
+use autofoam::vtk::VtpProcessor;
+use clap::Parser;
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Writes the differences of fields between a baseline and a variant surface onto the \
+             variant and prints their integrated statistics"
+)]
+pub struct Args {
+    #[arg(long, help = "Baseline surface (.vtp, legacy .vtk or .vtm)", value_hint = clap::ValueHint::FilePath)]
+    pub baseline: String,
+
+    #[arg(
+        long,
+        help = "Block of a baseline .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub baseline_block: Option<String>,
+
+    #[arg(long, help = "Variant surface (.vtp, legacy .vtk or .vtm)", value_hint = clap::ValueHint::FilePath)]
+    pub variant: String,
+
+    #[arg(
+        long,
+        help = "Block of a variant .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub variant_block: Option<String>,
+
+    #[arg(
+        long,
+        help = "Path to output file (default: overwrite --variant)",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub output: Option<String>,
+
+    #[arg(long, help = "Keep a copy of an existing output file as <output>.bak")]
+    pub backup: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["output", "backup"],
+        help = "Print the statistics only, without writing the differences"
+    )]
+    pub no_write: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "no_write",
+        help = "Replace existing <field>_<difference> fields of the variant"
+    )]
+    pub overwrite: bool,
+
+    #[arg(
+        long = "field",
+        help = "Field to compare, may be repeated (default: every field at --location in both \
+                files)"
+    )]
+    pub fields: Vec<String>,
+
+    #[arg(
+        long,
+        default_value = "cell",
+        help = "Attribute location of the fields (cell or point)"
+    )]
+    pub location: FieldLocation,
+
+    #[arg(
+        long,
+        help = "Component of vector or tensor fields (x, y, z, mag or an index; default: mag)"
+    )]
+    pub component: Option<Component>,
+
+    #[arg(
+        long = "difference",
+        value_delimiter = ',',
+        default_values_t = Difference::ALL,
+        help = "Differences to compute, may be repeated or comma separated: absolute, relative \
+                (to the baseline value) or normalised (by the baseline's area-weighted mean)"
+    )]
+    pub differences: Vec<Difference>,
+
+    #[arg(
+        long,
+        help = "Map the baseline onto the variant by nearest or closest-point (default: the \
+                surfaces must have the same cells)"
+    )]
+    pub map: Option<MapMethod>,
+
+    #[arg(
+        long,
+        requires = "map",
+        help = "Leave variant cells further than this from the baseline out, as NaN"
+    )]
+    pub max_distance: Option<f64>,
+
+    #[arg(
+        long,
+        default_value = "Float64",
+        help = "Data type of the written difference fields (e.g. Float32, Float64)"
+    )]
+    pub output_type: DataType,
+
+    #[arg(long, value_enum, default_value = "text", help = "Output format")]
+    pub format: OutputFormat,
+}
+
+struct FieldReport {
+    name: String,
+    baseline_integral: f64,
+    variant_integral: f64,
+    differences: Vec<(Difference, FieldStatistics)>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    if !args.no_write && args.output.is_none() {
+        if args.variant_block.is_some() {
+            return Err("--output is required with --variant-block".into());
+        }
+        if VtkFormat::is_multiblock(&args.variant) {
+            return Err("--output is required with a .vtm --variant".into());
+        }
+    }
+
+    let baseline = match &args.baseline_block {
+        Some(block) => VtpProcessor::from_block(&args.baseline, block)?,
+        None => VtpProcessor::from_file(&args.baseline)?,
+    };
+    let mut variant = match &args.variant_block {
+        Some(block) => VtpProcessor::from_block(&args.variant, block)?,
+        None => VtpProcessor::from_file(&args.variant)?,
+    };
+    let baseline_mesh = baseline.geometry()?;
+    let variant_mesh = variant.geometry()?;
+
+    let mapping = match args.map {
+        Some(method) => {
+            let mapping = FieldMapping::new(
+                &baseline_mesh,
+                &variant_mesh,
+                args.location,
+                method,
+                args.max_distance,
+            )?;
+            if mapping.num_unmapped() > 0 {
+                eprintln!(
+                    "{} variant {}s have no baseline within {} and are left out",
+                    mapping.num_unmapped(),
+                    args.location,
+                    args.max_distance.unwrap_or_default()
+                );
+            }
+            Some(mapping)
+        }
+        None => {
+            let count = |mesh: &SurfaceMesh| match args.location {
+                FieldLocation::Cell => mesh.num_cells(),
+                FieldLocation::Point => mesh.num_points(),
+            };
+            if count(&baseline_mesh) != count(&variant_mesh) {
+                return Err(format!(
+                    "The baseline has {} {}s and the variant {}, use --map to compare different \
+                     meshes",
+                    count(&baseline_mesh),
+                    args.location,
+                    count(&variant_mesh)
+                )
+                .into());
+            }
+            None
+        }
+    };
+
+    let fields = if args.fields.is_empty() {
+        let variant_fields = variant.list_fields_at(args.location)?;
+        baseline
+            .list_fields_at(args.location)?
+            .into_iter()
+            .filter(|f| variant_fields.contains(f))
+            .collect()
+    } else {
+        args.fields.clone()
+    };
+    if fields.is_empty() {
+        return Err(format!("No {} fields in common to compare", args.location).into());
+    }
+
+    let mut reports = Vec::with_capacity(fields.len());
+    for field in &fields {
+        // Multi-component fields default to their magnitude
+        let component = match args.component {
+            Some(component) => Some(component),
+            None if variant.num_components_at(field, args.location)? > 1 => {
+                Some(Component::Magnitude)
+            }
+            None => None,
+        };
+        let name = match component {
+            Some(component) => format!("{}_{}", field, component),
+            None => field.clone(),
+        };
+
+        let baseline_values = baseline.scalar_field_at(field, component, args.location)?;
+        let baseline_stats = weighted_statistics(
+            &cell_values(&baseline_mesh, &baseline_values, args.location),
+            baseline_mesh.areas(),
+        )
+        .map_err(|e| format!("Baseline field '{}': {}", name, e))?;
+        let baseline_values = match &mapping {
+            Some(mapping) => mapping.apply(&baseline_values, 1),
+            None => baseline_values,
+        };
+        let variant_values = variant.scalar_field_at(field, component, args.location)?;
+        let variant_stats = weighted_statistics(
+            &cell_values(&variant_mesh, &variant_values, args.location),
+            variant_mesh.areas(),
+        )
+        .map_err(|e| format!("Variant field '{}': {}", name, e))?;
+
+        let mut differences = Vec::with_capacity(args.differences.len());
+        for &difference in &args.differences {
+            let values: Vec<f64> = baseline_values
+                .iter()
+                .zip(&variant_values)
+                .map(|(&b, &v)| difference.of(b, v, baseline_stats.mean))
+                .collect();
+            let stats = weighted_statistics(
+                &cell_values(&variant_mesh, &values, args.location),
+                variant_mesh.areas(),
+            )
+            .map_err(|e| format!("Field '{}': {}", name, e))?;
+            differences.push((difference, stats));
+
+            if !args.no_write {
+                let diff_name = format!("{}{}", name, difference.suffix());
+                if variant.field_exists_at(&diff_name, args.location)? {
+                    if !args.overwrite {
+                        return Err(format!(
+                            "Field '{}' already exists, use --overwrite to replace it",
+                            diff_name
+                        )
+                        .into());
+                    }
+                    variant = variant.remove_field_at(&diff_name, args.location)?;
+                }
+                variant =
+                    variant.add_field_as(&diff_name, &values, args.output_type, args.location)?;
+            }
+        }
+
+        reports.push(FieldReport {
+            name,
+            baseline_integral: baseline_stats.integral,
+            variant_integral: variant_stats.integral,
+            differences,
+        });
+    }
+
+    match args.format {
+        OutputFormat::Text => print_text(&reports),
+        OutputFormat::Json => print_json(&reports),
+    }
+
+    if !args.no_write {
+        let output = args.output.as_deref().unwrap_or(&args.variant);
+        if args.backup {
+            backup_file(output)?;
+        }
+        variant.write_to_file(output)?;
+    }
+
+    Ok(())
+}
+
+/// One value per cell, averaging point values onto the cells
+fn cell_values(mesh: &SurfaceMesh, values: &[f64], location: FieldLocation) -> Vec<f64> {
+    match location {
+        FieldLocation::Cell => values.to_vec(),
+        FieldLocation::Point => mesh.point_to_cell(values, 1),
+    }
+}
+
+/// Change of the variant integral relative to the baseline, in percent
+fn integral_change(report: &FieldReport) -> f64 {
+    (report.variant_integral - report.baseline_integral)
+        / report.baseline_integral.abs().max(f64::MIN_POSITIVE)
+        * 100.0
+}
+
+fn print_text(reports: &[FieldReport]) {
+    for (i, report) in reports.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        println!("Field    : {}", report.name);
+        println!(
+            "Integral : {:.6e} baseline, {:.6e} variant ({:+.2}%)",
+            report.baseline_integral,
+            report.variant_integral,
+            integral_change(report)
+        );
+        println!(
+            "{:<10} {:>14} {:>14} {:>14} {:>14} {:>14}",
+            "Delta", "Integral", "Mean", "RMS", "Min", "Max"
+        );
+        for (difference, stats) in &report.differences {
+            println!(
+                "{:<10} {:>14.6e} {:>14.6e} {:>14.6e} {:>14.6e} {:>14.6e}",
+                difference.to_string(),
+                stats.integral,
+                stats.mean,
+                stats.rms,
+                stats.min,
+                stats.max
+            );
+        }
+    }
+}
+
+fn print_json(reports: &[FieldReport]) {
+    let fields: Vec<String> = reports
+        .iter()
+        .map(|report| {
+            let differences: Vec<String> = report
+                .differences
+                .iter()
+                .map(|(difference, stats)| {
+                    format!(
+                        "\"{}\": {{\"integral\": {}, \"mean\": {}, \"rms\": {}, \"min\": {}, \
+                         \"max\": {}}}",
+                        difference,
+                        json::number(stats.integral),
+                        json::number(stats.mean),
+                        json::number(stats.rms),
+                        json::number(stats.min),
+                        json::number(stats.max)
+                    )
+                })
+                .collect();
+            format!(
+                "    {{\"name\": {}, \"baseline_integral\": {}, \"variant_integral\": {}, \
+                 \"integral_change_percent\": {}, \"differences\": {{{}}}}}",
+                json::string(&report.name),
+                json::number(report.baseline_integral),
+                json::number(report.variant_integral),
+                json::number(integral_change(report)),
+                differences.join(", ")
+            )
+        })
+        .collect();
+    println!("{{\n  \"fields\": [\n{}\n  ]\n}}", fields.join(",\n"));
+}
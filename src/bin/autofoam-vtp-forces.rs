@@ -0,0 +1,220 @@
+use std::error::Error;
+
+use autofoam::coordinates::parse_vector;
+use autofoam::coordinates::vector::format_vector;
+use autofoam::vtk::clip::ClipMode;
+use autofoam::vtk::field_manager::FieldLocation;
+use autofoam::vtk::forces::force_coefficients;
+use autofoam::vtk::forces::integrate_forces;
+use autofoam::vtk::forces::CoefficientOptions;
+use autofoam::vtk::forces::ForceOptions;
+use autofoam::vtk::region::Region;
+use autofoam::vtk::VtpProcessor;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(
+    about = "Integrates pressure and wall shear stress over a surface into forces and moments, as \
+             OpenFOAM's forces and forceCoeffs function objects do"
+)]
+pub struct Args {
+    #[arg(long, help = "Path to .vtp, legacy .vtk or .vtm file", value_hint = clap::ValueHint::FilePath)]
+    pub file: String,
+
+    #[arg(
+        long,
+        help = "Block of a .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub block: Option<String>,
+
+    #[arg(long, default_value = "p", help = "Pressure field name")]
+    pub p: String,
+
+    #[arg(
+        long,
+        default_value = "wallShearStress",
+        help = "Wall shear stress field name"
+    )]
+    pub wss: String,
+
+    #[arg(long, help = "Only integrate the pressure force")]
+    pub no_viscous: bool,
+
+    #[arg(
+        long,
+        default_value = "cell",
+        help = "Attribute location of the fields (cell or point)"
+    )]
+    pub location: FieldLocation,
+
+    #[arg(
+        long = "clip",
+        help = "Only use the surface inside this region, may be repeated: plane:ORIGIN:NORMAL \
+                (behind the plane), box:MIN:MAX, sphere:CENTRE:RADIUS or \
+                cylinder:POINT:AXIS:RADIUS, with vectors as x,y,z"
+    )]
+    pub clips: Vec<Region>,
+
+    #[arg(
+        long,
+        default_value = "centroid",
+        help = "Keep whole cells by their centroid, or cut cells at the region boundary (exact)"
+    )]
+    pub clip_mode: ClipMode,
+
+    #[arg(long, help = "Use the surface outside all --clip regions instead")]
+    pub clip_invert: bool,
+
+    #[arg(
+        long,
+        default_value_t = 1.0,
+        help = "Density for kinematic p and wallShearStress (1 if they are in Pa)"
+    )]
+    pub rho: f64,
+
+    #[arg(
+        long,
+        help = "Freestream density for coefficients (default: --rho, for kinematic fields)"
+    )]
+    pub rho_inf: Option<f64>,
+
+    #[arg(
+        long,
+        default_value_t = 0.0,
+        allow_negative_numbers = true,
+        help = "Reference pressure"
+    )]
+    pub p_ref: f64,
+
+    #[arg(
+        long,
+        default_value = "0,0,0",
+        value_parser = parse_vector,
+        allow_hyphen_values = true,
+        help = "Centre of rotation for moments, as x,y,z"
+    )]
+    pub cofr: [f64; 3],
+
+    #[arg(
+        long,
+        help = "Reverse the surface normals if they point into the fluid"
+    )]
+    pub flip_normals: bool,
+
+    #[arg(
+        long,
+        requires = "a_ref",
+        help = "Freestream velocity magnitude, to print coefficients"
+    )]
+    pub u_inf: Option<f64>,
+
+    #[arg(
+        long,
+        requires = "u_inf",
+        help = "Reference area, to print coefficients"
+    )]
+    pub a_ref: Option<f64>,
+
+    #[arg(
+        long,
+        default_value_t = 1.0,
+        help = "Reference length for moment coefficients"
+    )]
+    pub l_ref: f64,
+
+    #[arg(
+        long,
+        default_value = "1,0,0",
+        value_parser = parse_vector,
+        allow_hyphen_values = true,
+        help = "Drag direction"
+    )]
+    pub drag_dir: [f64; 3],
+
+    #[arg(
+        long,
+        default_value = "0,0,1",
+        value_parser = parse_vector,
+        allow_hyphen_values = true,
+        help = "Lift direction"
+    )]
+    pub lift_dir: [f64; 3],
+
+    #[arg(
+        long,
+        default_value = "0,1,0",
+        value_parser = parse_vector,
+        allow_hyphen_values = true,
+        help = "Pitch axis"
+    )]
+    pub pitch_axis: [f64; 3],
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    let vtp = match &args.block {
+        Some(block) => VtpProcessor::from_block(&args.file, block)?,
+        None => VtpProcessor::from_file(&args.file)?,
+    };
+    let vtp = if args.clips.is_empty() {
+        vtp
+    } else {
+        vtp.clip(&args.clips, args.clip_mode, args.clip_invert)?
+    };
+    let mesh = vtp.geometry()?;
+
+    let p = vtp.cell_field(&args.p, None, args.location)?;
+    let wss = if args.no_viscous {
+        None
+    } else {
+        let vectors = vtp.vectors_at(&args.wss, args.location)?;
+        Some(match args.location {
+            FieldLocation::Cell => vectors,
+            FieldLocation::Point => mesh
+                .point_to_cell(vectors.as_flattened(), 3)
+                .as_chunks::<3>()
+                .0
+                .to_vec(),
+        })
+    };
+
+    let options = ForceOptions {
+        rho: args.rho,
+        p_ref: args.p_ref,
+        cof_r: args.cofr,
+        flip_normals: args.flip_normals,
+    };
+    let forces = integrate_forces(&mesh, &p, wss.as_deref(), &options)?;
+
+    println!("Pressure force  : {}", format_vector(forces.pressure));
+    println!("Viscous force   : {}", format_vector(forces.viscous));
+    println!("Total force     : {}", format_vector(forces.total()));
+    println!(
+        "Pressure moment : {}",
+        format_vector(forces.pressure_moment)
+    );
+    println!("Viscous moment  : {}", format_vector(forces.viscous_moment));
+    println!("Total moment    : {}", format_vector(forces.total_moment()));
+
+    if let (Some(u_inf), Some(a_ref)) = (args.u_inf, args.a_ref) {
+        let coefficient_options = CoefficientOptions {
+            u_inf,
+            a_ref,
+            l_ref: args.l_ref,
+            rho_inf: args.rho_inf.unwrap_or(args.rho),
+            drag_dir: args.drag_dir,
+            lift_dir: args.lift_dir,
+            pitch_axis: args.pitch_axis,
+        };
+        let coefficients = force_coefficients(&forces, &coefficient_options)?;
+
+        println!("Cd              : {:.6e}", coefficients.cd);
+        println!("Cl              : {:.6e}", coefficients.cl);
+        println!("CmPitch         : {:.6e}", coefficients.cm_pitch);
+        println!("Cf              : {}", format_vector(coefficients.force));
+        println!("Cm              : {}", format_vector(coefficients.moment));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use std::error::Error;
+
+use autofoam::vtk::atomic_write::backup_file;
+use autofoam::vtk::field_manager::FieldLocation;
+use autofoam::vtk::field_mapping::FieldMapping;
+use autofoam::vtk::field_mapping::MapMethod;
+use autofoam::vtk::format::VtkFormat;
+use autofoam::vtk::VtpProcessor;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(about = "Maps fields from a source surface onto a target surface with different cells")]
+pub struct Args {
+    #[arg(long, help = "Surface to take the fields from (.vtp, legacy .vtk or .vtm)", value_hint = clap::ValueHint::FilePath)]
+    pub source: String,
+
+    #[arg(
+        long,
+        help = "Block of a source .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub source_block: Option<String>,
+
+    #[arg(long, help = "Surface to add the mapped fields to (.vtp, legacy .vtk or .vtm)", value_hint = clap::ValueHint::FilePath)]
+    pub target: String,
+
+    #[arg(
+        long,
+        help = "Block of a target .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub target_block: Option<String>,
+
+    #[arg(
+        long,
+        help = "Path to output file (default: overwrite --target)",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub output: Option<String>,
+
+    #[arg(long, help = "Keep a copy of an existing output file as <output>.bak")]
+    pub backup: bool,
+
+    #[arg(
+        long = "field",
+        help = "Field to map, may be repeated (default: every source field at --location)"
+    )]
+    pub fields: Vec<String>,
+
+    #[arg(
+        long,
+        default_value = "cell",
+        help = "Attribute location of the fields (cell or point)"
+    )]
+    pub location: FieldLocation,
+
+    #[arg(
+        long,
+        default_value = "nearest",
+        help = "Take the value of the nearest source cell centroid or point (nearest), or at the \
+                closest point of the source surface (closest-point)"
+    )]
+    pub method: MapMethod,
+
+    #[arg(
+        long,
+        help = "Leave targets further than this from the source unmapped, as NaN"
+    )]
+    pub max_distance: Option<f64>,
+
+    #[arg(
+        long,
+        default_value = "_mapped",
+        help = "Suffix appended to the names of the mapped fields"
+    )]
+    pub suffix: String,
+
+    #[arg(long, help = "Replace existing fields of the mapped names")]
+    pub overwrite: bool,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    if args.output.is_none() {
+        if args.target_block.is_some() {
+            return Err("--output is required with --target-block".into());
+        }
+        if VtkFormat::is_multiblock(&args.target) {
+            return Err("--output is required with a .vtm --target".into());
+        }
+    }
+
+    let source = match &args.source_block {
+        Some(block) => VtpProcessor::from_block(&args.source, block)?,
+        None => VtpProcessor::from_file(&args.source)?,
+    };
+    let mut target = match &args.target_block {
+        Some(block) => VtpProcessor::from_block(&args.target, block)?,
+        None => VtpProcessor::from_file(&args.target)?,
+    };
+
+    let fields = if args.fields.is_empty() {
+        source.list_fields_at(args.location)?
+    } else {
+        args.fields.clone()
+    };
+    if fields.is_empty() {
+        return Err(format!("No {} fields found in the source", args.location).into());
+    }
+
+    let mapping = FieldMapping::new(
+        &source.geometry()?,
+        &target.geometry()?,
+        args.location,
+        args.method,
+        args.max_distance,
+    )?;
+    if mapping.num_unmapped() > 0 {
+        eprintln!(
+            "{} target {}s are further than {} from the source and left as NaN",
+            mapping.num_unmapped(),
+            args.location,
+            args.max_distance.unwrap_or_default()
+        );
+    }
+
+    for field in &fields {
+        let num_comp = source.num_components_at(field, args.location)?;
+        if num_comp != 1 && num_comp != 3 {
+            eprintln!("Skipping field '{}' with {} components", field, num_comp);
+            continue;
+        }
+        let mapped = mapping.apply(&source.field_at(field, args.location)?, num_comp);
+        let name = format!("{}{}", field, args.suffix);
+
+        if target.field_exists_at(&name, args.location)? {
+            if !args.overwrite {
+                return Err(format!(
+                    "Field '{}' already exists, use --overwrite to replace it",
+                    name
+                )
+                .into());
+            }
+            target = target.remove_field_at(&name, args.location)?;
+        }
+        target = if num_comp == 1 {
+            target.add_field_at(&name, &mapped, args.location)?
+        } else {
+            let (vectors, _) = mapped.as_chunks::<3>();
+            target.add_vector_field_at(&name, vectors, args.location)?
+        };
+        println!("Mapped {} to {}", field, name);
+    }
+
+    let output = args.output.as_deref().unwrap_or(&args.target);
+    if args.backup {
+        backup_file(output)?;
+    }
+    target.write_to_file(output)?;
+
+    Ok(())
+}
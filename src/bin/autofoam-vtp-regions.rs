@@ -0,0 +1,333 @@
+use std::error::Error;
+
+use autofoam::json;
+use autofoam::statistics::area_thresholds;
+use autofoam::statistics::Direction;
+use autofoam::statistics::Threshold;
+use autofoam::statistics::ThresholdMethod;
+use autofoam::vtk::atomic_write::backup_file;
+use autofoam::vtk::connected_regions::connected_regions;
+use autofoam::vtk::data_type::DataType;
+use autofoam::vtk::field_manager::Component;
+use autofoam::vtk::field_manager::FieldLocation;
+use autofoam::vtk::format::VtkFormat;
+use autofoam::vtk::VtpProcessor;
+use clap::ArgGroup;
+use clap::Parser;
+use clap::ValueEnum;
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Parser)]
+#[command(
+    about = "Labels the connected regions of a surface, optionally of the cells meeting a field \
+             condition, and prints their areas"
+)]
+#[command(group(
+    ArgGroup::new("condition")
+        .requires("field")
+        .args(["above", "below", "between", "percentile", "area"]),
+))]
+pub struct Args {
+    #[arg(long, help = "Path to .vtp, legacy .vtk or .vtm file", value_hint = clap::ValueHint::FilePath)]
+    pub file: String,
+
+    #[arg(
+        long,
+        help = "Block of a .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub block: Option<String>,
+
+    #[arg(
+        long,
+        help = "Path to output file (default: overwrite --file)",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub output: Option<String>,
+
+    #[arg(long, help = "Keep a copy of an existing output file as <output>.bak")]
+    pub backup: bool,
+
+    #[arg(
+        long,
+        default_value = "connectedRegion",
+        help = "Name of the region id cell field, -1 for cells outside the condition"
+    )]
+    pub name: String,
+
+    #[arg(long, help = "Replace an existing field named --name")]
+    pub overwrite: bool,
+
+    #[arg(
+        long,
+        conflicts_with_all = ["output", "backup"],
+        help = "Print regions only, without writing the region id field"
+    )]
+    pub no_write: bool,
+
+    #[arg(
+        long,
+        help = "Scalar field for the condition, whose extrema are reported per region"
+    )]
+    pub field: Option<String>,
+
+    #[arg(
+        long,
+        default_value = "cell",
+        help = "Attribute location of the field (cell or point; point values are averaged onto \
+                cells)"
+    )]
+    pub location: FieldLocation,
+
+    #[arg(
+        long,
+        help = "Component of a vector or tensor field (x, y, z, mag or an index)"
+    )]
+    pub component: Option<Component>,
+
+    #[arg(
+        long,
+        allow_negative_numbers = true,
+        help = "Only label cells with values greater than this"
+    )]
+    pub above: Option<f64>,
+
+    #[arg(
+        long,
+        allow_negative_numbers = true,
+        help = "Only label cells with values less than this"
+    )]
+    pub below: Option<f64>,
+
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["MIN", "MAX"],
+        allow_negative_numbers = true,
+        help = "Only label cells with values between MIN and MAX, inclusive"
+    )]
+    pub between: Option<Vec<f64>>,
+
+    #[arg(
+        long,
+        help = "Only label the cells within this percentile (0-100) of the area, see --from"
+    )]
+    pub percentile: Option<f64>,
+
+    #[arg(long, help = "Only label the cells within this much area, see --from")]
+    pub area: Option<f64>,
+
+    #[arg(
+        long,
+        default_value = "bottom",
+        help = "For --percentile and --area, use the lowest (bottom) or highest (top) values"
+    )]
+    pub from: Direction,
+
+    #[arg(long, value_enum, default_value = "text", help = "Output format")]
+    pub format: OutputFormat,
+}
+
+struct RegionReport {
+    cells: usize,
+    area: f64,
+    centroid: [f64; 3],
+    /// Smallest and largest value of --field
+    extrema: Option<(f64, f64)>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    if !args.no_write && args.output.is_none() {
+        if args.block.is_some() {
+            return Err("--output is required with --block".into());
+        }
+        if VtkFormat::is_multiblock(&args.file) {
+            return Err("--output is required with a .vtm --file".into());
+        }
+    }
+    if let Some(between) = &args.between {
+        if between[0] > between[1] {
+            return Err("--between MIN must not exceed MAX".into());
+        }
+    }
+
+    let vtp = match &args.block {
+        Some(block) => VtpProcessor::from_block(&args.file, block)?,
+        None => VtpProcessor::from_file(&args.file)?,
+    };
+    let exists = vtp.field_exists_at(&args.name, FieldLocation::Cell)?;
+    if !args.no_write && exists && !args.overwrite {
+        return Err(format!(
+            "Field '{}' already exists, use --overwrite to replace it",
+            args.name
+        )
+        .into());
+    }
+    let mesh = vtp.geometry()?;
+    let values = match &args.field {
+        Some(field) => Some(vtp.cell_field(field, args.component, args.location)?),
+        None => None,
+    };
+
+    let threshold = if let Some(above) = args.above {
+        Some(Threshold::Above(above))
+    } else if let Some(below) = args.below {
+        Some(Threshold::Below(below))
+    } else if let Some(between) = &args.between {
+        Some(Threshold::Between(between[0], between[1]))
+    } else {
+        let area_target = match (args.percentile, args.area) {
+            (Some(percentile), _) => Some(percentile * 0.01 * mesh.total_area()),
+            (None, area) => area,
+        };
+        match (area_target, &values) {
+            (Some(area_target), Some(values)) => {
+                let value = area_thresholds(
+                    values,
+                    mesh.areas(),
+                    &[area_target],
+                    ThresholdMethod::Exact,
+                    args.from,
+                )?[0];
+                eprintln!("Threshold : {:.6e}", value);
+                Some(args.from.region(value))
+            }
+            _ => None,
+        }
+    };
+    let include = match (threshold, &values) {
+        (Some(threshold), Some(values)) => Some(threshold.select(values)),
+        _ => None,
+    };
+
+    let labels = connected_regions(&mesh, include.as_deref())?;
+    let num_regions = labels.iter().flatten().max().map_or(0, |&l| l + 1);
+
+    let mut reports: Vec<RegionReport> = (0..num_regions)
+        .map(|_| RegionReport {
+            cells: 0,
+            area: 0.0,
+            centroid: [0.0; 3],
+            extrema: None,
+        })
+        .collect();
+    for (cell, label) in labels.iter().enumerate() {
+        let Some(label) = *label else {
+            continue;
+        };
+        let report = &mut reports[label];
+        let area = mesh.areas()[cell];
+        report.cells += 1;
+        report.area += area;
+        for (sum, c) in report.centroid.iter_mut().zip(mesh.centroids()[cell]) {
+            *sum += area * c;
+        }
+        if let Some(values) = &values {
+            let value = values[cell];
+            report.extrema = match report.extrema {
+                Some((min, max)) => Some((min.min(value), max.max(value))),
+                None => Some((value, value)),
+            };
+        }
+    }
+    for report in &mut reports {
+        if report.area > 0.0 {
+            report.centroid = report.centroid.map(|c| c / report.area);
+        }
+    }
+
+    match args.format {
+        OutputFormat::Text => print_text(&reports),
+        OutputFormat::Json => print_json(&reports, args.field.as_deref()),
+    }
+
+    if !args.no_write {
+        let ids: Vec<f64> = labels
+            .iter()
+            .map(|label| label.map_or(-1.0, |l| l as f64))
+            .collect();
+        let vtp = if exists {
+            vtp.remove_field_at(&args.name, FieldLocation::Cell)?
+        } else {
+            vtp
+        };
+        let vtp = vtp.add_field_as(&args.name, &ids, DataType::Int32, FieldLocation::Cell)?;
+
+        let output = args.output.as_deref().unwrap_or(&args.file);
+        if args.backup {
+            backup_file(output)?;
+        }
+        vtp.write_to_file(output)?;
+    }
+
+    Ok(())
+}
+
+fn print_text(reports: &[RegionReport]) {
+    println!("Regions : {}", reports.len());
+    if reports.is_empty() {
+        return;
+    }
+    let with_extrema = reports[0].extrema.is_some();
+    print!(
+        "{:>6} {:>8} {:>14} {:>14} {:>14} {:>14}",
+        "Region", "Cells", "Area", "Centroid x", "Centroid y", "Centroid z"
+    );
+    if with_extrema {
+        print!(" {:>14} {:>14}", "Min", "Max");
+    }
+    println!();
+    for (i, report) in reports.iter().enumerate() {
+        print!(
+            "{:>6} {:>8} {:>14.6e} {:>14.6e} {:>14.6e} {:>14.6e}",
+            i,
+            report.cells,
+            report.area,
+            report.centroid[0],
+            report.centroid[1],
+            report.centroid[2]
+        );
+        if let Some((min, max)) = report.extrema {
+            print!(" {:>14.6e} {:>14.6e}", min, max);
+        }
+        println!();
+    }
+}
+
+fn print_json(reports: &[RegionReport], field: Option<&str>) {
+    let regions: Vec<String> = reports
+        .iter()
+        .enumerate()
+        .map(|(i, report)| {
+            let extrema = match report.extrema {
+                Some((min, max)) => format!(
+                    ", \"min\": {}, \"max\": {}",
+                    json::number(min),
+                    json::number(max)
+                ),
+                None => String::new(),
+            };
+            format!(
+                "    {{\"region\": {}, \"cells\": {}, \"area\": {}, \"centroid\": [{}, {}, {}]{}}}",
+                i,
+                report.cells,
+                json::number(report.area),
+                json::number(report.centroid[0]),
+                json::number(report.centroid[1]),
+                json::number(report.centroid[2]),
+                extrema
+            )
+        })
+        .collect();
+    println!(
+        "{{\n  \"field\": {},\n  \"regions\": [\n{}\n  ]\n}}",
+        field.map_or("null".to_string(), json::string),
+        regions.join(",\n")
+    );
+}
@@ -0,0 +1,252 @@
+use std::error::Error;
+use std::io::BufWriter;
+use std::io::Write;
+
+use autofoam::coordinates::parse_vector;
+use autofoam::vtk::atomic_write::backup_file;
+use autofoam::vtk::atomic_write::write_atomic;
+use autofoam::vtk::field_manager::FieldLocation;
+use autofoam::vtk::slice::slice_mesh;
+use autofoam::vtk::VtpProcessor;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(
+    about = "Cuts a surface with a plane and writes the section as polylines with interpolated \
+             fields and arc length"
+)]
+pub struct Args {
+    #[arg(long, help = "Path to .vtp, legacy .vtk or .vtm file", value_hint = clap::ValueHint::FilePath)]
+    pub file: String,
+
+    #[arg(
+        long,
+        help = "Block of a .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub block: Option<String>,
+
+    #[arg(
+        long,
+        value_parser = parse_vector,
+        allow_hyphen_values = true,
+        help = "A point on the slice plane, as x,y,z"
+    )]
+    pub origin: [f64; 3],
+
+    #[arg(
+        long,
+        value_parser = parse_vector,
+        allow_hyphen_values = true,
+        help = "Normal of the slice plane, as x,y,z"
+    )]
+    pub normal: [f64; 3],
+
+    #[arg(
+        long,
+        help = "Path to output file, written as CSV for .csv and as polylines otherwise",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub output: String,
+
+    #[arg(long, help = "Keep a copy of an existing output file as <output>.bak")]
+    pub backup: bool,
+
+    #[arg(
+        long = "field",
+        help = "Field to sample, may be repeated (default: every point and cell field); cell \
+                fields are averaged onto the points first"
+    )]
+    pub fields: Vec<String>,
+}
+
+/// A field interpolated onto the slice points
+struct SampledField {
+    name: String,
+    num_comp: usize,
+    values: Vec<f64>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    if args.normal.iter().all(|&n| n == 0.0) {
+        return Err("--normal must not be zero".into());
+    }
+
+    let vtp = match &args.block {
+        Some(block) => VtpProcessor::from_block(&args.file, block)?,
+        None => VtpProcessor::from_file(&args.file)?,
+    };
+    let mesh = vtp.geometry()?;
+
+    let polylines = slice_mesh(&mesh, args.origin, args.normal);
+    if polylines.is_empty() {
+        return Err("The plane does not cut the surface".into());
+    }
+
+    let point_fields = vtp.list_fields_at(FieldLocation::Point)?;
+    let cell_fields = vtp.list_fields_at(FieldLocation::Cell)?;
+    let fields = if args.fields.is_empty() {
+        let mut fields = point_fields.clone();
+        fields.extend(
+            cell_fields
+                .iter()
+                .filter(|f| !point_fields.contains(f))
+                .cloned(),
+        );
+        fields
+    } else {
+        args.fields.clone()
+    };
+
+    // Point fields take precedence over cell fields of the same name
+    let mut sources = Vec::with_capacity(fields.len());
+    for field in fields {
+        let location = if point_fields.contains(&field) {
+            FieldLocation::Point
+        } else if cell_fields.contains(&field) {
+            FieldLocation::Cell
+        } else {
+            return Err(format!("Field '{}' not found", field).into());
+        };
+        let num_comp = vtp.num_components_at(&field, location)?;
+        let values = vtp.field_at(&field, location)?;
+        let values = match location {
+            FieldLocation::Point => values,
+            FieldLocation::Cell => mesh.cell_to_point(&values, num_comp),
+        };
+        sources.push((field, num_comp, values));
+    }
+
+    let slice_points: Vec<_> = polylines.iter().flat_map(|p| p.points.iter()).collect();
+    let positions: Vec<[f64; 3]> = slice_points
+        .iter()
+        .map(|p| p.position(mesh.points()))
+        .collect();
+    let arc_lengths: Vec<f64> = polylines
+        .iter()
+        .flat_map(|p| p.arc_lengths(mesh.points()))
+        .collect();
+    let sampled: Vec<SampledField> = sources
+        .into_iter()
+        .map(|(name, num_comp, values)| SampledField {
+            name,
+            num_comp,
+            values: slice_points
+                .iter()
+                .flat_map(|p| p.interpolate(&values, num_comp))
+                .collect(),
+        })
+        .collect();
+
+    println!("Polylines : {}", polylines.len());
+    for (i, polyline) in polylines.iter().enumerate() {
+        let length = polyline
+            .arc_lengths(mesh.points())
+            .last()
+            .copied()
+            .unwrap_or(0.0);
+        println!(
+            "{:>9} : {} points, length {:.6e}{}",
+            i,
+            polyline.points.len(),
+            length,
+            if polyline.is_closed() { ", closed" } else { "" }
+        );
+    }
+
+    if args.backup {
+        backup_file(&args.output)?;
+    }
+    let polyline_ids: Vec<usize> = polylines
+        .iter()
+        .enumerate()
+        .flat_map(|(i, p)| std::iter::repeat_n(i, p.points.len()))
+        .collect();
+    if args.output.to_ascii_lowercase().ends_with(".csv") {
+        write_atomic(&args.output, |file| {
+            let mut writer = BufWriter::new(file);
+            write_csv(
+                &mut writer,
+                &polyline_ids,
+                &positions,
+                &arc_lengths,
+                &sampled,
+            )?;
+            writer.flush()?;
+            Ok(())
+        })?;
+    } else {
+        let mut start = 0;
+        let lines: Vec<Vec<usize>> = polylines
+            .iter()
+            .map(|p| {
+                let line = (start..start + p.points.len()).collect();
+                start += p.points.len();
+                line
+            })
+            .collect();
+        let mut slice = VtpProcessor::from_polylines(&positions, &lines);
+        slice = slice.add_field_at("arc_length", &arc_lengths, FieldLocation::Point)?;
+        for field in &sampled {
+            slice = match field.num_comp {
+                1 => slice.add_field_at(&field.name, &field.values, FieldLocation::Point)?,
+                3 => {
+                    let (vectors, _) = field.values.as_chunks::<3>();
+                    slice.add_vector_field_at(&field.name, vectors, FieldLocation::Point)?
+                }
+                n => {
+                    eprintln!("Skipping field '{}' with {} components", field.name, n);
+                    slice
+                }
+            };
+        }
+        slice.write_to_file(&args.output)?;
+    }
+
+    Ok(())
+}
+
+fn write_csv(
+    writer: &mut impl Write,
+    polyline_ids: &[usize],
+    positions: &[[f64; 3]],
+    arc_lengths: &[f64],
+    fields: &[SampledField],
+) -> Result<(), Box<dyn Error>> {
+    let mut header = vec![
+        "polyline".to_string(),
+        "x".to_string(),
+        "y".to_string(),
+        "z".to_string(),
+        "arc_length".to_string(),
+    ];
+    for field in fields {
+        match field.num_comp {
+            1 => header.push(field.name.clone()),
+            3 => header.extend(["x", "y", "z"].map(|c| format!("{}_{}", field.name, c))),
+            n => header.extend((0..n).map(|c| format!("{}_{}", field.name, c))),
+        }
+    }
+    writeln!(writer, "{}", header.join(","))?;
+
+    for (i, position) in positions.iter().enumerate() {
+        let mut row = vec![
+            polyline_ids[i].to_string(),
+            position[0].to_string(),
+            position[1].to_string(),
+            position[2].to_string(),
+            arc_lengths[i].to_string(),
+        ];
+        for field in fields {
+            let n = field.num_comp;
+            row.extend(
+                field.values[i * n..(i + 1) * n]
+                    .iter()
+                    .map(|v| v.to_string()),
+            );
+        }
+        writeln!(writer, "{}", row.join(","))?;
+    }
+    Ok(())
+}
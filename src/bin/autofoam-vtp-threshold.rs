@@ -0,0 +1,181 @@
+use std::error::Error;
+
+use autofoam::statistics::area_thresholds;
+use autofoam::statistics::Direction;
+use autofoam::statistics::Threshold;
+use autofoam::statistics::ThresholdMethod;
+use autofoam::vtk::atomic_write::backup_file;
+use autofoam::vtk::clip::ClipMode;
+use autofoam::vtk::field_manager::Component;
+use autofoam::vtk::field_manager::FieldLocation;
+use autofoam::vtk::region::Region;
+use autofoam::vtk::VtpProcessor;
+use clap::ArgGroup;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(
+    about = "Writes the cells of a surface whose field values meet a condition to a new file"
+)]
+#[command(group(
+    ArgGroup::new("condition")
+        .required(true)
+        .args(["above", "below", "between", "percentile", "area"]),
+))]
+pub struct Args {
+    #[arg(long, help = "Path to .vtp, legacy .vtk or .vtm file", value_hint = clap::ValueHint::FilePath)]
+    pub file: String,
+
+    #[arg(
+        long,
+        help = "Block of a .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub block: Option<String>,
+
+    #[arg(long, help = "Path to output file", value_hint = clap::ValueHint::FilePath)]
+    pub output: String,
+
+    #[arg(long, help = "Keep a copy of an existing output file as <output>.bak")]
+    pub backup: bool,
+
+    #[arg(long, help = "Scalar field name")]
+    pub field: String,
+
+    #[arg(
+        long,
+        default_value = "cell",
+        help = "Attribute location of the field (cell or point; point values are averaged onto \
+                cells)"
+    )]
+    pub location: FieldLocation,
+
+    #[arg(
+        long,
+        help = "Component of a vector or tensor field (x, y, z, mag or an index)"
+    )]
+    pub component: Option<Component>,
+
+    #[arg(
+        long = "clip",
+        help = "Only use the surface inside this region, may be repeated: plane:ORIGIN:NORMAL \
+                (behind the plane), box:MIN:MAX, sphere:CENTRE:RADIUS or \
+                cylinder:POINT:AXIS:RADIUS, with vectors as x,y,z"
+    )]
+    pub clips: Vec<Region>,
+
+    #[arg(
+        long,
+        default_value = "centroid",
+        help = "Keep whole cells by their centroid, or cut cells at the region boundary (exact)"
+    )]
+    pub clip_mode: ClipMode,
+
+    #[arg(long, help = "Use the surface outside all --clip regions instead")]
+    pub clip_invert: bool,
+
+    #[arg(
+        long,
+        allow_negative_numbers = true,
+        help = "Keep cells with values greater than this"
+    )]
+    pub above: Option<f64>,
+
+    #[arg(
+        long,
+        allow_negative_numbers = true,
+        help = "Keep cells with values less than this"
+    )]
+    pub below: Option<f64>,
+
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["MIN", "MAX"],
+        allow_negative_numbers = true,
+        help = "Keep cells with values between MIN and MAX, inclusive"
+    )]
+    pub between: Option<Vec<f64>>,
+
+    #[arg(
+        long,
+        help = "Keep the cells within this percentile (0-100) of the area, see --from"
+    )]
+    pub percentile: Option<f64>,
+
+    #[arg(long, help = "Keep the cells within this much area, see --from")]
+    pub area: Option<f64>,
+
+    #[arg(
+        long,
+        default_value = "bottom",
+        help = "For --percentile and --area, keep the lowest (bottom) or highest (top) values"
+    )]
+    pub from: Direction,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    if let Some(between) = &args.between {
+        if between[0] > between[1] {
+            return Err("--between MIN must not exceed MAX".into());
+        }
+    }
+
+    let vtp = match &args.block {
+        Some(block) => VtpProcessor::from_block(&args.file, block)?,
+        None => VtpProcessor::from_file(&args.file)?,
+    };
+    let vtp = if args.clips.is_empty() {
+        vtp
+    } else {
+        vtp.clip(&args.clips, args.clip_mode, args.clip_invert)?
+    };
+    let mesh = vtp.geometry()?;
+    let values = vtp.cell_field(&args.field, args.component, args.location)?;
+
+    let threshold = if let Some(above) = args.above {
+        Threshold::Above(above)
+    } else if let Some(below) = args.below {
+        Threshold::Below(below)
+    } else if let Some(between) = &args.between {
+        Threshold::Between(between[0], between[1])
+    } else {
+        let area_target = match (args.percentile, args.area) {
+            (Some(percentile), _) => percentile * 0.01 * mesh.total_area(),
+            (None, Some(area)) => area,
+            (None, None) => unreachable!("clap requires a condition"),
+        };
+        let value = area_thresholds(
+            &values,
+            mesh.areas(),
+            &[area_target],
+            ThresholdMethod::Exact,
+            args.from,
+        )?[0];
+        println!("Threshold : {:.6e}", value);
+        args.from.region(value)
+    };
+
+    let keep = threshold.select(&values);
+    let kept_area: f64 = keep
+        .iter()
+        .zip(mesh.areas())
+        .filter(|(&k, _)| k)
+        .map(|(_, area)| area)
+        .sum();
+    println!(
+        "Cells     : {} of {}",
+        keep.iter().filter(|&&k| k).count(),
+        mesh.num_cells()
+    );
+    println!("Area      : {:.6e} of {:.6e}", kept_area, mesh.total_area());
+
+    let extracted = vtp.extract_cells(&keep)?;
+    if args.backup {
+        backup_file(&args.output)?;
+    }
+    extracted.write_to_file(&args.output)?;
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+
+use autofoam::stl::write_ascii_stl;
+use autofoam::surface::write_surface;
+use autofoam::surface::SurfaceFormat;
+use autofoam::vtk::VtpProcessor;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(
+    about = "Extracts the polygons of a .vtp or .vtk as triangles into a surface file (.stl, \
+             .obj, .ply, .off)"
+)]
+pub struct Args {
+    #[arg(long, help = "Path to .vtp, legacy .vtk or .vtm file", value_hint = clap::ValueHint::FilePath)]
+    pub file: String,
+
+    #[arg(
+        long,
+        help = "Block of a .vtm file to use (default: all surface blocks merged)"
+    )]
+    pub block: Option<String>,
+
+    #[arg(long, help = "Path to output surface file", value_hint = clap::ValueHint::FilePath)]
+    pub output: String,
+
+    #[arg(long, help = "Write ASCII instead of binary STL (.stl output only)")]
+    pub ascii: bool,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    if args.ascii && SurfaceFormat::from_path(&args.output)? != SurfaceFormat::Stl {
+        return Err("--ascii is only supported for .stl output".into());
+    }
+
+    let vertices = match &args.block {
+        Some(block) => VtpProcessor::from_block(&args.file, block)?,
+        None => VtpProcessor::from_file(&args.file)?,
+    }
+    .triangles()?;
+
+    if args.ascii {
+        let mut writer = BufWriter::new(File::create(&args.output)?);
+        write_ascii_stl(&mut writer, &vertices, "autofoam")?;
+        writer.flush()?;
+    } else {
+        write_surface(&args.output, &vertices)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+use std::error::Error;
+
+use super::BinaryOp;
+use super::Expr;
+use super::Function;
+use super::Value;
+use crate::coordinates::vector::cross;
+use crate::coordinates::vector::dot;
+use crate::coordinates::vector::length;
+use crate::coordinates::vector::unit;
+
+impl Expr {
+    /// Evaluates the expression, looking up fields and constants by name.
+    pub fn evaluate(
+        &self,
+        variables: &impl Fn(&str) -> Option<Value>,
+    ) -> Result<Value, Box<dyn Error>> {
+        match self {
+            Expr::Number(n) => Ok(Value::Scalar(*n)),
+            Expr::Variable(name) => variables(name)
+                .ok_or_else(|| format!("Unknown field or constant '{}'", name).into()),
+            Expr::Vector(components) => {
+                let mut vector = [0.0; 3];
+                for (v, expr) in vector.iter_mut().zip(components.iter()) {
+                    *v = expr.evaluate(variables)?.scalar("a vector component")?;
+                }
+                Ok(Value::Vector(vector))
+            }
+            Expr::Neg(expr) => Ok(match expr.evaluate(variables)? {
+                Value::Scalar(a) => Value::Scalar(-a),
+                Value::Vector(a) => Value::Vector(a.map(|x| -x)),
+            }),
+            Expr::Binary(op, lhs, rhs) => {
+                binary(*op, lhs.evaluate(variables)?, rhs.evaluate(variables)?)
+            }
+            Expr::Component(expr, index) => match expr.evaluate(variables)? {
+                Value::Vector(a) => Ok(Value::Scalar(a[*index])),
+                Value::Scalar(_) => Err("Cannot take a component of a scalar".into()),
+            },
+            Expr::Call(function, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.evaluate(variables))
+                    .collect::<Result<Vec<_>, _>>()?;
+                call(*function, &args)
+            }
+        }
+    }
+
+    /// Names of the fields and constants the expression refers to.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.collect_variables(&mut names);
+        names
+    }
+
+    fn collect_variables<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            Expr::Number(_) => {}
+            Expr::Variable(name) => {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+            Expr::Vector(components) => components
+                .iter()
+                .for_each(|expr| expr.collect_variables(names)),
+            Expr::Neg(expr) | Expr::Component(expr, _) => expr.collect_variables(names),
+            Expr::Binary(_, lhs, rhs) => {
+                lhs.collect_variables(names);
+                rhs.collect_variables(names);
+            }
+            Expr::Call(_, args) => args.iter().for_each(|expr| expr.collect_variables(names)),
+        }
+    }
+}
+
+impl Value {
+    fn scalar(self, context: &str) -> Result<f64, Box<dyn Error>> {
+        match self {
+            Value::Scalar(a) => Ok(a),
+            Value::Vector(_) => Err(format!("Expected a scalar for {}", context).into()),
+        }
+    }
+
+    fn vector(self, context: &str) -> Result<[f64; 3], Box<dyn Error>> {
+        match self {
+            Value::Vector(a) => Ok(a),
+            Value::Scalar(_) => Err(format!("Expected a vector for {}", context).into()),
+        }
+    }
+}
+
+fn binary(op: BinaryOp, lhs: Value, rhs: Value) -> Result<Value, Box<dyn Error>> {
+    use Value::Scalar;
+    use Value::Vector;
+
+    match (op, lhs, rhs) {
+        (BinaryOp::Add, Scalar(a), Scalar(b)) => Ok(Scalar(a + b)),
+        (BinaryOp::Sub, Scalar(a), Scalar(b)) => Ok(Scalar(a - b)),
+        (BinaryOp::Mul, Scalar(a), Scalar(b)) => Ok(Scalar(a * b)),
+        (BinaryOp::Div, Scalar(a), Scalar(b)) => Ok(Scalar(a / b)),
+        (BinaryOp::Pow, Scalar(a), Scalar(b)) => Ok(Scalar(a.powf(b))),
+        (BinaryOp::Add, Vector(a), Vector(b)) => {
+            Ok(Vector([a[0] + b[0], a[1] + b[1], a[2] + b[2]]))
+        }
+        (BinaryOp::Sub, Vector(a), Vector(b)) => {
+            Ok(Vector([a[0] - b[0], a[1] - b[1], a[2] - b[2]]))
+        }
+        (BinaryOp::Mul, Vector(a), Scalar(b)) | (BinaryOp::Mul, Scalar(b), Vector(a)) => {
+            Ok(Vector(a.map(|x| x * b)))
+        }
+        (BinaryOp::Div, Vector(a), Scalar(b)) => Ok(Vector(a.map(|x| x / b))),
+        (BinaryOp::Mul, Vector(_), Vector(_)) => {
+            Err("Cannot multiply two vectors, use dot() or cross()".into())
+        }
+        (op, _, _) => Err(format!("Unsupported operands for {:?}", op).into()),
+    }
+}
+
+fn call(function: Function, args: &[Value]) -> Result<Value, Box<dyn Error>> {
+    let context = format!("{}()", function);
+    let scalar = |i: usize| args[i].scalar(&context);
+    let vector = |i: usize| args[i].vector(&context);
+
+    let value = match function {
+        Function::Sqrt => Value::Scalar(scalar(0)?.sqrt()),
+        Function::Exp => Value::Scalar(scalar(0)?.exp()),
+        Function::Log => Value::Scalar(scalar(0)?.ln()),
+        Function::Log10 => Value::Scalar(scalar(0)?.log10()),
+        Function::Sin => Value::Scalar(scalar(0)?.sin()),
+        Function::Cos => Value::Scalar(scalar(0)?.cos()),
+        Function::Tan => Value::Scalar(scalar(0)?.tan()),
+        Function::Asin => Value::Scalar(scalar(0)?.asin()),
+        Function::Acos => Value::Scalar(scalar(0)?.acos()),
+        Function::Atan => Value::Scalar(scalar(0)?.atan()),
+        Function::Atan2 => Value::Scalar(scalar(0)?.atan2(scalar(1)?)),
+        Function::Pow => Value::Scalar(scalar(0)?.powf(scalar(1)?)),
+        Function::Min => Value::Scalar(scalar(0)?.min(scalar(1)?)),
+        Function::Max => Value::Scalar(scalar(0)?.max(scalar(1)?)),
+        Function::Abs | Function::Mag => match args[0] {
+            Value::Scalar(a) => Value::Scalar(a.abs()),
+            Value::Vector(a) => Value::Scalar(length(a)),
+        },
+        Function::MagSqr => match args[0] {
+            Value::Scalar(a) => Value::Scalar(a * a),
+            Value::Vector(a) => Value::Scalar(dot(a, a)),
+        },
+        Function::Dot => Value::Scalar(dot(vector(0)?, vector(1)?)),
+        Function::Cross => Value::Vector(cross(vector(0)?, vector(1)?)),
+        Function::Normalize => Value::Vector(unit(vector(0)?)),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calculator::parse_expression;
+
+    fn eval(input: &str) -> Result<Value, Box<dyn Error>> {
+        let variables = |name: &str| match name {
+            "p" => Some(Value::Scalar(3.0)),
+            "pInf" => Some(Value::Scalar(1.0)),
+            "U" => Some(Value::Vector([3.0, 0.0, 4.0])),
+            _ => None,
+        };
+        parse_expression(input)?.evaluate(&variables)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(eval("1 + 2 * 3").unwrap(), Value::Scalar(7.0));
+        assert_eq!(eval("(1 + 2) * 3").unwrap(), Value::Scalar(9.0));
+        assert_eq!(eval("2^3^2").unwrap(), Value::Scalar(512.0));
+        assert_eq!(eval("-2^2").unwrap(), Value::Scalar(-4.0));
+        assert_eq!(eval("8 / 2 / 2").unwrap(), Value::Scalar(2.0));
+    }
+
+    #[test]
+    fn test_pressure_coefficient() {
+        assert_eq!(
+            eval("(p - pInf)/(0.5*mag(U)^2)").unwrap(),
+            Value::Scalar(2.0 / 12.5)
+        );
+    }
+
+    #[test]
+    fn test_vector_operations() {
+        assert_eq!(eval("mag(U)").unwrap(), Value::Scalar(5.0));
+        assert_eq!(eval("magSqr(U)").unwrap(), Value::Scalar(25.0));
+        assert_eq!(eval("U.z").unwrap(), Value::Scalar(4.0));
+        assert_eq!(eval("2*U").unwrap(), Value::Vector([6.0, 0.0, 8.0]));
+        assert_eq!(
+            eval("U - [1, 0, 0]").unwrap(),
+            Value::Vector([2.0, 0.0, 4.0])
+        );
+        assert_eq!(eval("dot(U, [0, 0, 1])").unwrap(), Value::Scalar(4.0));
+        assert_eq!(
+            eval("cross([1, 0, 0], [0, 1, 0])").unwrap(),
+            Value::Vector([0.0, 0.0, 1.0])
+        );
+        assert_eq!(
+            eval("normalize(U)").unwrap(),
+            Value::Vector([0.6, 0.0, 0.8])
+        );
+    }
+
+    #[test]
+    fn test_type_errors() {
+        assert!(eval("U + 1").is_err());
+        assert!(eval("U * U").is_err());
+        assert!(eval("p.x").is_err());
+        assert!(eval("sqrt(U)").is_err());
+        assert!(eval("unknown + 1").is_err());
+    }
+
+    #[test]
+    fn test_variables() {
+        let expr = parse_expression("(p - pInf)/(0.5*mag(U)^2) + p").unwrap();
+        assert_eq!(expr.variables(), vec!["p", "pInf", "U"]);
+    }
+}
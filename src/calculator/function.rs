@@ -0,0 +1,81 @@
+use std::fmt;
+
+/// Built-in functions, named as in OpenFOAM where it has them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Function {
+    Sqrt,
+    Abs,
+    Exp,
+    Log,
+    Log10,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Pow,
+    Min,
+    Max,
+    Mag,
+    MagSqr,
+    Dot,
+    Cross,
+    Normalize,
+}
+
+const NAMES: [(&str, Function); 20] = [
+    ("sqrt", Function::Sqrt),
+    ("abs", Function::Abs),
+    ("exp", Function::Exp),
+    ("log", Function::Log),
+    ("log10", Function::Log10),
+    ("sin", Function::Sin),
+    ("cos", Function::Cos),
+    ("tan", Function::Tan),
+    ("asin", Function::Asin),
+    ("acos", Function::Acos),
+    ("atan", Function::Atan),
+    ("atan2", Function::Atan2),
+    ("pow", Function::Pow),
+    ("min", Function::Min),
+    ("max", Function::Max),
+    ("mag", Function::Mag),
+    ("magSqr", Function::MagSqr),
+    ("dot", Function::Dot),
+    ("cross", Function::Cross),
+    ("normalize", Function::Normalize),
+];
+
+impl Function {
+    pub fn from_name(name: &str) -> Option<Self> {
+        NAMES.iter().find(|(n, _)| *n == name).map(|&(_, f)| f)
+    }
+
+    pub fn name(self) -> &'static str {
+        NAMES
+            .iter()
+            .find(|(_, f)| *f == self)
+            .map(|&(n, _)| n)
+            .unwrap()
+    }
+
+    pub fn arity(self) -> usize {
+        match self {
+            Function::Atan2
+            | Function::Pow
+            | Function::Min
+            | Function::Max
+            | Function::Dot
+            | Function::Cross => 2,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
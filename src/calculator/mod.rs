@@ -0,0 +1,35 @@
+/// The value of an expression at one cell or point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Scalar(f64),
+    Vector([f64; 3]),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    /// A field or constant
+    Variable(String),
+    /// `[x, y, z]`
+    Vector(Box<[Expr; 3]>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// `U.x`, `U.y` or `U.z`
+    Component(Box<Expr>, usize),
+    Call(Function, Vec<Expr>),
+}
+
+pub mod evaluate;
+pub mod function;
+pub use function::Function;
+pub mod parse_expression;
+pub use parse_expression::parse_expression;
@@ -0,0 +1,353 @@
+use std::error::Error;
+
+use super::BinaryOp;
+use super::Expr;
+use super::Function;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    /// A field name in braces, which may contain any character but `}`
+    Field(String),
+    Op(char),
+}
+
+/// Parses an arithmetic expression such as `(p - pInf)/(0.5*mag(U)^2)`.
+///
+/// Supports `+ - * / ^`, parentheses, vector literals `[x, y, z]`,
+/// components `U.x`, the functions of [`Function`] and identifiers naming
+/// fields or constants. Field names that are not identifiers, such as
+/// `grad(p)`, are written in braces as `{grad(p)}`.
+pub fn parse_expression(input: &str) -> Result<Expr, Box<dyn Error>> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        position: 0,
+    };
+    let expr = parser.expression()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("Unexpected {} after the expression", describe(token)).into()),
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, Box<dyn Error>> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit()))
+        {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Exponent, only when digits follow so that `2e` stays an error
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let number = text
+                .parse()
+                .map_err(|_| format!("Invalid number '{}'", text))?;
+            tokens.push(Token::Number(number));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else if c == '{' {
+            let start = i + 1;
+            let len = chars[start..]
+                .iter()
+                .position(|&d| d == '}')
+                .ok_or("Unterminated '{' in the expression")?;
+            let name: String = chars[start..start + len].iter().collect();
+            if name.trim().is_empty() {
+                return Err("Empty field name in '{}'".into());
+            }
+            tokens.push(Token::Field(name));
+            i = start + len + 1;
+        } else if "+-*/^()[],.".contains(c) {
+            tokens.push(Token::Op(c));
+            i += 1;
+        } else {
+            return Err(format!("Unexpected character '{}'", c).into());
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Number(n) => format!("number {}", n),
+        Token::Ident(name) => format!("'{}'", name),
+        Token::Field(name) => format!("'{{{}}}'", name),
+        Token::Op(c) => format!("'{}'", c),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn eat(&mut self, op: char) -> bool {
+        if self.peek() == Some(&Token::Op(op)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: char) -> Result<(), Box<dyn Error>> {
+        match self.next() {
+            Some(Token::Op(c)) if c == op => Ok(()),
+            Some(token) => Err(format!("Expected '{}' but found {}", op, describe(&token)).into()),
+            None => Err(format!("Expected '{}' but the expression ended", op).into()),
+        }
+    }
+
+    /// term (('+' | '-') term)*
+    fn expression(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let mut expr = self.term()?;
+        loop {
+            let op = if self.eat('+') {
+                BinaryOp::Add
+            } else if self.eat('-') {
+                BinaryOp::Sub
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.term()?));
+        }
+    }
+
+    /// unary (('*' | '/') unary)*
+    fn term(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = if self.eat('*') {
+                BinaryOp::Mul
+            } else if self.eat('/') {
+                BinaryOp::Div
+            } else {
+                return Ok(expr);
+            };
+            expr = Expr::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    /// '-' unary | '+' unary | power
+    fn unary(&mut self) -> Result<Expr, Box<dyn Error>> {
+        if self.eat('-') {
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    /// postfix ('^' unary)?, right associative so that `-2^2` is -4 and
+    /// `2^3^2` is 512
+    fn power(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let base = self.postfix()?;
+        if self.eat('^') {
+            Ok(Expr::Binary(
+                BinaryOp::Pow,
+                Box::new(base),
+                Box::new(self.unary()?),
+            ))
+        } else {
+            Ok(base)
+        }
+    }
+
+    /// primary ('.' ('x' | 'y' | 'z'))*
+    fn postfix(&mut self) -> Result<Expr, Box<dyn Error>> {
+        let mut expr = self.primary()?;
+        while self.eat('.') {
+            let index = match self.next() {
+                Some(Token::Ident(name)) if name == "x" => 0,
+                Some(Token::Ident(name)) if name == "y" => 1,
+                Some(Token::Ident(name)) if name == "z" => 2,
+                Some(token) => {
+                    return Err(format!(
+                        "Expected x, y or z after '.' but found {}",
+                        describe(&token)
+                    )
+                    .into())
+                }
+                None => return Err("Expected x, y or z after '.'".into()),
+            };
+            expr = Expr::Component(Box::new(expr), index);
+        }
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, Box<dyn Error>> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Field(name)) => Ok(Expr::Variable(name)),
+            Some(Token::Ident(name)) => {
+                if !self.eat('(') {
+                    return Ok(Expr::Variable(name));
+                }
+                let function = Function::from_name(&name)
+                    .ok_or_else(|| format!("Unknown function '{}'", name))?;
+                let mut args = Vec::new();
+                if !self.eat(')') {
+                    loop {
+                        args.push(self.expression()?);
+                        if self.eat(')') {
+                            break;
+                        }
+                        self.expect(',')?;
+                    }
+                }
+                if args.len() != function.arity() {
+                    return Err(format!(
+                        "Function '{}' takes {} argument(s) but got {}",
+                        function,
+                        function.arity(),
+                        args.len()
+                    )
+                    .into());
+                }
+                Ok(Expr::Call(function, args))
+            }
+            Some(Token::Op('(')) => {
+                let expr = self.expression()?;
+                self.expect(')')?;
+                Ok(expr)
+            }
+            Some(Token::Op('[')) => {
+                let x = self.expression()?;
+                self.expect(',')?;
+                let y = self.expression()?;
+                self.expect(',')?;
+                let z = self.expression()?;
+                self.expect(']')?;
+                Ok(Expr::Vector(Box::new([x, y, z])))
+            }
+            Some(token) => Err(format!("Unexpected {}", describe(&token)).into()),
+            None => Err("Unexpected end of the expression".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(name: &str) -> Box<Expr> {
+        Box::new(Expr::Variable(name.to_string()))
+    }
+
+    fn num(n: f64) -> Box<Expr> {
+        Box::new(Expr::Number(n))
+    }
+
+    #[test]
+    fn test_precedence() {
+        assert_eq!(
+            parse_expression("a + b * 2").unwrap(),
+            Expr::Binary(
+                BinaryOp::Add,
+                var("a"),
+                Box::new(Expr::Binary(BinaryOp::Mul, var("b"), num(2.0)))
+            )
+        );
+        assert_eq!(
+            parse_expression("-2^2").unwrap(),
+            Expr::Neg(Box::new(Expr::Binary(BinaryOp::Pow, num(2.0), num(2.0))))
+        );
+    }
+
+    #[test]
+    fn test_left_associative() {
+        assert_eq!(
+            parse_expression("a - b - c").unwrap(),
+            Expr::Binary(
+                BinaryOp::Sub,
+                Box::new(Expr::Binary(BinaryOp::Sub, var("a"), var("b"))),
+                var("c")
+            )
+        );
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(parse_expression("1.5e-3").unwrap(), *num(1.5e-3));
+        assert_eq!(parse_expression(".5").unwrap(), *num(0.5));
+        assert_eq!(parse_expression("2E+2").unwrap(), *num(200.0));
+    }
+
+    #[test]
+    fn test_functions_and_components() {
+        assert_eq!(
+            parse_expression("mag(U) + U.x").unwrap(),
+            Expr::Binary(
+                BinaryOp::Add,
+                Box::new(Expr::Call(Function::Mag, vec![*var("U")])),
+                Box::new(Expr::Component(var("U"), 0))
+            )
+        );
+        assert_eq!(
+            parse_expression("[1, 0, p_rgh]").unwrap(),
+            Expr::Vector(Box::new([*num(1.0), *num(0.0), *var("p_rgh")]))
+        );
+    }
+
+    #[test]
+    fn test_braced_fields() {
+        assert_eq!(
+            parse_expression("mag({grad(p)}) + {grad(p)}.x").unwrap(),
+            Expr::Binary(
+                BinaryOp::Add,
+                Box::new(Expr::Call(Function::Mag, vec![*var("grad(p)")])),
+                Box::new(Expr::Component(var("grad(p)"), 0))
+            )
+        );
+        assert_eq!(parse_expression("{p}").unwrap(), *var("p"));
+    }
+
+    #[test]
+    fn test_errors() {
+        for input in [
+            "", "1 +", "(1", "1 2", "foo(1)", "max(1)", "U.w", "p $ 2", "[1, 2]", "{grad(p)", "{}",
+            "{p}(1)",
+        ] {
+            assert!(parse_expression(input).is_err(), "{}", input);
+        }
+    }
+}
@@ -1,2 +1,5 @@
 pub mod update_bounds;
 pub use update_bounds::update_coordinate_bounds;
+pub mod parse_vector;
+pub use parse_vector::parse_vector;
+pub mod vector;
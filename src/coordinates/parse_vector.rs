@@ -0,0 +1,42 @@
+/// Parses a vector given as "x,y,z" or in OpenFOAM style as "(x y z)".
+pub fn parse_vector(s: &str) -> Result<[f64; 3], String> {
+    let inner = s.trim().trim_start_matches('(').trim_end_matches(')');
+    let components: Vec<&str> = inner
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    match components.as_slice() {
+        [x, y, z] => {
+            let parse = |v: &str| {
+                v.parse::<f64>()
+                    .map_err(|_| format!("Invalid vector component '{}' in '{}'", v, s))
+            };
+            Ok([parse(x)?, parse(y)?, parse(z)?])
+        }
+        _ => Err(format!("Expected three components in '{}'", s)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comma_separated() {
+        assert_eq!(parse_vector("1,-2.5,3e2"), Ok([1.0, -2.5, 300.0]));
+        assert_eq!(parse_vector(" 1, 2, 3 "), Ok([1.0, 2.0, 3.0]));
+    }
+
+    #[test]
+    fn test_openfoam_style() {
+        assert_eq!(parse_vector("(0 0 1)"), Ok([0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(parse_vector("1,2").is_err());
+        assert!(parse_vector("1,2,3,4").is_err());
+        assert!(parse_vector("1,a,3").is_err());
+    }
+}
@@ -0,0 +1,55 @@
+pub fn add(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn scale(a: [f64; 3], factor: f64) -> [f64; 3] {
+    a.map(|x| x * factor)
+}
+
+pub fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn length(a: [f64; 3]) -> f64 {
+    dot(a, a).sqrt()
+}
+
+/// `a` scaled to unit length, or zero if it has none.
+pub fn unit(a: [f64; 3]) -> [f64; 3] {
+    let length = length(a);
+    if length > 0.0 {
+        a.map(|x| x / length)
+    } else {
+        [0.0; 3]
+    }
+}
+
+/// Formats `a` in OpenFOAM style as "(x y z)".
+pub fn format_vector(a: [f64; 3]) -> String {
+    format!("({:.6e} {:.6e} {:.6e})", a[0], a[1], a[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_products() {
+        assert_eq!(dot([1.0, 2.0, 3.0], [4.0, -5.0, 6.0]), 12.0);
+        assert_eq!(cross([1.0, 0.0, 0.0], [0.0, 1.0, 0.0]), [0.0, 0.0, 1.0]);
+        assert_eq!(unit([0.0, 0.0, -4.0]), [0.0, 0.0, -1.0]);
+        assert_eq!(unit([0.0; 3]), [0.0; 3]);
+    }
+}
@@ -0,0 +1,44 @@
+//! Minimal helpers for the JSON output of the command line tools.
+
+/// A JSON number; JSON has no NaN or infinity, so those become `null`.
+pub fn number(value: f64) -> String {
+    if value.is_finite() {
+        format!("{:e}", value)
+    } else {
+        "null".to_string()
+    }
+}
+
+/// A quoted and escaped JSON string.
+pub fn string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number(1.5), "1.5e0");
+        assert_eq!(number(f64::NAN), "null");
+        assert_eq!(number(f64::INFINITY), "null");
+    }
+
+    #[test]
+    fn test_string() {
+        assert_eq!(string("p"), "\"p\"");
+        assert_eq!(string("a\"b\\c\n"), "\"a\\\"b\\\\c\\u000a\"");
+    }
+}
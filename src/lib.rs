@@ -1,5 +1,12 @@
+pub mod calculator;
 pub mod coordinates;
 pub mod histogram;
 pub mod interpolation;
+pub mod json;
+pub mod obj;
+pub mod off;
+pub mod ply;
+pub mod statistics;
 pub mod stl;
+pub mod surface;
 pub mod vtk;
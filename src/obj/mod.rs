@@ -0,0 +1,4 @@
+pub mod process_obj_iter;
+pub use process_obj_iter::process_obj_iter;
+pub mod write_obj;
+pub use write_obj::write_obj;
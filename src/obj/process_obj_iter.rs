@@ -0,0 +1,250 @@
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+
+use crate::surface::fan_triangulate;
+
+pub fn process_obj_iter(
+    file: File,
+) -> impl Iterator<Item = Result<[f32; 3], Box<dyn std::error::Error>>> {
+    let reader = BufReader::new(file);
+    let mut vertices: Vec<[f32; 3]> = Vec::new();
+    let mut faces: Vec<Result<Vec<isize>, Box<dyn std::error::Error>>> = Vec::new();
+    let mut results: Vec<Result<[f32; 3], Box<dyn std::error::Error>>> = Vec::new();
+
+    for line_result in reader.lines() {
+        let line = match line_result {
+            Ok(line) => line,
+            Err(e) => {
+                results.push(Err(e.into()));
+                break;
+            }
+        };
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("v") => match (parts.next(), parts.next(), parts.next()) {
+                (Some(x_str), Some(y_str), Some(z_str)) => {
+                    match (
+                        x_str.parse::<f32>(),
+                        y_str.parse::<f32>(),
+                        z_str.parse::<f32>(),
+                    ) {
+                        // Keep the vertex list aligned with the file even if
+                        // a coordinate is broken, so later indices stay valid
+                        (Ok(x), Ok(y), Ok(z)) => vertices.push([x, y, z]),
+                        _ => {
+                            vertices.push([f32::NAN; 3]);
+                            faces.push(Err("Invalid vertex coordinate".into()));
+                        }
+                    }
+                }
+                _ => {
+                    vertices.push([f32::NAN; 3]);
+                    faces.push(Err("Incomplete vertex coordinate".into()));
+                }
+            },
+            Some("f") => {
+                // https://en.wikipedia.org/wiki/Wavefront_.obj_file
+                // "f v1/vt1/vn1 v2/vt2/vn2 v3/vt3/vn3 ..."
+                // Only the vertex index (before the first '/') is used.
+                // "Negative values indicate relative vertex numbers", counted
+                // back from the vertices defined before this face.
+                let vertex_count = vertices.len() as isize;
+                let indices: Result<Vec<isize>, _> = parts
+                    .map(|part| {
+                        let index = part.split('/').next().unwrap_or("").parse::<isize>();
+                        index.map(|i| if i < 0 { vertex_count + i + 1 } else { i })
+                    })
+                    .collect();
+                faces.push(indices.map_err(|_| "Invalid face index".into()));
+            }
+            _ => {}
+        }
+    }
+
+    // Errors and the triangles of valid faces are reported in file order
+    for face in faces {
+        match face.and_then(|indices| resolve_indices(&indices, vertices.len())) {
+            Ok(face) => {
+                for triangle in fan_triangulate(&face) {
+                    results.extend(triangle.iter().map(|&i| Ok(vertices[i])));
+                }
+            }
+            Err(e) => results.push(Err(e)),
+        }
+    }
+
+    results.into_iter()
+}
+
+fn resolve_indices(
+    indices: &[isize],
+    vertex_count: usize,
+) -> Result<Vec<usize>, Box<dyn std::error::Error>> {
+    // Indices are 1-based, relative ones were made absolute while parsing
+    indices
+        .iter()
+        .map(|&i| {
+            let resolved = i - 1;
+            if resolved < 0 || resolved as usize >= vertex_count {
+                Err("Face index out of range".into())
+            } else {
+                Ok(resolved as usize)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Seek;
+    use std::io::SeekFrom;
+    use std::io::Write;
+
+    use tempfile::tempfile;
+
+    use super::*;
+
+    fn create_test_file(content: &str) -> File {
+        let mut file = tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_single_triangle() {
+        let content = "\
+# comment
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 0.0 1.0 0.0
+f 1 2 3";
+        let file = create_test_file(content);
+
+        let vertices: Result<Vec<[f32; 3]>, _> = process_obj_iter(file).collect();
+        let vertices = vertices.unwrap();
+
+        assert_eq!(
+            vertices,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_quad_is_fan_triangulated() {
+        let content = "\
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 1.0 1.0 0.0
+v 0.0 1.0 0.0
+f 1/1/1 2/2/1 3/3/1 4/4/1";
+        let file = create_test_file(content);
+
+        let vertices: Result<Vec<[f32; 3]>, _> = process_obj_iter(file).collect();
+        let vertices = vertices.unwrap();
+
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(
+            vertices[0..3],
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]]
+        );
+        assert_eq!(
+            vertices[3..6],
+            [[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_negative_indices() {
+        let content = "\
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 0.0 1.0 0.0
+f -3 -2 -1";
+        let file = create_test_file(content);
+
+        let vertices: Result<Vec<[f32; 3]>, _> = process_obj_iter(file).collect();
+        let vertices = vertices.unwrap();
+
+        assert_eq!(
+            vertices,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_negative_indices_before_later_vertices() {
+        let content = "\
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 0.0 1.0 0.0
+f -3 -2 -1
+v 5.0 5.0 5.0
+f 2 3 -1";
+        let file = create_test_file(content);
+
+        let vertices: Result<Vec<[f32; 3]>, _> = process_obj_iter(file).collect();
+        let vertices = vertices.unwrap();
+
+        assert_eq!(
+            vertices,
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [5.0, 5.0, 5.0]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_index_out_of_range() {
+        let content = "\
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+f 1 2 3";
+        let file = create_test_file(content);
+
+        let results: Vec<_> = process_obj_iter(file).collect();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].as_ref().unwrap_err().to_string(),
+            "Face index out of range"
+        );
+    }
+
+    #[test]
+    fn test_invalid_coordinates() {
+        let content = "\
+v 0.0 invalid 0.0
+v 1.0 0.0 0.0
+v 0.0 1.0 0.0
+v 1.0 1.0 0.0
+f 2 3 4";
+        let file = create_test_file(content);
+
+        let results: Vec<_> = process_obj_iter(file).collect();
+
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[0].as_ref().unwrap_err().to_string(),
+            "Invalid vertex coordinate"
+        );
+        assert_eq!(results[1].as_ref().unwrap(), &[1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_no_faces() {
+        let content = "\
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0";
+        let file = create_test_file(content);
+
+        let vertices: Result<Vec<[f32; 3]>, _> = process_obj_iter(file).collect();
+        assert_eq!(vertices.unwrap().len(), 0);
+    }
+}
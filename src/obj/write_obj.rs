@@ -0,0 +1,35 @@
+use std::error::Error;
+use std::io::Write;
+
+use crate::surface::index_triangles;
+
+pub fn write_obj(writer: &mut impl Write, vertices: &[[f32; 3]]) -> Result<(), Box<dyn Error>> {
+    let (points, triangles) = index_triangles(vertices);
+
+    for p in &points {
+        writeln!(writer, "v {} {} {}", p[0], p[1], p[2])?;
+    }
+    // OBJ indices are 1-based
+    for t in &triangles {
+        writeln!(writer, "f {} {} {}", t[0] + 1, t[1] + 1, t[2] + 1)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_single_triangle() {
+        let vertices = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.5, 0.0]];
+        let mut buf = Vec::new();
+        write_obj(&mut buf, &vertices).unwrap();
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "v 0 0 0\nv 1 0 0\nv 0 1.5 0\nf 1 2 3\n"
+        );
+    }
+}
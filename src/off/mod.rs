@@ -0,0 +1,4 @@
+pub mod process_off_iter;
+pub use process_off_iter::process_off_iter;
+pub mod write_off;
+pub use write_off::write_off;
@@ -0,0 +1,201 @@
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+
+use crate::surface::fan_triangulate;
+
+pub fn process_off_iter(
+    file: File,
+) -> impl Iterator<Item = Result<[f32; 3], Box<dyn std::error::Error>>> {
+    match read_off(file) {
+        Ok(results) => results.into_iter(),
+        Err(e) => vec![Err(e)].into_iter(),
+    }
+}
+
+type OffResults = Vec<Result<[f32; 3], Box<dyn std::error::Error>>>;
+
+fn read_off(file: File) -> Result<OffResults, Box<dyn std::error::Error>> {
+    let reader = BufReader::new(file);
+
+    // https://en.wikipedia.org/wiki/OFF_(file_format)
+    // Comments start with '#' and blank lines are allowed anywhere
+    let mut lines = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let content = line.split('#').next().unwrap_or("").trim().to_string();
+        if !content.is_empty() {
+            lines.push(content);
+        }
+    }
+    let mut lines = lines.into_iter();
+
+    // "The first line contains the OFF keyword" (optionally prefixed as in
+    // COFF/NOFF, and optionally followed by the counts on the same line)
+    let header = lines.next().ok_or("Missing OFF header")?;
+    let mut header_parts = header.split_whitespace();
+    let keyword = header_parts.next().unwrap_or("");
+    if !keyword.ends_with("OFF") {
+        return Err("Missing OFF header".into());
+    }
+    if header.contains("BINARY") {
+        return Err("Binary OFF files are not supported".into());
+    }
+
+    let rest = header_parts.collect::<Vec<_>>().join(" ");
+    let counts_line = if rest.is_empty() {
+        lines.next().ok_or("Missing OFF element counts")?
+    } else {
+        rest
+    };
+    let counts: Vec<usize> = counts_line
+        .split_whitespace()
+        .take(2)
+        .map(|s| s.parse::<usize>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| "Invalid OFF element counts")?;
+    let (vertex_count, face_count) = match counts[..] {
+        [v, f] => (v, f),
+        _ => return Err("Invalid OFF element counts".into()),
+    };
+
+    let mut vertices = Vec::with_capacity(vertex_count);
+    for _ in 0..vertex_count {
+        let line = lines
+            .next()
+            .ok_or("Unexpected end of file in OFF vertices")?;
+        let mut parts = line.split_whitespace();
+        // Trailing values (colours, normals) are ignored
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(x_str), Some(y_str), Some(z_str)) => {
+                match (
+                    x_str.parse::<f32>(),
+                    y_str.parse::<f32>(),
+                    z_str.parse::<f32>(),
+                ) {
+                    (Ok(x), Ok(y), Ok(z)) => vertices.push([x, y, z]),
+                    _ => return Err("Invalid vertex coordinate".into()),
+                }
+            }
+            _ => return Err("Incomplete vertex coordinate".into()),
+        }
+    }
+
+    let mut results: OffResults = Vec::new();
+    for _ in 0..face_count {
+        let line = lines.next().ok_or("Unexpected end of file in OFF faces")?;
+        // Only the count and the indices are parsed, the colour that may
+        // follow can be given as floats
+        let mut parts = line.split_whitespace();
+        let face = match parts.next().map(|s| s.parse::<usize>()) {
+            Some(Ok(n)) => parts
+                .take(n)
+                .map(|s| s.parse::<usize>())
+                .collect::<Result<Vec<_>, _>>()
+                .ok()
+                .filter(|face| face.len() == n),
+            _ => None,
+        };
+        let face = match face {
+            Some(face) if !face.is_empty() => face,
+            _ => {
+                results.push(Err("Invalid face definition".into()));
+                continue;
+            }
+        };
+        if face.iter().any(|&i| i >= vertices.len()) {
+            results.push(Err("Face index out of range".into()));
+            continue;
+        }
+        for triangle in fan_triangulate(&face) {
+            results.extend(triangle.iter().map(|&i| Ok(vertices[i])));
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Seek;
+    use std::io::SeekFrom;
+    use std::io::Write;
+
+    use tempfile::tempfile;
+
+    use super::*;
+
+    fn create_test_file(content: &str) -> File {
+        let mut file = tempfile().unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_quad_with_comments() {
+        let content = "\
+OFF
+# a unit square
+4 1 0
+
+0 0 0
+1 0 0
+1 1 0
+0 1 0
+4 0 1 2 3 255 0 0";
+        let file = create_test_file(content);
+
+        let vertices: Result<Vec<[f32; 3]>, _> = process_off_iter(file).collect();
+        let vertices = vertices.unwrap();
+
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(vertices[2], [1.0, 1.0, 0.0]);
+        assert_eq!(vertices[5], [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_float_face_colour() {
+        let content = "OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2 0.8 0.2 0.2 1.0\n";
+        let file = create_test_file(content);
+
+        let vertices: Result<Vec<[f32; 3]>, _> = process_off_iter(file).collect();
+        assert_eq!(vertices.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_counts_on_header_line() {
+        let content = "OFF 3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n";
+        let file = create_test_file(content);
+
+        let vertices: Result<Vec<[f32; 3]>, _> = process_off_iter(file).collect();
+        assert_eq!(vertices.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_missing_header() {
+        let content = "3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n";
+        let file = create_test_file(content);
+
+        let results: Vec<_> = process_off_iter(file).collect();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].as_ref().unwrap_err().to_string(),
+            "Missing OFF header"
+        );
+    }
+
+    #[test]
+    fn test_index_out_of_range() {
+        let content = "OFF\n3 2 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 5\n3 0 1 2\n";
+        let file = create_test_file(content);
+
+        let results: Vec<_> = process_off_iter(file).collect();
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results[0].as_ref().unwrap_err().to_string(),
+            "Face index out of range"
+        );
+        assert!(results[1..].iter().all(|r| r.is_ok()));
+    }
+}
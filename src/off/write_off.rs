@@ -0,0 +1,36 @@
+use std::error::Error;
+use std::io::Write;
+
+use crate::surface::index_triangles;
+
+pub fn write_off(writer: &mut impl Write, vertices: &[[f32; 3]]) -> Result<(), Box<dyn Error>> {
+    let (points, triangles) = index_triangles(vertices);
+
+    writeln!(writer, "OFF")?;
+    writeln!(writer, "{} {} 0", points.len(), triangles.len())?;
+    for p in &points {
+        writeln!(writer, "{} {} {}", p[0], p[1], p[2])?;
+    }
+    for t in &triangles {
+        writeln!(writer, "3 {} {} {}", t[0], t[1], t[2])?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_single_triangle() {
+        let vertices = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.5, 0.0]];
+        let mut buf = Vec::new();
+        write_off(&mut buf, &vertices).unwrap();
+
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "OFF\n3 1 0\n0 0 0\n1 0 0\n0 1.5 0\n3 0 1 2\n"
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod process_ply_iter;
+pub use process_ply_iter::process_ply_iter;
+pub mod write_ply;
+pub use write_ply::write_ply;
@@ -0,0 +1,443 @@
+use std::fs::File;
+use std::io::Read;
+
+use crate::surface::fan_triangulate;
+
+pub fn process_ply_iter(
+    file: File,
+) -> impl Iterator<Item = Result<[f32; 3], Box<dyn std::error::Error>>> {
+    match read_ply(file) {
+        Ok(results) => results.into_iter(),
+        Err(e) => vec![Err(e)].into_iter(),
+    }
+}
+
+type PlyResults = Vec<Result<[f32; 3], Box<dyn std::error::Error>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlyType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl PlyType {
+    fn parse(s: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        // http://paulbourke.net/dataformats/ply/
+        // Both the original names and the sized aliases are in use
+        match s {
+            "char" | "int8" => Ok(PlyType::I8),
+            "uchar" | "uint8" => Ok(PlyType::U8),
+            "short" | "int16" => Ok(PlyType::I16),
+            "ushort" | "uint16" => Ok(PlyType::U16),
+            "int" | "int32" => Ok(PlyType::I32),
+            "uint" | "uint32" => Ok(PlyType::U32),
+            "float" | "float32" => Ok(PlyType::F32),
+            "double" | "float64" => Ok(PlyType::F64),
+            _ => Err(format!("Unknown PLY property type '{}'", s).into()),
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            PlyType::I8 | PlyType::U8 => 1,
+            PlyType::I16 | PlyType::U16 => 2,
+            PlyType::I32 | PlyType::U32 | PlyType::F32 => 4,
+            PlyType::F64 => 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PlyProperty {
+    Scalar(String, PlyType),
+    List(String, PlyType, PlyType),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+struct PlyHeader {
+    format: PlyFormat,
+    elements: Vec<PlyElement>,
+    length: usize,
+}
+
+fn parse_header(data: &[u8]) -> Result<PlyHeader, Box<dyn std::error::Error>> {
+    let marker = b"end_header";
+    let end = data
+        .windows(marker.len())
+        .position(|w| w == marker)
+        .ok_or("Missing PLY end_header")?;
+    // The body starts after the newline terminating end_header
+    let mut length = end + marker.len();
+    while length < data.len() && data[length] != b'\n' {
+        length += 1;
+    }
+    length = (length + 1).min(data.len());
+
+    let text = std::str::from_utf8(&data[..end])?;
+    let mut lines = text.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err("Missing PLY magic number".into());
+    }
+
+    let mut format = None;
+    let mut elements: Vec<PlyElement> = Vec::new();
+    for line in lines {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["format", "ascii", ..] => format = Some(PlyFormat::Ascii),
+            ["format", "binary_little_endian", ..] => format = Some(PlyFormat::BinaryLittleEndian),
+            ["format", "binary_big_endian", ..] => format = Some(PlyFormat::BinaryBigEndian),
+            ["element", name, count] => elements.push(PlyElement {
+                name: name.to_string(),
+                count: count.parse()?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_type, item_type, name] => elements
+                .last_mut()
+                .ok_or("PLY property before any element")?
+                .properties
+                .push(PlyProperty::List(
+                    name.to_string(),
+                    PlyType::parse(count_type)?,
+                    PlyType::parse(item_type)?,
+                )),
+            ["property", value_type, name] => elements
+                .last_mut()
+                .ok_or("PLY property before any element")?
+                .properties
+                .push(PlyProperty::Scalar(
+                    name.to_string(),
+                    PlyType::parse(value_type)?,
+                )),
+            _ => {}
+        }
+    }
+
+    Ok(PlyHeader {
+        format: format.ok_or("Missing PLY format")?,
+        elements,
+        length,
+    })
+}
+
+enum PlyBody<'a> {
+    Ascii(std::str::SplitAsciiWhitespace<'a>),
+    Binary {
+        data: &'a [u8],
+        pos: usize,
+        big_endian: bool,
+    },
+}
+
+impl PlyBody<'_> {
+    fn next_value(&mut self, ty: PlyType) -> Result<f64, Box<dyn std::error::Error>> {
+        match self {
+            PlyBody::Ascii(tokens) => {
+                let token = tokens.next().ok_or("Unexpected end of PLY data")?;
+                token
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid PLY value '{}'", token).into())
+            }
+            PlyBody::Binary {
+                data,
+                pos,
+                big_endian,
+            } => {
+                let size = ty.size();
+                let bytes = data
+                    .get(*pos..*pos + size)
+                    .ok_or("Unexpected end of PLY data")?;
+                *pos += size;
+
+                let mut buf = [0u8; 8];
+                buf[..size].copy_from_slice(bytes);
+                if *big_endian {
+                    buf[..size].reverse();
+                }
+                Ok(match ty {
+                    PlyType::I8 => buf[0] as i8 as f64,
+                    PlyType::U8 => buf[0] as f64,
+                    PlyType::I16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    PlyType::U16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+                    PlyType::I32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    PlyType::U32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    PlyType::F32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+                    PlyType::F64 => f64::from_le_bytes(buf),
+                })
+            }
+        }
+    }
+}
+
+fn read_ply(mut file: File) -> Result<PlyResults, Box<dyn std::error::Error>> {
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+
+    let header = parse_header(&data)?;
+    let mut body = match header.format {
+        PlyFormat::Ascii => {
+            PlyBody::Ascii(std::str::from_utf8(&data[header.length..])?.split_ascii_whitespace())
+        }
+        PlyFormat::BinaryLittleEndian | PlyFormat::BinaryBigEndian => PlyBody::Binary {
+            data: &data[header.length..],
+            pos: 0,
+            big_endian: header.format == PlyFormat::BinaryBigEndian,
+        },
+    };
+
+    let mut vertices: Vec<[f32; 3]> = Vec::new();
+    let mut faces: Vec<Vec<f64>> = Vec::new();
+
+    // Elements are stored one after another in header order, so unknown
+    // elements still have to be read to reach the ones that follow
+    for element in &header.elements {
+        for _ in 0..element.count {
+            let mut xyz = [None; 3];
+            let mut face = None;
+            for property in &element.properties {
+                match property {
+                    PlyProperty::Scalar(name, ty) => {
+                        let value = body.next_value(*ty)?;
+                        match name.as_str() {
+                            "x" => xyz[0] = Some(value as f32),
+                            "y" => xyz[1] = Some(value as f32),
+                            "z" => xyz[2] = Some(value as f32),
+                            _ => {}
+                        }
+                    }
+                    PlyProperty::List(name, count_type, item_type) => {
+                        let count = body.next_value(*count_type)? as usize;
+                        let items = (0..count)
+                            .map(|_| body.next_value(*item_type))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        if name == "vertex_indices" || name == "vertex_index" {
+                            face = Some(items);
+                        }
+                    }
+                }
+            }
+
+            match element.name.as_str() {
+                "vertex" => match xyz {
+                    [Some(x), Some(y), Some(z)] => vertices.push([x, y, z]),
+                    _ => return Err("PLY vertex element is missing x, y or z".into()),
+                },
+                "face" => faces.push(face.ok_or("PLY face element has no vertex_indices")?),
+                _ => {}
+            }
+        }
+    }
+
+    let mut results: PlyResults = Vec::new();
+    for face in faces {
+        // Negative indices would otherwise saturate to vertex 0
+        if face
+            .iter()
+            .any(|&i| i < 0.0 || i as usize >= vertices.len())
+        {
+            results.push(Err("Face index out of range".into()));
+            continue;
+        }
+        let face: Vec<usize> = face.iter().map(|&i| i as usize).collect();
+        for triangle in fan_triangulate(&face) {
+            results.extend(triangle.iter().map(|&i| Ok(vertices[i])));
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Seek;
+    use std::io::SeekFrom;
+    use std::io::Write;
+
+    use tempfile::tempfile;
+
+    use super::*;
+
+    fn create_test_file(content: &[u8]) -> File {
+        let mut file = tempfile().unwrap();
+        file.write_all(content).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+        file
+    }
+
+    const HEADER_TAIL: &str = "element vertex 4
+property float x
+property float y
+property float z
+element face 1
+property list uchar int vertex_indices
+end_header
+";
+
+    #[test]
+    fn test_ascii_quad() {
+        let content = format!(
+            "ply\nformat ascii 1.0\ncomment made by hand\n{}0 0 0\n1 0 0\n1 1 0\n0 1 0\n4 0 1 2 \
+             3\n",
+            HEADER_TAIL
+        );
+        let file = create_test_file(content.as_bytes());
+
+        let vertices: Result<Vec<[f32; 3]>, _> = process_ply_iter(file).collect();
+        let vertices = vertices.unwrap();
+
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(
+            vertices[0..3],
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]]
+        );
+        assert_eq!(
+            vertices[3..6],
+            [[0.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+    }
+
+    fn binary_body(big_endian: bool) -> Vec<u8> {
+        let points: [[f32; 3]; 4] = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ];
+        let mut body = Vec::new();
+        for p in points {
+            for c in p {
+                if big_endian {
+                    body.extend_from_slice(&c.to_be_bytes());
+                } else {
+                    body.extend_from_slice(&c.to_le_bytes());
+                }
+            }
+        }
+        body.push(4u8);
+        for i in 0..4i32 {
+            if big_endian {
+                body.extend_from_slice(&i.to_be_bytes());
+            } else {
+                body.extend_from_slice(&i.to_le_bytes());
+            }
+        }
+        body
+    }
+
+    #[test]
+    fn test_binary_little_endian_quad() {
+        let mut content =
+            format!("ply\nformat binary_little_endian 1.0\n{}", HEADER_TAIL).into_bytes();
+        content.extend(binary_body(false));
+        let file = create_test_file(&content);
+
+        let vertices: Result<Vec<[f32; 3]>, _> = process_ply_iter(file).collect();
+        let vertices = vertices.unwrap();
+
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(vertices[5], [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_binary_big_endian_quad() {
+        let mut content =
+            format!("ply\nformat binary_big_endian 1.0\n{}", HEADER_TAIL).into_bytes();
+        content.extend(binary_body(true));
+        let file = create_test_file(&content);
+
+        let vertices: Result<Vec<[f32; 3]>, _> = process_ply_iter(file).collect();
+        let vertices = vertices.unwrap();
+
+        assert_eq!(vertices.len(), 6);
+        assert_eq!(vertices[2], [1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_extra_properties_and_elements_are_skipped() {
+        let content = "ply
+format ascii 1.0
+element vertex 3
+property double x
+property double y
+property double z
+property uchar red
+element face 1
+property list uchar uint vertex_index
+property float quality
+element edge 1
+property int vertex1
+property int vertex2
+end_header
+0 0 0 255
+1 0 0 255
+0 1 0 255
+3 0 1 2 0.5
+0 1
+";
+        let file = create_test_file(content.as_bytes());
+
+        let vertices: Result<Vec<[f32; 3]>, _> = process_ply_iter(file).collect();
+        assert_eq!(
+            vertices.unwrap(),
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn test_negative_index() {
+        let content = format!(
+            "ply\nformat ascii 1.0\n{}0 0 0\n1 0 0\n1 1 0\n0 1 0\n4 0 1 2 -1\n",
+            HEADER_TAIL
+        );
+        let file = create_test_file(content.as_bytes());
+
+        let results: Vec<_> = process_ply_iter(file).collect();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].as_ref().unwrap_err().to_string(),
+            "Face index out of range"
+        );
+    }
+
+    #[test]
+    fn test_missing_magic_number() {
+        let content = format!("format ascii 1.0\n{}", HEADER_TAIL);
+        let file = create_test_file(content.as_bytes());
+
+        let results: Vec<_> = process_ply_iter(file).collect();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].as_ref().unwrap_err().to_string(),
+            "Missing PLY magic number"
+        );
+    }
+
+    #[test]
+    fn test_truncated_body() {
+        let content = format!("ply\nformat ascii 1.0\n{}0 0 0\n1 0 0\n", HEADER_TAIL);
+        let file = create_test_file(content.as_bytes());
+
+        let results: Vec<_> = process_ply_iter(file).collect();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
+}
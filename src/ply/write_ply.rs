@@ -0,0 +1,42 @@
+use std::error::Error;
+use std::io::Write;
+
+use crate::surface::index_triangles;
+
+pub fn write_ply(writer: &mut impl Write, vertices: &[[f32; 3]]) -> Result<(), Box<dyn Error>> {
+    let (points, triangles) = index_triangles(vertices);
+
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format ascii 1.0")?;
+    writeln!(writer, "element vertex {}", points.len())?;
+    writeln!(writer, "property float x")?;
+    writeln!(writer, "property float y")?;
+    writeln!(writer, "property float z")?;
+    writeln!(writer, "element face {}", triangles.len())?;
+    writeln!(writer, "property list uchar int vertex_indices")?;
+    writeln!(writer, "end_header")?;
+    for p in &points {
+        writeln!(writer, "{} {} {}", p[0], p[1], p[2])?;
+    }
+    for t in &triangles {
+        writeln!(writer, "3 {} {} {}", t[0], t[1], t[2])?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_single_triangle() {
+        let vertices = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.5, 0.0]];
+        let mut buf = Vec::new();
+        write_ply(&mut buf, &vertices).unwrap();
+
+        let text = String::from_utf8(buf).unwrap();
+        assert!(text.starts_with("ply\nformat ascii 1.0\nelement vertex 3\n"));
+        assert!(text.ends_with("end_header\n0 0 0\n1 0 0\n0 1.5 0\n3 0 1 2\n"));
+    }
+}
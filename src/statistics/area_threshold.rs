@@ -0,0 +1,271 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use super::Threshold;
+use crate::histogram::weighted_histogram;
+use crate::interpolation::interpolate;
+
+/// Bins of the cumulative histogram used by [`ThresholdMethod::Histogram`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Binning {
+    Width(f64),
+    /// Equal bins spanning the range of the values
+    Count(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ThresholdMethod {
+    /// Sorts the values and accumulates their weights
+    #[default]
+    Exact,
+    /// Interpolates a cumulative weighted histogram
+    Histogram(Binning),
+}
+
+/// Which end of the distribution the area is accumulated from.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Direction {
+    /// The lowest values, e.g. suction peaks
+    #[default]
+    Bottom,
+    /// The highest values, e.g. stagnation regions
+    Top,
+}
+
+impl Direction {
+    /// The cells making up the area bounded by `threshold`.
+    pub fn region(self, threshold: f64) -> Threshold {
+        match self {
+            Direction::Bottom => Threshold::Between(f64::NEG_INFINITY, threshold),
+            Direction::Top => Threshold::Between(threshold, f64::INFINITY),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bottom" => Ok(Direction::Bottom),
+            "top" => Ok(Direction::Top),
+            _ => Err(format!("Unknown direction '{}', expected top or bottom", s)),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Direction::Bottom => write!(f, "bottom"),
+            Direction::Top => write!(f, "top"),
+        }
+    }
+}
+
+/// The value below which `target_area` of the total weight lies, see
+/// [`area_thresholds`].
+pub fn area_threshold(
+    values: &[f64],
+    weights: &[f64],
+    target_area: f64,
+    method: ThresholdMethod,
+) -> Result<f64, Box<dyn Error>> {
+    let thresholds = area_thresholds(values, weights, &[target_area], method, Direction::Bottom)?;
+    Ok(thresholds[0])
+}
+
+/// For each target area, the value bounding that much of the total weight
+/// from the `direction` end of the distribution. The values are sorted or
+/// binned once for all targets.
+///
+/// From the bottom, the exact method returns the smallest value whose
+/// cells, together with all cells of lower values, cover at least the
+/// target, so it does not depend on the units of the field. NaN values are
+/// skipped.
+pub fn area_thresholds(
+    values: &[f64],
+    weights: &[f64],
+    target_areas: &[f64],
+    method: ThresholdMethod,
+    direction: Direction,
+) -> Result<Vec<f64>, Box<dyn Error>> {
+    if values.len() != weights.len() {
+        return Err(format!("Got {} values but {} weights", values.len(), weights.len()).into());
+    }
+
+    // Accumulating from the top is accumulating the negated values from
+    // the bottom
+    let sign = match direction {
+        Direction::Bottom => 1.0,
+        Direction::Top => -1.0,
+    };
+    let (values, weights): (Vec<f64>, Vec<f64>) = values
+        .iter()
+        .zip(weights)
+        .filter(|(v, _)| !v.is_nan())
+        .map(|(&v, &w)| (sign * v, w))
+        .unzip();
+    if values.is_empty() {
+        return Err("No values to compute an area threshold from".into());
+    }
+
+    let thresholds = match method {
+        ThresholdMethod::Exact => exact_thresholds(&values, &weights, target_areas),
+        ThresholdMethod::Histogram(binning) => {
+            histogram_thresholds(&values, &weights, target_areas, binning)?
+        }
+    };
+    Ok(thresholds.into_iter().map(|t| sign * t).collect())
+}
+
+fn exact_thresholds(values: &[f64], weights: &[f64], target_areas: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+    let cumulative: Vec<f64> = order
+        .iter()
+        .scan(0.0, |sum, &i| {
+            *sum += weights[i];
+            Some(*sum)
+        })
+        .collect();
+
+    target_areas
+        .iter()
+        .map(|&target| {
+            let position = cumulative
+                .partition_point(|&c| c < target)
+                .min(order.len() - 1);
+            values[order[position]]
+        })
+        .collect()
+}
+
+fn histogram_thresholds(
+    values: &[f64],
+    weights: &[f64],
+    target_areas: &[f64],
+    binning: Binning,
+) -> Result<Vec<f64>, Box<dyn Error>> {
+    let min_value = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max_value = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+    let bin_width = match binning {
+        Binning::Width(width) => width,
+        Binning::Count(0) => return Err("Number of bins must be positive".into()),
+        // A constant field has nothing to bin
+        Binning::Count(_) if max_value == min_value => {
+            return Ok(vec![min_value; target_areas.len()])
+        }
+        Binning::Count(count) => (max_value - min_value) / count as f64,
+    };
+    if !(bin_width > 0.0 && bin_width.is_finite()) {
+        return Err(format!("Bin width must be positive, got {}", bin_width).into());
+    }
+
+    let histogram = weighted_histogram(values, weights, &bin_width);
+
+    let mut area_cumsum: Vec<f64> = histogram
+        .heights
+        .iter()
+        .scan(0.0, |sum, &val| {
+            *sum += val;
+            Some(*sum)
+        })
+        .collect();
+    if let Some(&last) = area_cumsum.last() {
+        area_cumsum.push(last);
+    }
+
+    Ok(target_areas
+        .iter()
+        .map(|&target| interpolate(&area_cumsum, &histogram.bin_edges, target))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact() {
+        let values = [3.0, 1.0, 2.0, 4.0];
+        let weights = [1.0, 1.0, 2.0, 1.0];
+        let threshold = |target| area_threshold(&values, &weights, target, ThresholdMethod::Exact);
+        assert_eq!(threshold(1.0).unwrap(), 1.0);
+        assert_eq!(threshold(1.5).unwrap(), 2.0);
+        assert_eq!(threshold(3.0).unwrap(), 2.0);
+        assert_eq!(threshold(3.5).unwrap(), 3.0);
+        assert_eq!(threshold(10.0).unwrap(), 4.0);
+    }
+
+    #[test]
+    fn test_exact_is_unit_independent() {
+        let values = [1e-3, 2e-3, 3e-3];
+        let weights = [1.0; 3];
+        let threshold = area_threshold(&values, &weights, 2.0, ThresholdMethod::Exact).unwrap();
+        assert_eq!(threshold, 2e-3);
+    }
+
+    #[test]
+    fn test_histogram_width() {
+        let values = vec![1.0, 2.0];
+        let weights = vec![1.0, 1.0];
+        let method = ThresholdMethod::Histogram(Binning::Width(1.0));
+        assert_eq!(area_threshold(&values, &weights, 1.5, method).unwrap(), 1.5);
+        // Below the first bin's area the lowest edge is returned
+        assert_eq!(area_threshold(&values, &weights, 0.5, method).unwrap(), 1.0);
+    }
+
+    #[test]
+    fn test_histogram_count() {
+        let values = vec![0.0, 1e-3];
+        let weights = vec![1.0, 1.0];
+        let method = ThresholdMethod::Histogram(Binning::Count(2));
+        let threshold = area_threshold(&values, &weights, 1.5, method).unwrap();
+        assert!((threshold - 7.5e-4).abs() < 1e-15);
+
+        let constant = area_threshold(&[2.0; 3], &[1.0; 3], 1.0, method).unwrap();
+        assert_eq!(constant, 2.0);
+    }
+
+    #[test]
+    fn test_several_targets_from_top() {
+        let values = [3.0, 1.0, 2.0, 4.0];
+        let weights = [1.0, 1.0, 2.0, 1.0];
+        let thresholds = area_thresholds(
+            &values,
+            &weights,
+            &[1.0, 1.5, 3.0, 10.0],
+            ThresholdMethod::Exact,
+            Direction::Top,
+        )
+        .unwrap();
+        assert_eq!(thresholds, vec![4.0, 3.0, 2.0, 1.0]);
+
+        let region = Direction::Top.region(thresholds[1]);
+        assert_eq!(region.select(&values), vec![true, false, false, true]);
+    }
+
+    #[test]
+    fn test_direction_from_str() {
+        assert_eq!("Top".parse::<Direction>().unwrap(), Direction::Top);
+        assert_eq!("bottom".parse::<Direction>().unwrap(), Direction::Bottom);
+        assert!("up".parse::<Direction>().is_err());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let exact = ThresholdMethod::Exact;
+        assert!(area_threshold(&[], &[], 1.0, exact).is_err());
+        assert!(area_threshold(&[1.0], &[], 1.0, exact).is_err());
+        assert!(area_threshold(&[f64::NAN], &[1.0], 1.0, exact).is_err());
+
+        let zero_width = ThresholdMethod::Histogram(Binning::Width(0.0));
+        assert!(area_threshold(&[1.0], &[1.0], 1.0, zero_width).is_err());
+        let no_bins = ThresholdMethod::Histogram(Binning::Count(0));
+        assert!(area_threshold(&[1.0], &[1.0], 1.0, no_bins).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::difference::DIVISOR_EPSILON;
+
+/// How the deviation of a value from a reference is scaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Normalisation {
+    /// (value - reference) / |scale|, where the scale is usually the
+    /// reference itself
+    #[default]
+    Relative,
+    /// value - reference
+    Absolute,
+    /// (value - reference) / area-weighted standard deviation about the
+    /// reference
+    ZScore,
+}
+
+impl Normalisation {
+    /// The deviation of `value` from `reference`. `scale` is what relative
+    /// deviations are taken against and `std_dev` the spread for z-scores.
+    pub fn deviation(self, value: f64, reference: f64, scale: f64, std_dev: f64) -> f64 {
+        let delta = value - reference;
+        match self {
+            Normalisation::Relative => delta / (scale.abs() + DIVISOR_EPSILON),
+            Normalisation::Absolute => delta,
+            Normalisation::ZScore => delta / (std_dev + DIVISOR_EPSILON),
+        }
+    }
+}
+
+impl FromStr for Normalisation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "relative" => Ok(Normalisation::Relative),
+            "absolute" => Ok(Normalisation::Absolute),
+            "z-score" | "zscore" => Ok(Normalisation::ZScore),
+            _ => Err(format!(
+                "Unknown normalisation '{}', expected relative, absolute or z-score",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Normalisation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Normalisation::Relative => write!(f, "relative"),
+            Normalisation::Absolute => write!(f, "absolute"),
+            Normalisation::ZScore => write!(f, "z-score"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deviation() {
+        let relative = Normalisation::Relative.deviation(3.0, 2.0, -4.0, 0.5);
+        assert!((relative - 0.25).abs() < 1e-12);
+        assert_eq!(Normalisation::Absolute.deviation(3.0, 2.0, -4.0, 0.5), 1.0);
+        let z_score = Normalisation::ZScore.deviation(3.0, 2.0, -4.0, 0.5);
+        assert!((z_score - 2.0).abs() < 1e-12);
+        assert!(Normalisation::ZScore
+            .deviation(1.0, 0.0, 0.0, 0.0)
+            .is_finite());
+    }
+
+    #[test]
+    fn test_from_str() {
+        for normalisation in [
+            Normalisation::Relative,
+            Normalisation::Absolute,
+            Normalisation::ZScore,
+        ] {
+            let parsed: Normalisation = normalisation.to_string().parse().unwrap();
+            assert_eq!(parsed, normalisation);
+        }
+        assert!("scaled".parse::<Normalisation>().is_err());
+    }
+}
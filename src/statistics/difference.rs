@@ -0,0 +1,97 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Added to divisors so that zero references give large rather than
+/// infinite differences, as in `autofoam-scalar-deviation`.
+pub const DIVISOR_EPSILON: f64 = 1e-15;
+
+/// How a variant value is compared with its baseline value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difference {
+    /// variant - baseline
+    Absolute,
+    /// (variant - baseline) / |baseline|
+    Relative,
+    /// (variant - baseline) / |area-weighted mean of the baseline|
+    Normalised,
+}
+
+impl Difference {
+    pub const ALL: [Difference; 3] = [
+        Difference::Absolute,
+        Difference::Relative,
+        Difference::Normalised,
+    ];
+
+    /// The difference of `variant` from `baseline`, where `baseline_mean`
+    /// is the area-weighted mean of the baseline field.
+    pub fn of(self, baseline: f64, variant: f64, baseline_mean: f64) -> f64 {
+        let delta = variant - baseline;
+        match self {
+            Difference::Absolute => delta,
+            Difference::Relative => delta / (baseline.abs() + DIVISOR_EPSILON),
+            Difference::Normalised => delta / (baseline_mean.abs() + DIVISOR_EPSILON),
+        }
+    }
+
+    /// Appended to a field name to name its difference field
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Difference::Absolute => "_delta",
+            Difference::Relative => "_delta_rel",
+            Difference::Normalised => "_delta_norm",
+        }
+    }
+}
+
+impl FromStr for Difference {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "absolute" | "abs" => Ok(Difference::Absolute),
+            "relative" | "rel" => Ok(Difference::Relative),
+            "normalised" | "normalized" | "norm" => Ok(Difference::Normalised),
+            _ => Err(format!(
+                "Unknown difference '{}', expected absolute, relative or normalised",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difference::Absolute => write!(f, "absolute"),
+            Difference::Relative => write!(f, "relative"),
+            Difference::Normalised => write!(f, "normalised"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_differences() {
+        assert_eq!(Difference::Absolute.of(2.0, 3.0, 4.0), 1.0);
+        assert!((Difference::Relative.of(-2.0, -3.0, 4.0) + 0.5).abs() < 1e-12);
+        assert!((Difference::Normalised.of(2.0, 3.0, -4.0) - 0.25).abs() < 1e-12);
+        assert!(Difference::Relative.of(0.0, 1.0, 1.0).is_finite());
+    }
+
+    #[test]
+    fn test_from_str() {
+        for difference in Difference::ALL {
+            let parsed: Difference = difference.to_string().parse().unwrap();
+            assert_eq!(parsed, difference);
+        }
+        assert_eq!(
+            "normalized".parse::<Difference>().unwrap(),
+            Difference::Normalised
+        );
+        assert!("ratio".parse::<Difference>().is_err());
+    }
+}
@@ -0,0 +1,34 @@
+/// Area-weighted summary of a field over the cells of a surface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldStatistics {
+    /// Sum of the weights, i.e. the area the statistics are taken over
+    pub area: f64,
+    /// Sum of value times weight
+    pub integral: f64,
+    pub mean: f64,
+    pub rms: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    /// Index of the cell holding the minimum
+    pub min_cell: usize,
+    pub max: f64,
+    /// Index of the cell holding the maximum
+    pub max_cell: usize,
+}
+
+pub mod area_threshold;
+pub use area_threshold::area_threshold;
+pub use area_threshold::area_thresholds;
+pub use area_threshold::Binning;
+pub use area_threshold::Direction;
+pub use area_threshold::ThresholdMethod;
+pub mod deviation;
+pub use deviation::Normalisation;
+pub mod difference;
+pub use difference::Difference;
+pub mod threshold;
+pub use threshold::Threshold;
+pub mod weighted_percentiles;
+pub use weighted_percentiles::weighted_percentiles;
+pub mod weighted_statistics;
+pub use weighted_statistics::weighted_statistics;
@@ -0,0 +1,46 @@
+/// A condition on field values selecting cells.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    /// Values strictly greater than the bound
+    Above(f64),
+    /// Values strictly less than the bound
+    Below(f64),
+    /// Values within the bounds, inclusive
+    Between(f64, f64),
+}
+
+impl Threshold {
+    pub fn contains(&self, value: f64) -> bool {
+        match *self {
+            Threshold::Above(lower) => value > lower,
+            Threshold::Below(upper) => value < upper,
+            Threshold::Between(lower, upper) => lower <= value && value <= upper,
+        }
+    }
+
+    pub fn select(&self, values: &[f64]) -> Vec<bool> {
+        values.iter().map(|&v| self.contains(v)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select() {
+        let values = [1.0, 2.0, 3.0, f64::NAN];
+        assert_eq!(
+            Threshold::Above(2.0).select(&values),
+            vec![false, false, true, false]
+        );
+        assert_eq!(
+            Threshold::Below(2.0).select(&values),
+            vec![true, false, false, false]
+        );
+        assert_eq!(
+            Threshold::Between(2.0, 3.0).select(&values),
+            vec![false, true, true, false]
+        );
+    }
+}
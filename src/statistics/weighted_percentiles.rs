@@ -0,0 +1,90 @@
+use std::error::Error;
+
+/// Weighted percentiles (0-100) of `values`, computed in one sort.
+///
+/// Each value sits at the middle of its share of the cumulative weight and
+/// percentiles in between are interpolated linearly, so equal weights give
+/// the usual median. Percentiles outside the first or last midpoint clamp
+/// to the extreme values. NaN values and zero weights are skipped.
+pub fn weighted_percentiles(
+    values: &[f64],
+    weights: &[f64],
+    percentiles: &[f64],
+) -> Result<Vec<f64>, Box<dyn Error>> {
+    if values.len() != weights.len() {
+        return Err(format!("Got {} values but {} weights", values.len(), weights.len()).into());
+    }
+    if let Some(p) = percentiles.iter().find(|p| !(0.0..=100.0).contains(*p)) {
+        return Err(format!("Percentile {} is outside 0-100", p).into());
+    }
+
+    let mut sorted: Vec<(f64, f64)> = values
+        .iter()
+        .zip(weights)
+        .filter(|(v, &w)| !v.is_nan() && w > 0.0)
+        .map(|(&v, &w)| (v, w))
+        .collect();
+    if sorted.is_empty() {
+        return Err("No weighted values to compute percentiles from".into());
+    }
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let total: f64 = sorted.iter().map(|(_, w)| w).sum();
+    let mut midpoints = Vec::with_capacity(sorted.len());
+    let mut cumulative = 0.0;
+    for &(_, weight) in &sorted {
+        midpoints.push(cumulative + 0.5 * weight);
+        cumulative += weight;
+    }
+
+    Ok(percentiles
+        .iter()
+        .map(|p| {
+            let target = p * 0.01 * total;
+            let upper = midpoints.partition_point(|&m| m < target);
+            if upper == 0 {
+                sorted[0].0
+            } else if upper == sorted.len() {
+                sorted[upper - 1].0
+            } else {
+                let (m0, m1) = (midpoints[upper - 1], midpoints[upper]);
+                let (v0, v1) = (sorted[upper - 1].0, sorted[upper].0);
+                v0 + (v1 - v0) * (target - m0) / (m1 - m0)
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_weights_median() {
+        let result = weighted_percentiles(&[3.0, 1.0, 2.0], &[1.0; 3], &[50.0]).unwrap();
+        assert_eq!(result, vec![2.0]);
+        let result = weighted_percentiles(&[1.0, 2.0], &[1.0; 2], &[50.0]).unwrap();
+        assert_eq!(result, vec![1.5]);
+    }
+
+    #[test]
+    fn test_unequal_weights() {
+        // Midpoints at 1.5 and 4 of a total weight of 5
+        let result = weighted_percentiles(&[10.0, 20.0], &[3.0, 2.0], &[50.0]).unwrap();
+        assert_eq!(result, vec![14.0]);
+    }
+
+    #[test]
+    fn test_clamped_to_extremes() {
+        let result =
+            weighted_percentiles(&[1.0, 2.0, 3.0], &[1.0; 3], &[0.0, 10.0, 95.0, 100.0]).unwrap();
+        assert_eq!(result, vec![1.0, 1.0, 3.0, 3.0]);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(weighted_percentiles(&[1.0], &[1.0], &[101.0]).is_err());
+        assert!(weighted_percentiles(&[1.0], &[0.0], &[50.0]).is_err());
+        assert!(weighted_percentiles(&[1.0], &[1.0, 2.0], &[50.0]).is_err());
+    }
+}
@@ -0,0 +1,121 @@
+use std::error::Error;
+
+use super::FieldStatistics;
+
+/// Weighted mean, RMS, standard deviation, extrema and integral of
+/// `values`. NaN values are skipped.
+pub fn weighted_statistics(
+    values: &[f64],
+    weights: &[f64],
+) -> Result<FieldStatistics, Box<dyn Error>> {
+    if values.len() != weights.len() {
+        return Err(format!("Got {} values but {} weights", values.len(), weights.len()).into());
+    }
+
+    let mut area = 0.0;
+    let mut integral = 0.0;
+    let mut square_integral = 0.0;
+    let mut min = (f64::INFINITY, None);
+    let mut max = (f64::NEG_INFINITY, None);
+
+    for (i, (&value, &weight)) in values.iter().zip(weights).enumerate() {
+        if value.is_nan() {
+            continue;
+        }
+        area += weight;
+        integral += value * weight;
+        square_integral += value * value * weight;
+        if min.1.is_none() || value < min.0 {
+            min = (value, Some(i));
+        }
+        if max.1.is_none() || value > max.0 {
+            max = (value, Some(i));
+        }
+    }
+
+    let (Some(min_cell), Some(max_cell)) = (min.1, max.1) else {
+        return Err("No values to compute statistics from".into());
+    };
+    if area <= 0.0 {
+        return Err("Total weight is zero, cannot compute weighted statistics".into());
+    }
+
+    let mean = integral / area;
+    // A second pass about the mean avoids the cancellation of
+    // E[x^2] - E[x]^2 for fields with a large offset
+    let variance = values
+        .iter()
+        .zip(weights)
+        .filter(|(value, _)| !value.is_nan())
+        .map(|(&value, &weight)| (value - mean) * (value - mean) * weight)
+        .sum::<f64>()
+        / area;
+    Ok(FieldStatistics {
+        area,
+        integral,
+        mean,
+        rms: (square_integral / area).sqrt(),
+        std_dev: variance.sqrt(),
+        min: min.0,
+        min_cell,
+        max: max.0,
+        max_cell,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equal_weights() {
+        let stats = weighted_statistics(&[1.0, 3.0], &[1.0, 1.0]).unwrap();
+        assert_eq!(stats.area, 2.0);
+        assert_eq!(stats.integral, 4.0);
+        assert_eq!(stats.mean, 2.0);
+        assert_eq!(stats.rms, 5.0_f64.sqrt());
+        assert_eq!(stats.std_dev, 1.0);
+    }
+
+    #[test]
+    fn test_unequal_weights() {
+        let stats = weighted_statistics(&[1.0, 4.0], &[2.0, 1.0]).unwrap();
+        assert_eq!(stats.integral, 6.0);
+        assert_eq!(stats.mean, 2.0);
+        assert_eq!(stats.std_dev, 2.0_f64.sqrt());
+    }
+
+    #[test]
+    fn test_extrema_cells() {
+        let stats = weighted_statistics(&[2.0, -1.0, 5.0, 5.0], &[1.0; 4]).unwrap();
+        assert_eq!((stats.min, stats.min_cell), (-1.0, 1));
+        // The first cell wins a tie
+        assert_eq!((stats.max, stats.max_cell), (5.0, 2));
+    }
+
+    #[test]
+    fn test_uniform_field() {
+        let stats = weighted_statistics(&[0.1; 3], &[0.3, 0.7, 1.1]).unwrap();
+        assert_eq!(stats.std_dev, 0.0);
+    }
+
+    #[test]
+    fn test_large_offset() {
+        let stats = weighted_statistics(&[1e9 + 1.0, 1e9 + 3.0], &[1.0, 1.0]).unwrap();
+        assert_eq!(stats.std_dev, 1.0);
+    }
+
+    #[test]
+    fn test_nan_skipped() {
+        let stats = weighted_statistics(&[f64::NAN, 1.0], &[1.0, 2.0]).unwrap();
+        assert_eq!(stats.area, 2.0);
+        assert_eq!((stats.min_cell, stats.max_cell), (1, 1));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!(weighted_statistics(&[], &[]).is_err());
+        assert!(weighted_statistics(&[1.0], &[0.0]).is_err());
+        assert!(weighted_statistics(&[1.0], &[1.0, 1.0]).is_err());
+    }
+}
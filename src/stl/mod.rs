@@ -4,3 +4,6 @@ pub mod process_ascii_iter;
 pub use process_ascii_iter::process_ascii_iter;
 pub mod process_binary_iter;
 pub use process_binary_iter::process_binary_iter;
+pub mod write_stl;
+pub use write_stl::write_ascii_stl;
+pub use write_stl::write_binary_stl;
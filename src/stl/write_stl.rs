@@ -0,0 +1,104 @@
+use std::error::Error;
+use std::io::Write;
+
+use crate::surface::triangle_normal;
+
+pub fn write_ascii_stl(
+    writer: &mut impl Write,
+    vertices: &[[f32; 3]],
+    name: &str,
+) -> Result<(), Box<dyn Error>> {
+    let (triangles, _) = vertices.as_chunks::<3>();
+
+    writeln!(writer, "solid {}", name)?;
+    for tri in triangles {
+        let n = triangle_normal(tri);
+        writeln!(writer, "  facet normal {:e} {:e} {:e}", n[0], n[1], n[2])?;
+        writeln!(writer, "    outer loop")?;
+        for v in tri {
+            writeln!(writer, "      vertex {:e} {:e} {:e}", v[0], v[1], v[2])?;
+        }
+        writeln!(writer, "    endloop")?;
+        writeln!(writer, "  endfacet")?;
+    }
+    writeln!(writer, "endsolid {}", name)?;
+
+    Ok(())
+}
+
+pub fn write_binary_stl(
+    writer: &mut impl Write,
+    vertices: &[[f32; 3]],
+) -> Result<(), Box<dyn Error>> {
+    let (triangles, _) = vertices.as_chunks::<3>();
+
+    // https://en.wikipedia.org/wiki/STL_(file_format)
+    // The header must not start with "solid", otherwise readers take the
+    // file for ASCII
+    let mut header = [0u8; 80];
+    let text = b"binary STL written by autofoam";
+    header[..text.len()].copy_from_slice(text);
+    writer.write_all(&header)?;
+
+    let count = u32::try_from(triangles.len()).map_err(|_| "Too many triangles for binary STL")?;
+    writer.write_all(&count.to_le_bytes())?;
+
+    for tri in triangles {
+        for f in triangle_normal(tri) {
+            writer.write_all(&f.to_le_bytes())?;
+        }
+        for v in tri {
+            for f in v {
+                writer.write_all(&f.to_le_bytes())?;
+            }
+        }
+        // Attribute byte count
+        writer.write_all(&[0u8; 2])?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Seek;
+    use std::io::SeekFrom;
+
+    use tempfile::tempfile;
+
+    use super::*;
+    use crate::stl::is_ascii;
+    use crate::stl::process_ascii_iter;
+    use crate::stl::process_binary_iter;
+
+    const VERTICES: [[f32; 3]; 6] = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.5, 0.25, 1.0],
+        [1.5, 0.0, 1.0],
+        [0.0, 1.0, 1.0],
+    ];
+
+    #[test]
+    fn test_ascii_round_trip() {
+        let mut file = tempfile().unwrap();
+        write_ascii_stl(&mut file, &VERTICES, "test").unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        assert!(is_ascii(&mut file));
+        let read: Result<Vec<[f32; 3]>, _> = process_ascii_iter(file).collect();
+        assert_eq!(read.unwrap(), VERTICES);
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let mut file = tempfile().unwrap();
+        write_binary_stl(&mut file, &VERTICES).unwrap();
+        file.seek(SeekFrom::Start(0)).unwrap();
+
+        assert!(!is_ascii(&mut file));
+        let read: Result<Vec<[f32; 3]>, _> = process_binary_iter(file).collect();
+        assert_eq!(read.unwrap(), VERTICES);
+    }
+}
@@ -0,0 +1,32 @@
+pub fn fan_triangulate(face: &[usize]) -> Vec<[usize; 3]> {
+    if face.len() < 3 {
+        return Vec::new();
+    }
+    (1..face.len() - 1)
+        .map(|i| [face[0], face[i], face[i + 1]])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triangle() {
+        assert_eq!(fan_triangulate(&[4, 5, 6]), vec![[4, 5, 6]]);
+    }
+
+    #[test]
+    fn test_pentagon() {
+        assert_eq!(
+            fan_triangulate(&[0, 1, 2, 3, 4]),
+            vec![[0, 1, 2], [0, 2, 3], [0, 3, 4]]
+        );
+    }
+
+    #[test]
+    fn test_degenerate() {
+        assert!(fan_triangulate(&[0, 1]).is_empty());
+        assert!(fan_triangulate(&[]).is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SurfaceFormat {
+    Stl,
+    Obj,
+    Ply,
+    Off,
+    Vtp,
+}
+
+impl SurfaceFormat {
+    pub fn from_path(path: &str) -> Result<Self, Box<dyn Error>> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("stl") => Ok(SurfaceFormat::Stl),
+            Some("obj") => Ok(SurfaceFormat::Obj),
+            Some("ply") => Ok(SurfaceFormat::Ply),
+            Some("off") => Ok(SurfaceFormat::Off),
+            Some("vtp" | "vtk") => Ok(SurfaceFormat::Vtp),
+            _ => Err(format!("Unsupported surface format for '{}'", path).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_extensions() {
+        assert_eq!(
+            SurfaceFormat::from_path("a.stl").unwrap(),
+            SurfaceFormat::Stl
+        );
+        assert_eq!(
+            SurfaceFormat::from_path("dir/a.OBJ").unwrap(),
+            SurfaceFormat::Obj
+        );
+        assert_eq!(
+            SurfaceFormat::from_path("a.ply").unwrap(),
+            SurfaceFormat::Ply
+        );
+        assert_eq!(
+            SurfaceFormat::from_path("a.off").unwrap(),
+            SurfaceFormat::Off
+        );
+        assert_eq!(
+            SurfaceFormat::from_path("a.vtp").unwrap(),
+            SurfaceFormat::Vtp
+        );
+        assert_eq!(
+            SurfaceFormat::from_path("a.vtk").unwrap(),
+            SurfaceFormat::Vtp
+        );
+    }
+
+    #[test]
+    fn test_unknown_extension() {
+        assert!(SurfaceFormat::from_path("a.txt").is_err());
+        assert!(SurfaceFormat::from_path("stl").is_err());
+    }
+}
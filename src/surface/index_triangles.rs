@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+/// Merges bitwise-identical vertices of a triangle soup (three vertices per
+/// triangle, as produced by the format readers) into an indexed mesh.
+pub fn index_triangles(vertices: &[[f32; 3]]) -> (Vec<[f32; 3]>, Vec<[usize; 3]>) {
+    let mut unique: Vec<[f32; 3]> = Vec::new();
+    let mut lookup: HashMap<[u32; 3], usize> = HashMap::new();
+
+    let mut index_of = |v: [f32; 3]| {
+        let key = [v[0].to_bits(), v[1].to_bits(), v[2].to_bits()];
+        *lookup.entry(key).or_insert_with(|| {
+            unique.push(v);
+            unique.len() - 1
+        })
+    };
+
+    let (chunks, _) = vertices.as_chunks::<3>();
+    let triangles = chunks
+        .iter()
+        .map(|tri| [index_of(tri[0]), index_of(tri[1]), index_of(tri[2])])
+        .collect();
+
+    (unique, triangles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_vertices_are_merged() {
+        let vertices = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+        ];
+        let (points, triangles) = index_triangles(&vertices);
+
+        assert_eq!(points.len(), 4);
+        assert_eq!(triangles, vec![[0, 1, 2], [0, 2, 3]]);
+    }
+
+    #[test]
+    fn test_incomplete_triangle_is_dropped() {
+        let vertices = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]];
+        let (points, triangles) = index_triangles(&vertices);
+
+        assert!(points.is_empty());
+        assert!(triangles.is_empty());
+    }
+}
@@ -0,0 +1,14 @@
+pub mod format;
+pub use format::SurfaceFormat;
+pub mod fan_triangulate;
+pub use fan_triangulate::fan_triangulate;
+pub mod index_triangles;
+pub use index_triangles::index_triangles;
+pub mod strip_triangulate;
+pub use strip_triangulate::strip_triangulate;
+pub mod process_surface_iter;
+pub use process_surface_iter::process_surface_iter;
+pub mod write_surface;
+pub use write_surface::write_surface;
+pub mod triangle_normal;
+pub use triangle_normal::triangle_normal;
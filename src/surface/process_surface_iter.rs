@@ -0,0 +1,86 @@
+use std::error::Error;
+use std::fs::File;
+
+use super::SurfaceFormat;
+use crate::obj::process_obj_iter;
+use crate::off::process_off_iter;
+use crate::ply::process_ply_iter;
+use crate::stl::is_ascii;
+use crate::stl::process_ascii_iter;
+use crate::stl::process_binary_iter;
+use crate::vtk::VtpProcessor;
+
+pub type VertexIter = Box<dyn Iterator<Item = Result<[f32; 3], Box<dyn Error>>>>;
+
+/// Opens a surface file and streams its triangle vertices, three per
+/// triangle, picking the reader from the file extension.
+pub fn process_surface_iter(path: &str) -> Result<VertexIter, Box<dyn Error>> {
+    let iter: VertexIter = match SurfaceFormat::from_path(path)? {
+        SurfaceFormat::Stl => {
+            let mut file = File::open(path)?;
+            if is_ascii(&mut file) {
+                Box::new(process_ascii_iter(file))
+            } else {
+                Box::new(process_binary_iter(file))
+            }
+        }
+        SurfaceFormat::Obj => Box::new(process_obj_iter(File::open(path)?)),
+        SurfaceFormat::Ply => Box::new(process_ply_iter(File::open(path)?)),
+        SurfaceFormat::Off => Box::new(process_off_iter(File::open(path)?)),
+        SurfaceFormat::Vtp => {
+            let triangles = VtpProcessor::from_file(path)?.triangles()?;
+            Box::new(triangles.into_iter().map(Ok))
+        }
+    };
+
+    Ok(iter)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_same_triangles_from_every_format() {
+        let dir = tempdir().unwrap();
+        let sources = [
+            (
+                "a.stl",
+                "solid a\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nvertex 1 0 0\nvertex 0 1 \
+                 0\nendloop\nendfacet\nendsolid a\n",
+            ),
+            ("a.obj", "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n"),
+            (
+                "a.ply",
+                "ply\nformat ascii 1.0\nelement vertex 3\nproperty float x\nproperty float \
+                 y\nproperty float z\nelement face 1\nproperty list uchar int \
+                 vertex_indices\nend_header\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n",
+            ),
+            ("a.off", "OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n"),
+        ];
+
+        for (name, content) in sources {
+            let path = dir.path().join(name);
+            fs::write(&path, content).unwrap();
+
+            let vertices: Result<Vec<[f32; 3]>, _> = process_surface_iter(path.to_str().unwrap())
+                .unwrap()
+                .collect();
+            assert_eq!(
+                vertices.unwrap(),
+                vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_unsupported_extension() {
+        assert!(process_surface_iter("surface.xyz").is_err());
+    }
+}
@@ -0,0 +1,40 @@
+/// Splits a triangle strip into triangles. Every other triangle is flipped so
+/// all keep the orientation of the first, as VTK does.
+pub fn strip_triangulate(strip: &[usize]) -> Vec<[usize; 3]> {
+    if strip.len() < 3 {
+        return Vec::new();
+    }
+    (0..strip.len() - 2)
+        .map(|i| {
+            if i % 2 == 0 {
+                [strip[i], strip[i + 1], strip[i + 2]]
+            } else {
+                [strip[i + 1], strip[i], strip[i + 2]]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triangle() {
+        assert_eq!(strip_triangulate(&[4, 5, 6]), vec![[4, 5, 6]]);
+    }
+
+    #[test]
+    fn test_strip() {
+        assert_eq!(
+            strip_triangulate(&[0, 1, 2, 3, 4]),
+            vec![[0, 1, 2], [2, 1, 3], [2, 3, 4]]
+        );
+    }
+
+    #[test]
+    fn test_degenerate() {
+        assert!(strip_triangulate(&[0, 1]).is_empty());
+        assert!(strip_triangulate(&[]).is_empty());
+    }
+}
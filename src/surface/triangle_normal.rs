@@ -0,0 +1,33 @@
+use crate::coordinates::vector::cross;
+use crate::coordinates::vector::sub;
+use crate::coordinates::vector::unit;
+
+/// Unit normal of a triangle following the right-hand rule, or zero for a
+/// degenerate triangle.
+pub fn triangle_normal(tri: &[[f32; 3]; 3]) -> [f32; 3] {
+    let p = tri.map(|v| v.map(f64::from));
+    unit(cross(sub(p[1], p[0]), sub(p[2], p[0]))).map(|x| x as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter_clockwise_triangle() {
+        let tri = [[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 2.0, 0.0]];
+        assert_eq!(triangle_normal(&tri), [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_clockwise_triangle() {
+        let tri = [[0.0, 0.0, 0.0], [0.0, 0.0, 3.0], [0.0, 3.0, 0.0]];
+        assert_eq!(triangle_normal(&tri), [-1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_degenerate_triangle() {
+        let tri = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [2.0, 0.0, 0.0]];
+        assert_eq!(triangle_normal(&tri), [0.0, 0.0, 0.0]);
+    }
+}
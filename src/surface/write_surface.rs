@@ -0,0 +1,65 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+
+use super::SurfaceFormat;
+use crate::obj::write_obj;
+use crate::off::write_off;
+use crate::ply::write_ply;
+use crate::stl::write_binary_stl;
+use crate::vtk::VtpProcessor;
+
+/// Writes a triangle soup (three vertices per triangle) to `path`, picking
+/// the writer from the file extension.
+pub fn write_surface(path: &str, vertices: &[[f32; 3]]) -> Result<(), Box<dyn Error>> {
+    match SurfaceFormat::from_path(path)? {
+        SurfaceFormat::Stl => write_buffered(path, |w| write_binary_stl(w, vertices)),
+        SurfaceFormat::Obj => write_buffered(path, |w| write_obj(w, vertices)),
+        SurfaceFormat::Ply => write_buffered(path, |w| write_ply(w, vertices)),
+        SurfaceFormat::Off => write_buffered(path, |w| write_off(w, vertices)),
+        SurfaceFormat::Vtp => {
+            VtpProcessor::from_triangles(vertices, None, false)?.write_to_file(path)
+        }
+    }
+}
+
+fn write_buffered(
+    path: &str,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::surface::process_surface_iter;
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempdir().unwrap();
+        let vertices = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.5],
+        ];
+
+        for name in ["out.stl", "out.obj", "out.ply", "out.off"] {
+            let path = dir.path().join(name);
+            let path = path.to_str().unwrap();
+            write_surface(path, &vertices).unwrap();
+
+            let read: Result<Vec<[f32; 3]>, _> = process_surface_iter(path).unwrap().collect();
+            assert_eq!(read.unwrap(), vertices, "{}", name);
+        }
+    }
+}
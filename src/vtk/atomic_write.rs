@@ -0,0 +1,106 @@
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tempfile::NamedTempFile;
+
+/// Writes `path` through a temporary file in the same directory that is
+/// renamed over `path` once complete, so a failed write never leaves a
+/// truncated file behind.
+pub fn write_atomic<F>(path: &str, write: F) -> Result<(), Box<dyn Error>>
+where
+    F: FnOnce(&mut File) -> Result<(), Box<dyn Error>>,
+{
+    let target = Path::new(path);
+    let dir = match target.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut temp = NamedTempFile::new_in(dir)?;
+    write(temp.as_file_mut())?;
+    temp.as_file().sync_all()?;
+
+    // Temporary files are private; keep the permissions of the file being
+    // replaced, or use the usual ones for a new file
+    match fs::metadata(target) {
+        Ok(metadata) => temp.as_file().set_permissions(metadata.permissions())?,
+        Err(_) => {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                temp.as_file()
+                    .set_permissions(fs::Permissions::from_mode(0o644))?;
+            }
+        }
+    }
+
+    temp.persist(target)?;
+    Ok(())
+}
+
+/// Copies an existing `path` to `path.bak`, returning the backup path, or
+/// `None` if there is nothing to back up.
+pub fn backup_file(path: &str) -> Result<Option<PathBuf>, Box<dyn Error>> {
+    if !Path::new(path).exists() {
+        return Ok(None);
+    }
+    let backup = PathBuf::from(format!("{}.bak", path));
+    fs::copy(path, &backup)?;
+    Ok(Some(backup))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_write_atomic_replaces_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("surface.vtp");
+        let path = path.to_str().unwrap();
+        fs::write(path, "old").unwrap();
+
+        write_atomic(path, |file| Ok(file.write_all(b"new")?)).unwrap();
+
+        assert_eq!(fs::read_to_string(path).unwrap(), "new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_failed_write_keeps_original() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("surface.vtp");
+        let path = path.to_str().unwrap();
+        fs::write(path, "old").unwrap();
+
+        let result = write_atomic(path, |file| {
+            file.write_all(b"partial")?;
+            Err("disk full".into())
+        });
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(path).unwrap(), "old");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_backup_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("surface.vtp");
+        let path = path.to_str().unwrap();
+
+        assert_eq!(backup_file(path).unwrap(), None);
+
+        fs::write(path, "old").unwrap();
+        let backup = backup_file(path).unwrap().unwrap();
+        assert_eq!(backup, PathBuf::from(format!("{}.bak", path)));
+        assert_eq!(fs::read_to_string(backup).unwrap(), "old");
+    }
+}
@@ -0,0 +1,79 @@
+use super::geometry::CellKind;
+use crate::coordinates::vector::cross;
+use crate::coordinates::vector::length;
+use crate::coordinates::vector::sub;
+use crate::surface::fan_triangulate;
+use crate::surface::strip_triangulate;
+
+/// Area of each cell: polygons are fan triangulated and strips split into
+/// their triangles; vertices and lines have no area.
+pub fn calculate_cell_areas(
+    points: &[f64],
+    connectivity: &[usize],
+    offsets: &[usize],
+    kinds: &[CellKind],
+) -> Vec<f64> {
+    let mut areas = Vec::with_capacity(offsets.len());
+    let mut conn_idx = 0;
+
+    for (&offset, &kind) in offsets.iter().zip(kinds) {
+        let cell_indices = &connectivity[conn_idx..offset];
+        conn_idx = offset;
+
+        let triangles = match kind {
+            CellKind::Vertex | CellKind::Line => Vec::new(),
+            CellKind::Polygon => fan_triangulate(cell_indices),
+            CellKind::Strip => strip_triangulate(cell_indices),
+        };
+        areas.push(
+            triangles
+                .iter()
+                .map(|triangle| triangle_area(points, triangle))
+                .sum(),
+        );
+    }
+
+    areas
+}
+
+fn triangle_area(points: &[f64], triangle: &[usize; 3]) -> f64 {
+    let p = triangle.map(|i| [points[3 * i], points[3 * i + 1], points[3 * i + 2]]);
+    0.5 * length(cross(sub(p[1], p[0]), sub(p[2], p[0])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POINTS: [f64; 12] = [
+        0.0, 0.0, 0.0, // vertex 0
+        1.0, 0.0, 0.0, // vertex 1
+        0.0, 1.0, 0.0, // vertex 2
+        1.0, 1.0, 0.0, // vertex 3
+    ];
+
+    #[test]
+    fn test_strip_area() {
+        let areas = calculate_cell_areas(&POINTS, &[0, 1, 2, 3], &[4], &[CellKind::Strip]);
+        assert!((areas[0] - 1.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_mixed_cells() {
+        let connectivity = [3, 0, 1, 2, 0, 1, 3, 2, 0, 1, 2];
+        let offsets = [1, 4, 8, 11];
+        let kinds = [
+            CellKind::Vertex,
+            CellKind::Line,
+            CellKind::Polygon,
+            CellKind::Strip,
+        ];
+
+        let areas = calculate_cell_areas(&POINTS, &connectivity, &offsets, &kinds);
+        assert_eq!(areas.len(), 4);
+        assert_eq!(areas[0], 0.0);
+        assert_eq!(areas[1], 0.0);
+        assert!((areas[2] - 1.0).abs() < 1e-10);
+        assert!((areas[3] - 0.5).abs() < 1e-10);
+    }
+}
@@ -0,0 +1,456 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use vtkio::model::Attribute;
+use vtkio::model::Attributes;
+use vtkio::model::DataArray;
+use vtkio::model::FieldArray;
+use vtkio::model::IOBuffer;
+use vtkio::model::PolyDataPiece;
+use vtkio::model::VertexNumbers;
+
+use super::data_type::buffer_to_f64;
+use super::data_type::DataType;
+use super::pieces::gather_buffer;
+use crate::surface::strip_triangulate;
+
+/// How cells on the boundary of a clip region are treated.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ClipMode {
+    /// Keep whole cells whose centroid is inside
+    #[default]
+    Centroid,
+    /// Cut cells along the boundary, interpolating point data
+    Exact,
+}
+
+impl FromStr for ClipMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "centroid" => Ok(ClipMode::Centroid),
+            "exact" => Ok(ClipMode::Exact),
+            _ => Err(format!(
+                "Unknown clip mode '{}', expected centroid or exact",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ClipMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClipMode::Centroid => write!(f, "centroid"),
+            ClipMode::Exact => write!(f, "exact"),
+        }
+    }
+}
+
+/// A point of the clipped surface: an input point, or a point on the edge
+/// between two input points at parameter `t` from the first.
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    Point(usize),
+    Edge(usize, usize, f64),
+}
+
+/// Output points, shared between the cells that use them
+struct Points<'a> {
+    values: &'a [f64],
+    sources: Vec<Source>,
+    point_index: HashMap<usize, u64>,
+    edge_index: HashMap<(usize, usize), u64>,
+}
+
+impl Points<'_> {
+    fn point(&mut self, i: usize) -> u64 {
+        *self.point_index.entry(i).or_insert_with(|| {
+            self.sources.push(Source::Point(i));
+            self.sources.len() as u64 - 1
+        })
+    }
+
+    /// Where the implicit function crosses zero on the edge `a`-`b`
+    fn crossing(&mut self, a: usize, b: usize) -> u64 {
+        let (a, b) = if a < b { (a, b) } else { (b, a) };
+        let t = self.values[a] / (self.values[a] - self.values[b]);
+        *self.edge_index.entry((a, b)).or_insert_with(|| {
+            self.sources.push(Source::Edge(a, b, t));
+            self.sources.len() as u64 - 1
+        })
+    }
+
+    fn inside(&self, i: usize) -> bool {
+        self.values[i] <= 0.0
+    }
+}
+
+#[derive(Default)]
+struct Cells {
+    connectivity: Vec<u64>,
+    offsets: Vec<u64>,
+    /// Input cell each output cell was cut from
+    parents: Vec<usize>,
+}
+
+impl Cells {
+    fn push(&mut self, point_ids: &[u64], parent: usize) {
+        self.connectivity.extend_from_slice(point_ids);
+        self.offsets.push(self.connectivity.len() as u64);
+        self.parents.push(parent);
+    }
+
+    fn into_vertex_numbers(self) -> Option<VertexNumbers> {
+        (!self.offsets.is_empty()).then_some(VertexNumbers::XML {
+            connectivity: self.connectivity,
+            offsets: self.offsets,
+        })
+    }
+}
+
+/// Cuts the cells of `piece` to the part where `value` is zero or
+/// negative. Polygons are clipped against the linear interpolation of
+/// `value` along their edges, so curved boundaries are approximated by
+/// straight segments; strips are split into triangles and become polygons.
+/// Point data is interpolated onto the new points and cell data is copied
+/// from the cell each piece was cut from. Bit arrays are dropped.
+pub fn clip_poly_data(
+    piece: &PolyDataPiece,
+    value: impl Fn([f64; 3]) -> f64,
+) -> Result<PolyDataPiece, Box<dyn Error>> {
+    let coordinates = buffer_to_f64(&piece.points).ok_or("Unsupported point data format")?;
+    let values: Vec<f64> = coordinates
+        .as_chunks::<3>()
+        .0
+        .iter()
+        .map(|&p| value(p))
+        .collect();
+    let num_points = values.len();
+
+    let mut points = Points {
+        values: &values,
+        sources: Vec::new(),
+        point_index: HashMap::new(),
+        edge_index: HashMap::new(),
+    };
+    let mut verts = Cells::default();
+    let mut lines = Cells::default();
+    let mut polys = Cells::default();
+    let mut strip_polys = Cells::default();
+
+    let kinds = [&piece.verts, &piece.lines, &piece.polys, &piece.strips];
+    let mut cell = 0;
+    for (k, cells) in kinds.into_iter().enumerate() {
+        let Some(cells) = cells else {
+            continue;
+        };
+        let (connectivity, offsets) = cells.clone().into_xml();
+        let mut start = 0;
+        for &end in &offsets {
+            let ids: Vec<usize> = connectivity[start..end as usize]
+                .iter()
+                .map(|&i| i as usize)
+                .collect();
+            if let Some(&i) = ids.iter().find(|&&i| i >= num_points) {
+                return Err(format!("Point index {} out of range", i).into());
+            }
+            match k {
+                0 => clip_vertex(&ids, cell, &mut points, &mut verts),
+                1 => clip_polyline(&ids, cell, &mut points, &mut lines),
+                2 => clip_polygon(&ids, cell, &mut points, &mut polys),
+                _ => {
+                    for triangle in strip_triangulate(&ids) {
+                        clip_polygon(&triangle, cell, &mut points, &mut strip_polys);
+                    }
+                }
+            }
+            start = end as usize;
+            cell += 1;
+        }
+    }
+    let num_cells = cell;
+
+    // Polygons cut from strips follow the other polygons
+    for (i, &offset) in strip_polys.offsets.iter().enumerate() {
+        let start = i
+            .checked_sub(1)
+            .map_or(0, |j| strip_polys.offsets[j] as usize);
+        let ids = &strip_polys.connectivity[start..offset as usize];
+        polys.push(ids, strip_polys.parents[i]);
+    }
+
+    let parents: Vec<usize> = [&verts.parents, &lines.parents, &polys.parents]
+        .into_iter()
+        .flatten()
+        .copied()
+        .collect();
+    let sources = points.sources;
+
+    let point_type = DataType::of(&piece.points).unwrap_or_default();
+    let new_coordinates = interpolate(&coordinates, 3, &sources);
+
+    Ok(PolyDataPiece {
+        points: point_type.buffer_from_f64(&new_coordinates),
+        verts: verts.into_vertex_numbers(),
+        lines: lines.into_vertex_numbers(),
+        polys: polys.into_vertex_numbers(),
+        strips: None,
+        data: Attributes {
+            point: map_arrays(&piece.data.point, |data| {
+                let num_comp = data.len().checked_div(num_points).unwrap_or(1);
+                let values = buffer_to_f64(data)?;
+                Some(DataType::of(data)?.buffer_from_f64(&interpolate(&values, num_comp, &sources)))
+            }),
+            cell: map_arrays(&piece.data.cell, |data| {
+                let num_comp = data.len().checked_div(num_cells).unwrap_or(1);
+                gather_buffer(data, &parents, num_comp)
+            }),
+        },
+    })
+}
+
+/// Keeps the points of a (poly)vertex that are inside
+fn clip_vertex(ids: &[usize], cell: usize, points: &mut Points, out: &mut Cells) {
+    let mut kept = Vec::new();
+    for &i in ids {
+        if points.inside(i) {
+            kept.push(points.point(i));
+        }
+    }
+    if !kept.is_empty() {
+        out.push(&kept, cell);
+    }
+}
+
+/// Splits a polyline into the runs that are inside
+fn clip_polyline(ids: &[usize], cell: usize, points: &mut Points, out: &mut Cells) {
+    let mut run = Vec::new();
+    for (j, &i) in ids.iter().enumerate() {
+        let previous = j.checked_sub(1).map(|j| ids[j]);
+        match (previous, points.inside(i)) {
+            (Some(p), true) if !points.inside(p) => run.push(points.crossing(p, i)),
+            (Some(p), false) if points.inside(p) => {
+                run.push(points.crossing(p, i));
+                if run.len() >= 2 {
+                    out.push(&run, cell);
+                }
+                run.clear();
+            }
+            _ => {}
+        }
+        if points.inside(i) {
+            run.push(points.point(i));
+        }
+    }
+    if run.len() >= 2 {
+        out.push(&run, cell);
+    }
+}
+
+/// Sutherland-Hodgman clipping of a polygon against the implicit function
+fn clip_polygon(ids: &[usize], cell: usize, points: &mut Points, out: &mut Cells) {
+    let mut clipped = Vec::with_capacity(ids.len() + 2);
+    for (j, &current) in ids.iter().enumerate() {
+        let next = ids[(j + 1) % ids.len()];
+        if points.inside(current) {
+            clipped.push(points.point(current));
+        }
+        if points.inside(current) != points.inside(next) {
+            clipped.push(points.crossing(current, next));
+        }
+    }
+    if clipped.len() >= 3 {
+        out.push(&clipped, cell);
+    }
+}
+
+/// Values of tuples with `num_comp` components at the output points
+fn interpolate(values: &[f64], num_comp: usize, sources: &[Source]) -> Vec<f64> {
+    let tuple = |i: usize| &values[i * num_comp..(i + 1) * num_comp];
+    let mut out = Vec::with_capacity(sources.len() * num_comp);
+    for source in sources {
+        match *source {
+            Source::Point(i) => out.extend_from_slice(tuple(i)),
+            Source::Edge(a, b, t) => out.extend(
+                tuple(a)
+                    .iter()
+                    .zip(tuple(b))
+                    .map(|(va, vb)| va + t * (vb - va)),
+            ),
+        }
+    }
+    out
+}
+
+fn map_arrays(
+    attributes: &[Attribute],
+    map: impl Fn(&IOBuffer) -> Option<IOBuffer>,
+) -> Vec<Attribute> {
+    attributes
+        .iter()
+        .filter_map(|attr| match attr {
+            Attribute::DataArray(arr) => Some(Attribute::DataArray(DataArray {
+                name: arr.name.clone(),
+                elem: arr.elem.clone(),
+                data: map(&arr.data)?,
+            })),
+            Attribute::Field { name, data_array } => Some(Attribute::Field {
+                name: name.clone(),
+                data_array: data_array
+                    .iter()
+                    .filter_map(|arr| {
+                        Some(FieldArray {
+                            name: arr.name.clone(),
+                            elem: arr.elem,
+                            data: map(&arr.data)?,
+                        })
+                    })
+                    .collect(),
+            }),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use vtkio::model::ElementType;
+
+    use super::*;
+
+    fn scalars(name: &str, data: IOBuffer) -> Attribute {
+        Attribute::DataArray(DataArray {
+            name: name.to_string(),
+            elem: ElementType::Scalars {
+                num_comp: 1,
+                lookup_table: None,
+            },
+            data,
+        })
+    }
+
+    /// A unit square in the z = 0 plane with a line along its bottom edge
+    fn square() -> PolyDataPiece {
+        PolyDataPiece {
+            points: IOBuffer::F64(vec![
+                0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0,
+            ]),
+            verts: None,
+            lines: Some(VertexNumbers::XML {
+                connectivity: vec![0, 1],
+                offsets: vec![2],
+            }),
+            polys: Some(VertexNumbers::XML {
+                connectivity: vec![0, 1, 2, 3],
+                offsets: vec![4],
+            }),
+            strips: None,
+            data: Attributes {
+                point: vec![scalars("x", IOBuffer::F32(vec![0.0, 1.0, 1.0, 0.0]))],
+                cell: vec![scalars("id", IOBuffer::I32(vec![7, 8]))],
+            },
+        }
+    }
+
+    #[test]
+    fn test_clip_square_in_half() {
+        let clipped = clip_poly_data(&square(), |p| p[0] - 0.25).unwrap();
+
+        assert_eq!(
+            clipped.points,
+            IOBuffer::F64(vec![
+                0.0, 0.0, 0.0, 0.25, 0.0, 0.0, 0.25, 1.0, 0.0, 0.0, 1.0, 0.0,
+            ])
+        );
+        let (conn, offs) = clipped.lines.unwrap().into_xml();
+        assert_eq!((conn, offs), (vec![0, 1], vec![2]));
+        // The crossing on the shared bottom edge is reused
+        let (conn, offs) = clipped.polys.unwrap().into_xml();
+        assert_eq!((conn, offs), (vec![0, 1, 2, 3], vec![4]));
+
+        assert_eq!(
+            clipped.data.point,
+            vec![scalars("x", IOBuffer::F32(vec![0.0, 0.25, 0.25, 0.0]))]
+        );
+        assert_eq!(
+            clipped.data.cell,
+            vec![scalars("id", IOBuffer::I32(vec![7, 8]))]
+        );
+    }
+
+    #[test]
+    fn test_clip_corner() {
+        let clipped = clip_poly_data(&square(), |p| 1.5 - p[0] - p[1]).unwrap();
+        assert_eq!(clipped.lines, None);
+        let (conn, _) = clipped.polys.unwrap().into_xml();
+        assert_eq!(conn.len(), 3);
+        assert_eq!(
+            clipped.data.cell,
+            vec![scalars("id", IOBuffer::I32(vec![8]))]
+        );
+    }
+
+    #[test]
+    fn test_clip_everything_or_nothing() {
+        let all = clip_poly_data(&square(), |_| -1.0).unwrap();
+        assert_eq!(all.points, square().points);
+        assert_eq!(all.polys, square().polys);
+
+        let none = clip_poly_data(&square(), |_| 1.0).unwrap();
+        assert_eq!(none.points.len(), 0);
+        assert_eq!(none.polys, None);
+    }
+
+    #[test]
+    fn test_polyline_split() {
+        let piece = PolyDataPiece {
+            points: IOBuffer::F64(vec![
+                0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 2.0, 0.0, 0.0, 3.0, 0.0, 0.0,
+            ]),
+            verts: None,
+            lines: Some(VertexNumbers::XML {
+                connectivity: vec![0, 1, 2, 3],
+                offsets: vec![4],
+            }),
+            polys: None,
+            strips: None,
+            data: Attributes::new(),
+        };
+        // Keeps x < 0.5 and x > 2.5
+        let clipped = clip_poly_data(&piece, |p| 0.25 - (p[0] - 1.5).powi(2) / 4.0).unwrap();
+        let (conn, offs) = clipped.lines.unwrap().into_xml();
+        assert_eq!(offs, vec![2, 4]);
+        assert_eq!(conn.len(), 4);
+    }
+
+    #[test]
+    fn test_strip_becomes_polygons() {
+        let piece = PolyDataPiece {
+            points: IOBuffer::F64(vec![
+                0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0,
+            ]),
+            verts: None,
+            lines: None,
+            polys: None,
+            strips: Some(VertexNumbers::XML {
+                connectivity: vec![0, 1, 2, 3],
+                offsets: vec![4],
+            }),
+            data: Attributes {
+                point: Vec::new(),
+                cell: vec![scalars("id", IOBuffer::I32(vec![5]))],
+            },
+        };
+        let clipped = clip_poly_data(&piece, |_| -1.0).unwrap();
+        assert_eq!(clipped.strips, None);
+        let (_, offs) = clipped.polys.unwrap().into_xml();
+        assert_eq!(offs, vec![3, 6]);
+        assert_eq!(
+            clipped.data.cell,
+            vec![scalars("id", IOBuffer::I32(vec![5, 5]))]
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use std::borrow::Cow;
+use std::error::Error;
+use std::io::Read;
+
+use super::xml_writer::base64;
+
+/// Rewrites the compressed inline `format="binary"` data arrays of an XML
+/// VTK file as uncompressed ones. vtkio decompresses appended data but
+/// reads inline arrays as if they were never compressed. Files without a
+/// compressor are returned unchanged.
+pub fn decompress_inline_arrays(xml: &[u8]) -> Result<Cow<'_, [u8]>, Box<dyn Error>> {
+    // Raw appended data need not be text, only the part before it is read
+    let end = find(xml, b"<AppendedData", 0).unwrap_or(xml.len());
+    let Some(header) = find(xml, b"<VTKFile", 0).and_then(|start| {
+        let stop = find(xml, b">", start)?;
+        std::str::from_utf8(&xml[start..stop]).ok()
+    }) else {
+        return Ok(Cow::Borrowed(xml));
+    };
+    let compressor = match attribute(header, "compressor") {
+        Some("vtkZLibDataCompressor") => Compressor::Zlib,
+        Some("vtkLZ4DataCompressor") => Compressor::Lz4,
+        Some(other) if !other.is_empty() => {
+            return Err(format!("Unsupported compressor '{}'", other).into())
+        }
+        _ => return Ok(Cow::Borrowed(xml)),
+    };
+    let header = Header {
+        // vtkio assumes 64-bit headers when the type is missing
+        wide: attribute(header, "header_type") != Some("UInt32"),
+        big_endian: attribute(header, "byte_order") == Some("BigEndian"),
+    };
+
+    let mut out = Vec::with_capacity(xml.len());
+    let mut pos = 0;
+    while let Some(start) = find(&xml[..end], b"<DataArray", pos) {
+        let tag_end = find(xml, b">", start).ok_or("Unterminated DataArray tag")?;
+        let tag = std::str::from_utf8(&xml[start..tag_end])?;
+        if tag.ends_with('/') || attribute(tag, "format") != Some("binary") {
+            out.extend_from_slice(&xml[pos..=tag_end]);
+            pos = tag_end + 1;
+            continue;
+        }
+        let data_end = find(xml, b"</DataArray", tag_end).ok_or("Unterminated DataArray")?;
+        let encoded = std::str::from_utf8(&xml[tag_end + 1..data_end])?.trim();
+        let bytes = header.decompress(&base64_decode(encoded)?, compressor)?;
+
+        let mut data = header.encode(bytes.len());
+        data.extend(bytes);
+        out.extend_from_slice(&xml[pos..=tag_end]);
+        out.extend_from_slice(base64(&data).as_bytes());
+        pos = data_end;
+    }
+    out.extend_from_slice(&xml[pos..]);
+    Ok(Cow::Owned(out))
+}
+
+#[derive(Clone, Copy)]
+enum Compressor {
+    Zlib,
+    Lz4,
+}
+
+/// Width and byte order of the size numbers preceding the data
+#[derive(Clone, Copy)]
+struct Header {
+    wide: bool,
+    big_endian: bool,
+}
+
+impl Header {
+    fn size(self) -> usize {
+        if self.wide {
+            8
+        } else {
+            4
+        }
+    }
+
+    fn read(self, bytes: &[u8], index: usize) -> Result<usize, Box<dyn Error>> {
+        let size = self.size();
+        let bytes = bytes
+            .get(index * size..(index + 1) * size)
+            .ok_or("Truncated compression header")?;
+        let mut buf = [0u8; 8];
+        let value = if self.big_endian {
+            buf[8 - size..].copy_from_slice(bytes);
+            u64::from_be_bytes(buf)
+        } else {
+            buf[..size].copy_from_slice(bytes);
+            u64::from_le_bytes(buf)
+        };
+        Ok(value as usize)
+    }
+
+    fn encode(self, value: usize) -> Vec<u8> {
+        match (self.wide, self.big_endian) {
+            (true, false) => (value as u64).to_le_bytes().to_vec(),
+            (true, true) => (value as u64).to_be_bytes().to_vec(),
+            (false, false) => (value as u32).to_le_bytes().to_vec(),
+            (false, true) => (value as u32).to_be_bytes().to_vec(),
+        }
+    }
+
+    /// Inflates data laid out as `[nb][nu][np][nc_1]...[nc_nb]` followed by
+    /// the compressed blocks, where nb is the number of blocks, nu the block
+    /// size, np the size of a last partial block and nc_i the compressed
+    /// size of block i.
+    fn decompress(self, bytes: &[u8], compressor: Compressor) -> Result<Vec<u8>, Box<dyn Error>> {
+        let num_blocks = self.read(bytes, 0)?;
+        let block_size = self.read(bytes, 1)?;
+        let last_size = self.read(bytes, 2)?;
+
+        let mut out = Vec::with_capacity(num_blocks * block_size);
+        let mut start = self.size() * (3 + num_blocks);
+        for i in 0..num_blocks {
+            let compressed_size = self.read(bytes, 3 + i)?;
+            let block = bytes
+                .get(start..start + compressed_size)
+                .ok_or("Truncated compressed data")?;
+            start += compressed_size;
+            let size = if i + 1 == num_blocks && last_size != 0 {
+                last_size
+            } else {
+                block_size
+            };
+            match compressor {
+                Compressor::Zlib => {
+                    let mut decoder = flate2::read::ZlibDecoder::new(block);
+                    decoder.read_to_end(&mut out)?;
+                }
+                Compressor::Lz4 => {
+                    out.extend(lz4_flex::block::decompress(block, size)?);
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|i| from + i)
+}
+
+/// The value of attribute `name` in an opening tag
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let key = format!(" {}=\"", name);
+    let start = tag.find(&key)? + key.len();
+    let len = tag[start..].find('"')?;
+    Some(&tag[start..start + len])
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let mut n = 0u32;
+    let mut bits = 0;
+    for c in text.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' | b' ' | b'\n' | b'\r' | b'\t' => continue,
+            _ => return Err(format!("Invalid base64 character '{}'", c as char).into()),
+        };
+        n = n << 6 | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((n >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_decode() {
+        for text in ["", "f", "fo", "foo", "foob", "fooba", "foobar"] {
+            let decoded = base64_decode(&base64(text.as_bytes())).unwrap();
+            assert_eq!(decoded, text.as_bytes());
+        }
+        assert!(base64_decode("Zm9v!").is_err());
+    }
+
+    #[test]
+    fn test_uncompressed_unchanged() {
+        let xml = b"<VTKFile type=\"PolyData\"><DataArray format=\"binary\">AAAA</DataArray>";
+        assert!(matches!(
+            decompress_inline_arrays(xml).unwrap(),
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_decompress_zlib() {
+        let values: Vec<u8> = (0..100).collect();
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::new(6));
+        std::io::Write::write_all(&mut encoder, &values).unwrap();
+        let block = encoder.finish().unwrap();
+        let mut data: Vec<u8> = [1, 1 << 15, 100, block.len() as u32]
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect();
+        data.extend(block);
+
+        let xml = format!(
+            "<VTKFile header_type=\"UInt32\" compressor=\"vtkZLibDataCompressor\">\n<DataArray \
+             type=\"UInt8\" format=\"binary\">\n{}\n</DataArray>",
+            base64(&data)
+        );
+        let out = decompress_inline_arrays(xml.as_bytes()).unwrap();
+        let out = std::str::from_utf8(&out).unwrap();
+        let start = out.find("binary\">").unwrap() + 8;
+        let end = out.find("</DataArray>").unwrap();
+        let decoded = base64_decode(&out[start..end]).unwrap();
+        assert_eq!(&decoded[..4], &100u32.to_le_bytes());
+        assert_eq!(&decoded[4..], &values[..]);
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use super::geometry::CellKind;
+use super::surface_mesh::SurfaceMesh;
+use crate::surface::strip_triangulate;
+
+/// Labels the cells of `mesh` by the connected region they belong to, with
+/// cells connected when they share an edge. Only cells whose entry in
+/// `include` is true take part; the others are labelled `None`.
+///
+/// Regions are numbered from 0 by decreasing area, ties going to the region
+/// holding the lower cell index.
+pub fn connected_regions(
+    mesh: &SurfaceMesh,
+    include: Option<&[bool]>,
+) -> Result<Vec<Option<usize>>, Box<dyn Error>> {
+    if let Some(include) = include {
+        if include.len() != mesh.num_cells() {
+            return Err(format!(
+                "Expected {} cell flags, got {}",
+                mesh.num_cells(),
+                include.len()
+            )
+            .into());
+        }
+    }
+    let included = |cell: usize| include.is_none_or(|include| include[cell]);
+
+    let mut sets = DisjointSets::new(mesh.num_cells());
+    let mut edge_owners: HashMap<(usize, usize), usize> = HashMap::new();
+    for (index, cell) in mesh.cells().enumerate() {
+        if !included(index) {
+            continue;
+        }
+        for (a, b) in cell_edges(cell.kind, cell.point_ids) {
+            let edge = (a.min(b), a.max(b));
+            match edge_owners.get(&edge) {
+                Some(&owner) => sets.union(owner, index),
+                None => {
+                    edge_owners.insert(edge, index);
+                }
+            }
+        }
+    }
+
+    // Area and first cell of each root
+    let areas = mesh.areas();
+    let mut roots: HashMap<usize, (f64, usize)> = HashMap::new();
+    for cell in (0..mesh.num_cells()).filter(|&c| included(c)) {
+        let root = roots.entry(sets.find(cell)).or_insert((0.0, cell));
+        root.0 += areas[cell];
+    }
+    let mut order: Vec<(usize, f64, usize)> = roots
+        .into_iter()
+        .map(|(root, (area, first))| (root, area, first))
+        .collect();
+    order.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.2.cmp(&b.2)));
+    let labels: HashMap<usize, usize> = order
+        .iter()
+        .enumerate()
+        .map(|(label, &(root, _, _))| (root, label))
+        .collect();
+
+    Ok((0..mesh.num_cells())
+        .map(|cell| included(cell).then(|| labels[&sets.find(cell)]))
+        .collect())
+}
+
+fn cell_edges(kind: CellKind, point_ids: &[usize]) -> Vec<(usize, usize)> {
+    let n = point_ids.len();
+    match kind {
+        CellKind::Vertex => Vec::new(),
+        CellKind::Line => point_ids.windows(2).map(|w| (w[0], w[1])).collect(),
+        CellKind::Polygon if n < 2 => Vec::new(),
+        CellKind::Polygon => (0..n)
+            .map(|i| (point_ids[i], point_ids[(i + 1) % n]))
+            .collect(),
+        CellKind::Strip => strip_triangulate(point_ids)
+            .into_iter()
+            .flat_map(|[a, b, c]| [(a, b), (b, c), (c, a)])
+            .collect(),
+    }
+}
+
+/// Union-find over cell indices, with path halving and union by size
+struct DisjointSets {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        DisjointSets {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        let (large, small) = if self.sizes[a] >= self.sizes[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parents[small] = large;
+        self.sizes[large] += self.sizes[small];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A row of three unit squares, then a separate small triangle
+    fn mesh() -> SurfaceMesh {
+        let points = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [3.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [2.0, 1.0, 0.0],
+            [3.0, 1.0, 0.0],
+            [5.0, 0.0, 0.0],
+            [6.0, 0.0, 0.0],
+            [5.0, 1.0, 0.0],
+        ];
+        SurfaceMesh::new(
+            points,
+            vec![0, 1, 5, 4, 1, 2, 6, 5, 2, 3, 7, 6, 8, 9, 10],
+            vec![4, 8, 12, 15],
+            vec![CellKind::Polygon; 4],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_all_cells() {
+        let labels = connected_regions(&mesh(), None).unwrap();
+        assert_eq!(labels, vec![Some(0), Some(0), Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn test_restricted() {
+        let include = [true, false, true, true];
+        let labels = connected_regions(&mesh(), Some(&include)).unwrap();
+        // The two unit squares come before the triangle, by area, then by
+        // their first cell
+        assert_eq!(labels, vec![Some(0), None, Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn test_shared_point_is_not_connected() {
+        let points = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [-1.0, 0.0, 0.0],
+            [0.0, -1.0, 0.0],
+        ];
+        let mesh = SurfaceMesh::new(
+            points,
+            vec![0, 1, 2, 0, 3, 4],
+            vec![3, 6],
+            vec![CellKind::Polygon; 2],
+        )
+        .unwrap();
+        let labels = connected_regions(&mesh, None).unwrap();
+        assert_eq!(labels, vec![Some(0), Some(1)]);
+    }
+
+    #[test]
+    fn test_wrong_length() {
+        assert!(connected_regions(&mesh(), Some(&[true])).is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+use std::str::FromStr;
+
+use vtkio::model::IOBuffer;
+
+/// Numeric type of a data array, named as in VTK XML files.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DataType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Float32,
+    #[default]
+    Float64,
+}
+
+impl DataType {
+    /// Stores `values` as this type. Values are rounded when stored as
+    /// integers, and clamped to the range of the type.
+    pub fn buffer_from_f64(self, values: &[f64]) -> IOBuffer {
+        macro_rules! convert {
+            ($variant:ident, $ty:ty) => {
+                IOBuffer::$variant(values.iter().map(|&v| v.round() as $ty).collect())
+            };
+        }
+        match self {
+            DataType::Int8 => convert!(I8, i8),
+            DataType::UInt8 => convert!(U8, u8),
+            DataType::Int16 => convert!(I16, i16),
+            DataType::UInt16 => convert!(U16, u16),
+            DataType::Int32 => convert!(I32, i32),
+            DataType::UInt32 => convert!(U32, u32),
+            DataType::Int64 => convert!(I64, i64),
+            DataType::UInt64 => convert!(U64, u64),
+            DataType::Float32 => IOBuffer::F32(values.iter().map(|&v| v as f32).collect()),
+            DataType::Float64 => IOBuffer::F64(values.to_vec()),
+        }
+    }
+
+    /// Type of `buffer`, or `None` for bit arrays
+    pub fn of(buffer: &IOBuffer) -> Option<Self> {
+        match buffer {
+            IOBuffer::Bit(_) => None,
+            IOBuffer::I8(_) => Some(DataType::Int8),
+            IOBuffer::U8(_) => Some(DataType::UInt8),
+            IOBuffer::I16(_) => Some(DataType::Int16),
+            IOBuffer::U16(_) => Some(DataType::UInt16),
+            IOBuffer::I32(_) => Some(DataType::Int32),
+            IOBuffer::U32(_) => Some(DataType::UInt32),
+            IOBuffer::I64(_) => Some(DataType::Int64),
+            IOBuffer::U64(_) => Some(DataType::UInt64),
+            IOBuffer::F32(_) => Some(DataType::Float32),
+            IOBuffer::F64(_) => Some(DataType::Float64),
+        }
+    }
+}
+
+/// Converts any numeric buffer to `f64`. Bit arrays are packed and have no
+/// per-element value, so they are not supported.
+pub fn buffer_to_f64(buffer: &IOBuffer) -> Option<Vec<f64>> {
+    macro_rules! convert {
+        ($data:expr) => {
+            Some($data.iter().map(|&v| v as f64).collect())
+        };
+    }
+    match buffer {
+        IOBuffer::Bit(_) => None,
+        IOBuffer::I8(data) => convert!(data),
+        IOBuffer::U8(data) => convert!(data),
+        IOBuffer::I16(data) => convert!(data),
+        IOBuffer::U16(data) => convert!(data),
+        IOBuffer::I32(data) => convert!(data),
+        IOBuffer::U32(data) => convert!(data),
+        IOBuffer::I64(data) => convert!(data),
+        IOBuffer::U64(data) => convert!(data),
+        IOBuffer::F32(data) => convert!(data),
+        IOBuffer::F64(data) => Some(data.clone()),
+    }
+}
+
+impl FromStr for DataType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "int8" | "i8" => Ok(DataType::Int8),
+            "uint8" | "u8" => Ok(DataType::UInt8),
+            "int16" | "i16" => Ok(DataType::Int16),
+            "uint16" | "u16" => Ok(DataType::UInt16),
+            "int32" | "i32" => Ok(DataType::Int32),
+            "uint32" | "u32" => Ok(DataType::UInt32),
+            "int64" | "i64" => Ok(DataType::Int64),
+            "uint64" | "u64" => Ok(DataType::UInt64),
+            "float32" | "f32" | "float" => Ok(DataType::Float32),
+            "float64" | "f64" | "double" => Ok(DataType::Float64),
+            _ => Err(format!("Unknown data type '{}'", s)),
+        }
+    }
+}
+
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            DataType::Int8 => "Int8",
+            DataType::UInt8 => "UInt8",
+            DataType::Int16 => "Int16",
+            DataType::UInt16 => "UInt16",
+            DataType::Int32 => "Int32",
+            DataType::UInt32 => "UInt32",
+            DataType::Int64 => "Int64",
+            DataType::UInt64 => "UInt64",
+            DataType::Float32 => "Float32",
+            DataType::Float64 => "Float64",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_buffers_to_f64() {
+        assert_eq!(
+            buffer_to_f64(&IOBuffer::I32(vec![-1, 2])),
+            Some(vec![-1.0, 2.0])
+        );
+        assert_eq!(
+            buffer_to_f64(&IOBuffer::U8(vec![0, 255])),
+            Some(vec![0.0, 255.0])
+        );
+        assert_eq!(buffer_to_f64(&IOBuffer::U64(vec![7])), Some(vec![7.0]));
+        assert_eq!(buffer_to_f64(&IOBuffer::Bit(vec![1])), None);
+    }
+
+    #[test]
+    fn test_buffer_from_f64() {
+        let values = [0.4, 0.6, -3.0, 300.0];
+        assert_eq!(
+            DataType::UInt8.buffer_from_f64(&values),
+            IOBuffer::U8(vec![0, 1, 0, 255])
+        );
+        assert_eq!(
+            DataType::Int32.buffer_from_f64(&values),
+            IOBuffer::I32(vec![0, 1, -3, 300])
+        );
+        assert_eq!(
+            DataType::Float32.buffer_from_f64(&values),
+            IOBuffer::F32(vec![0.4, 0.6, -3.0, 300.0])
+        );
+    }
+
+    #[test]
+    fn test_round_trip_names() {
+        for data_type in [DataType::Int8, DataType::UInt64, DataType::Float32] {
+            assert_eq!(data_type.to_string().parse::<DataType>(), Ok(data_type));
+        }
+        assert_eq!("double".parse::<DataType>(), Ok(DataType::Float64));
+        assert!("complex".parse::<DataType>().is_err());
+    }
+}
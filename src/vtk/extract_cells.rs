@@ -0,0 +1,229 @@
+use std::error::Error;
+
+use vtkio::model::Attribute;
+use vtkio::model::Attributes;
+use vtkio::model::DataArray;
+use vtkio::model::FieldArray;
+use vtkio::model::IOBuffer;
+use vtkio::model::PolyDataPiece;
+use vtkio::model::VertexNumbers;
+
+use super::pieces::gather_buffer;
+
+/// Copies the cells of `piece` whose entry in `keep` is true, indexed in
+/// VTK cell order, together with the points they use. Points are
+/// renumbered, and point and cell data arrays are carried over; bit arrays
+/// are dropped.
+pub fn extract_cells(
+    piece: &PolyDataPiece,
+    keep: &[bool],
+) -> Result<PolyDataPiece, Box<dyn Error>> {
+    let cells = [&piece.verts, &piece.lines, &piece.polys, &piece.strips];
+    let num_cells: usize = cells
+        .iter()
+        .flat_map(|c| c.as_ref())
+        .map(|c| c.num_cells())
+        .sum();
+    if keep.len() != num_cells {
+        return Err(format!(
+            "Cell selection has {} entries for {} cells",
+            keep.len(),
+            num_cells
+        )
+        .into());
+    }
+    let num_points = piece.points.len() / 3;
+
+    let mut kept_cells = Vec::new();
+    let mut kept_kinds: [Option<(Vec<u64>, Vec<u64>)>; 4] = Default::default();
+    let mut cell = 0;
+    for (kind, cells) in kept_kinds.iter_mut().zip(cells) {
+        let Some(cells) = cells else {
+            continue;
+        };
+        let (connectivity, offsets) = cells.clone().into_xml();
+        let mut start = 0;
+        for &end in &offsets {
+            let end = end as usize;
+            if keep[cell] {
+                let (conn, offs) = kind.get_or_insert_with(Default::default);
+                conn.extend_from_slice(&connectivity[start..end]);
+                offs.push(conn.len() as u64);
+                kept_cells.push(cell);
+            }
+            start = end;
+            cell += 1;
+        }
+    }
+
+    // Renumber the points in their original order
+    let mut new_index = vec![None; num_points];
+    for (conn, _) in kept_kinds.iter().flatten() {
+        for &i in conn {
+            let slot = new_index
+                .get_mut(i as usize)
+                .ok_or_else(|| format!("Point index {} out of range", i))?;
+            *slot = Some(0);
+        }
+    }
+    let mut kept_points = Vec::new();
+    for (i, slot) in new_index.iter_mut().enumerate() {
+        if slot.is_some() {
+            *slot = Some(kept_points.len() as u64);
+            kept_points.push(i);
+        }
+    }
+
+    let [verts, lines, polys, strips] = kept_kinds.map(|kind| {
+        kind.map(|(connectivity, offsets)| VertexNumbers::XML {
+            connectivity: connectivity
+                .iter()
+                .map(|&i| new_index[i as usize].unwrap())
+                .collect(),
+            offsets,
+        })
+    });
+
+    Ok(PolyDataPiece {
+        points: gather_buffer(&piece.points, &kept_points, 3)
+            .ok_or("Unsupported point data format")?,
+        verts,
+        lines,
+        polys,
+        strips,
+        data: Attributes {
+            point: gather_attributes(&piece.data.point, &kept_points, num_points),
+            cell: gather_attributes(&piece.data.cell, &kept_cells, num_cells),
+        },
+    })
+}
+
+fn gather_attributes(attributes: &[Attribute], indices: &[usize], size: usize) -> Vec<Attribute> {
+    let gather = |data: &IOBuffer| {
+        let num_comp = data.len().checked_div(size).unwrap_or(1);
+        gather_buffer(data, indices, num_comp)
+    };
+
+    attributes
+        .iter()
+        .filter_map(|attr| match attr {
+            Attribute::DataArray(arr) => Some(Attribute::DataArray(DataArray {
+                name: arr.name.clone(),
+                elem: arr.elem.clone(),
+                data: gather(&arr.data)?,
+            })),
+            Attribute::Field { name, data_array } => Some(Attribute::Field {
+                name: name.clone(),
+                data_array: data_array
+                    .iter()
+                    .filter_map(|arr| {
+                        Some(FieldArray {
+                            name: arr.name.clone(),
+                            elem: arr.elem,
+                            data: gather(&arr.data)?,
+                        })
+                    })
+                    .collect(),
+            }),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use vtkio::model::ElementType;
+
+    use super::*;
+
+    fn array(name: &str, num_comp: u32, data: IOBuffer) -> Attribute {
+        Attribute::DataArray(DataArray {
+            name: name.to_string(),
+            elem: ElementType::Scalars {
+                num_comp,
+                lookup_table: None,
+            },
+            data,
+        })
+    }
+
+    /// A line and two triangles sharing an edge
+    fn piece() -> PolyDataPiece {
+        PolyDataPiece {
+            points: IOBuffer::F32(vec![
+                0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0,
+            ]),
+            verts: None,
+            lines: Some(VertexNumbers::XML {
+                connectivity: vec![0, 3],
+                offsets: vec![2],
+            }),
+            polys: Some(VertexNumbers::XML {
+                connectivity: vec![0, 1, 2, 0, 2, 3],
+                offsets: vec![3, 6],
+            }),
+            strips: None,
+            data: Attributes {
+                point: vec![array("t", 1, IOBuffer::I32(vec![10, 11, 12, 13]))],
+                cell: vec![
+                    array("p", 1, IOBuffer::F64(vec![0.0, 1.0, 2.0])),
+                    array("U", 2, IOBuffer::F32(vec![0.0, 0.5, 1.0, 1.5, 2.0, 2.5])),
+                ],
+            },
+        }
+    }
+
+    #[test]
+    fn test_extract_one_polygon() {
+        let extracted = extract_cells(&piece(), &[false, false, true]).unwrap();
+
+        assert_eq!(
+            extracted.points,
+            IOBuffer::F32(vec![0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0])
+        );
+        assert_eq!(extracted.lines, None);
+        assert_eq!(
+            extracted.polys,
+            Some(VertexNumbers::XML {
+                connectivity: vec![0, 1, 2],
+                offsets: vec![3],
+            })
+        );
+        assert_eq!(
+            extracted.data.point,
+            vec![array("t", 1, IOBuffer::I32(vec![10, 12, 13]))]
+        );
+        assert_eq!(
+            extracted.data.cell,
+            vec![
+                array("p", 1, IOBuffer::F64(vec![2.0])),
+                array("U", 2, IOBuffer::F32(vec![2.0, 2.5])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_across_kinds() {
+        let extracted = extract_cells(&piece(), &[true, true, false]).unwrap();
+        let (conn, offs) = extracted.lines.unwrap().into_xml();
+        assert_eq!((conn, offs), (vec![0, 3], vec![2]));
+        let (conn, offs) = extracted.polys.unwrap().into_xml();
+        assert_eq!((conn, offs), (vec![0, 1, 2], vec![3]));
+        assert_eq!(extracted.points.len(), 12);
+    }
+
+    #[test]
+    fn test_extract_nothing() {
+        let extracted = extract_cells(&piece(), &[false; 3]).unwrap();
+        assert_eq!(extracted.points.len(), 0);
+        assert_eq!(extracted.polys, None);
+        assert_eq!(
+            extracted.data.cell[0],
+            array("p", 1, IOBuffer::F64(Vec::new()))
+        );
+    }
+
+    #[test]
+    fn test_selection_length() {
+        assert!(extract_cells(&piece(), &[true; 2]).is_err());
+    }
+}
@@ -1,4 +1,6 @@
 use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
 
 use vtkio::model::Attribute;
 use vtkio::model::DataArray;
@@ -6,86 +8,486 @@ use vtkio::model::ElementType;
 use vtkio::model::IOBuffer;
 use vtkio::Vtk;
 
+use super::data_type::buffer_to_f64;
+use super::data_type::DataType;
 use super::reader::get_poly_data;
 use super::reader::get_poly_data_mut;
 
+/// Where a field's values live: one per cell (`CellData`) or one per point
+/// (`PointData`).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FieldLocation {
+    #[default]
+    Cell,
+    Point,
+}
+
+impl FromStr for FieldLocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cell" => Ok(FieldLocation::Cell),
+            "point" => Ok(FieldLocation::Point),
+            _ => Err(format!(
+                "Unknown field location '{}', expected cell or point",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for FieldLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldLocation::Cell => write!(f, "cell"),
+            FieldLocation::Point => write!(f, "point"),
+        }
+    }
+}
+
+/// Selects one value per tuple of a multi-component field.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Component {
+    Index(usize),
+    Magnitude,
+}
+
+impl FromStr for Component {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "x" => Ok(Component::Index(0)),
+            "y" => Ok(Component::Index(1)),
+            "z" => Ok(Component::Index(2)),
+            "mag" | "magnitude" => Ok(Component::Magnitude),
+            other => other.parse().map(Component::Index).map_err(|_| {
+                format!(
+                    "Unknown component '{}', expected x, y, z, mag or an index",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Component::Index(0) => write!(f, "x"),
+            Component::Index(1) => write!(f, "y"),
+            Component::Index(2) => write!(f, "z"),
+            Component::Index(index) => write!(f, "{}", index),
+            Component::Magnitude => write!(f, "mag"),
+        }
+    }
+}
+
 pub struct FieldManager;
 
 impl FieldManager {
     pub fn get_field(vtk: &Vtk, field_name: &str) -> Result<Vec<f64>, Box<dyn Error>> {
-        let poly_data = get_poly_data(vtk)?;
+        Self::get_field_at(vtk, field_name, FieldLocation::Cell)
+    }
 
-        let field_data = poly_data
-            .data
-            .cell
-            .iter()
-            .find_map(|attr| match attr {
-                Attribute::DataArray(arr) if arr.name == field_name => Some(&arr.data),
-                _ => None,
-            })
-            .ok_or_else(|| format!("Field '{}' not found", field_name))?;
+    pub fn get_field_at(
+        vtk: &Vtk,
+        field_name: &str,
+        location: FieldLocation,
+    ) -> Result<Vec<f64>, Box<dyn Error>> {
+        let field_data = Self::find_data(Self::attributes(vtk, location)?, field_name)
+            .ok_or_else(|| format!("{} '{}' not found", Self::label(location), field_name))?;
 
         Self::convert_to_f64(field_data, field_name)
     }
 
+    /// Number of components per tuple, e.g. 3 for vectors and 9 for tensors
+    pub fn num_components_at(
+        vtk: &Vtk,
+        field_name: &str,
+        location: FieldLocation,
+    ) -> Result<usize, Box<dyn Error>> {
+        Self::find_array(Self::attributes(vtk, location)?, field_name)
+            .map(|(_, num_comp)| num_comp)
+            .ok_or_else(|| format!("{} '{}' not found", Self::label(location), field_name).into())
+    }
+
+    pub fn get_vectors_at(
+        vtk: &Vtk,
+        field_name: &str,
+        location: FieldLocation,
+    ) -> Result<Vec<[f64; 3]>, Box<dyn Error>> {
+        let num_comp = Self::num_components_at(vtk, field_name, location)?;
+        if num_comp != 3 {
+            return Err(format!(
+                "Field '{}' has {} components, expected a vector",
+                field_name, num_comp
+            )
+            .into());
+        }
+        let values = Self::get_field_at(vtk, field_name, location)?;
+        Ok(values
+            .as_chunks::<3>()
+            .0
+            .iter()
+            .map(|&[x, y, z]| [x, y, z])
+            .collect())
+    }
+
+    /// One value per tuple: either a single component or the magnitude. Scalar
+    /// fields are returned as they are.
+    pub fn get_component_at(
+        vtk: &Vtk,
+        field_name: &str,
+        component: Component,
+        location: FieldLocation,
+    ) -> Result<Vec<f64>, Box<dyn Error>> {
+        let num_comp = Self::num_components_at(vtk, field_name, location)?;
+        let values = Self::get_field_at(vtk, field_name, location)?;
+        if num_comp <= 1 {
+            return Ok(values);
+        }
+
+        let tuples = values.chunks(num_comp);
+        match component {
+            Component::Magnitude => Ok(tuples
+                .map(|tuple| tuple.iter().map(|v| v * v).sum::<f64>().sqrt())
+                .collect()),
+            Component::Index(index) if index < num_comp => {
+                Ok(tuples.map(|tuple| tuple[index]).collect())
+            }
+            Component::Index(index) => Err(format!(
+                "Component {} out of range for field '{}' with {} components",
+                index, field_name, num_comp
+            )
+            .into()),
+        }
+    }
+
     pub fn list_fields(vtk: &Vtk) -> Result<Vec<String>, Box<dyn Error>> {
-        let poly_data = get_poly_data(vtk)?;
-        Ok(poly_data
-            .data
-            .cell
+        Self::list_fields_at(vtk, FieldLocation::Cell)
+    }
+
+    pub fn list_fields_at(
+        vtk: &Vtk,
+        location: FieldLocation,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(Self::attributes(vtk, location)?
             .iter()
-            .filter_map(|attr| match attr {
-                Attribute::DataArray(arr) => Some(arr.name.clone()),
-                _ => None,
+            .flat_map(|attr| match attr {
+                Attribute::DataArray(arr) => vec![arr.name.clone()],
+                Attribute::Field { data_array, .. } => {
+                    data_array.iter().map(|arr| arr.name.clone()).collect()
+                }
             })
             .collect())
     }
 
     pub fn field_exists(vtk: &Vtk, field_name: &str) -> Result<bool, Box<dyn Error>> {
-        let poly_data = get_poly_data(vtk)?;
-        Ok(poly_data
-            .data
-            .cell
-            .iter()
-            .any(|attr| matches!(attr, Attribute::DataArray(arr) if arr.name == field_name)))
+        Self::field_exists_at(vtk, field_name, FieldLocation::Cell)
+    }
+
+    pub fn field_exists_at(
+        vtk: &Vtk,
+        field_name: &str,
+        location: FieldLocation,
+    ) -> Result<bool, Box<dyn Error>> {
+        Ok(Self::find_data(Self::attributes(vtk, location)?, field_name).is_some())
     }
 
     pub fn remove_field(vtk: &mut Vtk, field_name: &str) -> Result<(), Box<dyn Error>> {
-        let poly_data = get_poly_data_mut(vtk)?;
-        if let Some(pos) =
-            poly_data.data.cell.iter().position(
-                |attr| matches!(attr, Attribute::DataArray(arr) if arr.name == field_name),
-            )
+        Self::remove_field_at(vtk, field_name, FieldLocation::Cell)
+    }
+
+    pub fn remove_field_at(
+        vtk: &mut Vtk,
+        field_name: &str,
+        location: FieldLocation,
+    ) -> Result<(), Box<dyn Error>> {
+        let attributes = Self::attributes_mut(vtk, location)?;
+        if let Some(pos) = attributes
+            .iter()
+            .position(|attr| matches!(attr, Attribute::DataArray(arr) if arr.name == field_name))
         {
-            poly_data.data.cell.remove(pos);
+            attributes.remove(pos);
+        }
+        // Legacy files group arrays under FIELD attributes
+        for attr in attributes.iter_mut() {
+            if let Attribute::Field { data_array, .. } = attr {
+                data_array.retain(|arr| arr.name != field_name);
+            }
         }
+        attributes.retain(
+            |attr| !matches!(attr, Attribute::Field { data_array, .. } if data_array.is_empty()),
+        );
         Ok(())
     }
 
     pub fn add_field(vtk: &mut Vtk, field_name: &str, data: &[f64]) -> Result<(), Box<dyn Error>> {
-        if Self::field_exists(vtk, field_name)? {
-            return Err(format!("Field '{}' already exists", field_name).into());
+        Self::add_field_at(vtk, field_name, data, FieldLocation::Cell)
+    }
+
+    pub fn add_field_at(
+        vtk: &mut Vtk,
+        field_name: &str,
+        data: &[f64],
+        location: FieldLocation,
+    ) -> Result<(), Box<dyn Error>> {
+        Self::add_field_as(vtk, field_name, data, DataType::Float64, location)
+    }
+
+    /// Adds a scalar field stored as `data_type`, e.g. `Float32` to halve
+    /// the file size or `UInt8` for masks.
+    pub fn add_field_as(
+        vtk: &mut Vtk,
+        field_name: &str,
+        data: &[f64],
+        data_type: DataType,
+        location: FieldLocation,
+    ) -> Result<(), Box<dyn Error>> {
+        if Self::field_exists_at(vtk, field_name, location)? {
+            return Err(
+                format!("{} '{}' already exists", Self::label(location), field_name).into(),
+            );
         }
 
-        let poly_data = get_poly_data_mut(vtk)?;
         let data_array = DataArray {
             name: field_name.to_string(),
             elem: ElementType::Scalars {
                 num_comp: 1,
                 lookup_table: None,
             },
-            data: IOBuffer::F64(data.to_vec()),
+            data: data_type.buffer_from_f64(data),
         };
 
-        poly_data.data.cell.push(Attribute::DataArray(data_array));
+        Self::attributes_mut(vtk, location)?.push(Attribute::DataArray(data_array));
         Ok(())
     }
 
-    fn convert_to_f64(buffer: &IOBuffer, field_name: &str) -> Result<Vec<f64>, Box<dyn Error>> {
-        match buffer {
-            IOBuffer::F64(data) => Ok(data.clone()),
-            IOBuffer::F32(data) => Ok(data.iter().map(|&x| x as f64).collect()),
-            _ => Err(format!("Unsupported data type for field '{}'", field_name).into()),
+    pub fn add_vector_field_at(
+        vtk: &mut Vtk,
+        field_name: &str,
+        data: &[[f64; 3]],
+        location: FieldLocation,
+    ) -> Result<(), Box<dyn Error>> {
+        if Self::field_exists_at(vtk, field_name, location)? {
+            return Err(
+                format!("{} '{}' already exists", Self::label(location), field_name).into(),
+            );
+        }
+
+        let data_array = DataArray {
+            name: field_name.to_string(),
+            elem: ElementType::Vectors,
+            data: IOBuffer::F64(data.as_flattened().to_vec()),
+        };
+
+        Self::attributes_mut(vtk, location)?.push(Attribute::DataArray(data_array));
+        Ok(())
+    }
+
+    fn attributes(vtk: &Vtk, location: FieldLocation) -> Result<&Vec<Attribute>, Box<dyn Error>> {
+        let poly_data = get_poly_data(vtk)?;
+        Ok(match location {
+            FieldLocation::Cell => &poly_data.data.cell,
+            FieldLocation::Point => &poly_data.data.point,
+        })
+    }
+
+    fn attributes_mut(
+        vtk: &mut Vtk,
+        location: FieldLocation,
+    ) -> Result<&mut Vec<Attribute>, Box<dyn Error>> {
+        let poly_data = get_poly_data_mut(vtk)?;
+        Ok(match location {
+            FieldLocation::Cell => &mut poly_data.data.cell,
+            FieldLocation::Point => &mut poly_data.data.point,
+        })
+    }
+
+    fn label(location: FieldLocation) -> &'static str {
+        match location {
+            FieldLocation::Cell => "Field",
+            FieldLocation::Point => "Point field",
+        }
+    }
+
+    fn find_data<'a>(attributes: &'a [Attribute], field_name: &str) -> Option<&'a IOBuffer> {
+        Self::find_array(attributes, field_name).map(|(data, _)| data)
+    }
+
+    /// The array named `field_name` and its number of components
+    fn find_array<'a>(
+        attributes: &'a [Attribute],
+        field_name: &str,
+    ) -> Option<(&'a IOBuffer, usize)> {
+        attributes.iter().find_map(|attr| match attr {
+            Attribute::DataArray(arr) if arr.name == field_name => {
+                Some((&arr.data, Self::num_components(&arr.elem)))
+            }
+            Attribute::Field { data_array, .. } => data_array
+                .iter()
+                .find(|arr| arr.name == field_name)
+                .map(|arr| (&arr.data, arr.elem.max(1) as usize)),
+            _ => None,
+        })
+    }
+
+    pub(crate) fn num_components(elem: &ElementType) -> usize {
+        match elem {
+            ElementType::ColorScalars(n) | ElementType::TCoords(n) | ElementType::Generic(n) => {
+                *n as usize
+            }
+            ElementType::Scalars { num_comp, .. } => *num_comp as usize,
+            ElementType::LookupTable => 4,
+            ElementType::Vectors | ElementType::Normals => 3,
+            ElementType::Tensors => 9,
         }
     }
+
+    fn convert_to_f64(buffer: &IOBuffer, field_name: &str) -> Result<Vec<f64>, Box<dyn Error>> {
+        buffer_to_f64(buffer)
+            .ok_or_else(|| format!("Unsupported data type for field '{}'", field_name).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vtk::surface_conversion::SurfaceConverter;
+
+    fn triangle_vtk() -> Vtk {
+        let vertices = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+        SurfaceConverter::from_triangles(&vertices, None, false).unwrap()
+    }
+
+    #[test]
+    fn test_locations_are_separate() {
+        let mut vtk = triangle_vtk();
+        FieldManager::add_field_at(&mut vtk, "T", &[1.0, 2.0, 3.0], FieldLocation::Point).unwrap();
+        FieldManager::add_field(&mut vtk, "T", &[4.0]).unwrap();
+
+        assert_eq!(
+            FieldManager::get_field_at(&vtk, "T", FieldLocation::Point).unwrap(),
+            vec![1.0, 2.0, 3.0]
+        );
+        assert_eq!(FieldManager::get_field(&vtk, "T").unwrap(), vec![4.0]);
+        assert_eq!(
+            FieldManager::list_fields_at(&vtk, FieldLocation::Point).unwrap(),
+            vec!["T"]
+        );
+
+        FieldManager::remove_field_at(&mut vtk, "T", FieldLocation::Point).unwrap();
+        assert!(!FieldManager::field_exists_at(&vtk, "T", FieldLocation::Point).unwrap());
+        assert!(FieldManager::field_exists(&vtk, "T").unwrap());
+    }
+
+    #[test]
+    fn test_add_existing_point_field() {
+        let mut vtk = triangle_vtk();
+        FieldManager::add_field_at(&mut vtk, "T", &[1.0, 2.0, 3.0], FieldLocation::Point).unwrap();
+
+        let result = FieldManager::add_field_at(&mut vtk, "T", &[0.0; 3], FieldLocation::Point);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Point field 'T' already exists"
+        );
+    }
+
+    #[test]
+    fn test_vector_field() {
+        let mut vtk = triangle_vtk();
+        let vectors = [[3.0, 4.0, 0.0]];
+        FieldManager::add_vector_field_at(&mut vtk, "U", &vectors, FieldLocation::Cell).unwrap();
+
+        assert_eq!(
+            FieldManager::num_components_at(&vtk, "U", FieldLocation::Cell).unwrap(),
+            3
+        );
+        assert_eq!(
+            FieldManager::get_vectors_at(&vtk, "U", FieldLocation::Cell).unwrap(),
+            vectors
+        );
+        assert_eq!(
+            FieldManager::get_component_at(&vtk, "U", Component::Magnitude, FieldLocation::Cell)
+                .unwrap(),
+            vec![5.0]
+        );
+        assert_eq!(
+            FieldManager::get_component_at(&vtk, "U", Component::Index(1), FieldLocation::Cell)
+                .unwrap(),
+            vec![4.0]
+        );
+        assert!(FieldManager::get_component_at(
+            &vtk,
+            "U",
+            Component::Index(3),
+            FieldLocation::Cell
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_scalar_is_not_a_vector() {
+        let mut vtk = triangle_vtk();
+        FieldManager::add_field(&mut vtk, "p", &[1.0]).unwrap();
+
+        assert!(FieldManager::get_vectors_at(&vtk, "p", FieldLocation::Cell).is_err());
+        assert_eq!(
+            FieldManager::get_component_at(&vtk, "p", Component::Magnitude, FieldLocation::Cell)
+                .unwrap(),
+            vec![1.0]
+        );
+    }
+
+    #[test]
+    fn test_component_from_str() {
+        assert_eq!("x".parse::<Component>(), Ok(Component::Index(0)));
+        assert_eq!("Z".parse::<Component>(), Ok(Component::Index(2)));
+        assert_eq!("4".parse::<Component>(), Ok(Component::Index(4)));
+        assert_eq!("mag".parse::<Component>(), Ok(Component::Magnitude));
+        assert!("w".parse::<Component>().is_err());
+        assert_eq!(Component::Index(1).to_string(), "y");
+    }
+
+    #[test]
+    fn test_integer_fields() {
+        let mut vtk = triangle_vtk();
+        FieldManager::add_field_as(
+            &mut vtk,
+            "mask",
+            &[1.0],
+            DataType::UInt8,
+            FieldLocation::Cell,
+        )
+        .unwrap();
+        FieldManager::add_field_as(
+            &mut vtk,
+            "T",
+            &[1.5, 2.5, 3.5],
+            DataType::Float32,
+            FieldLocation::Point,
+        )
+        .unwrap();
+
+        let poly_data = get_poly_data(&vtk).unwrap();
+        assert!(matches!(
+            FieldManager::find_data(&poly_data.data.cell, "mask"),
+            Some(IOBuffer::U8(_))
+        ));
+        assert_eq!(FieldManager::get_field(&vtk, "mask").unwrap(), vec![1.0]);
+        assert_eq!(
+            FieldManager::get_field_at(&vtk, "T", FieldLocation::Point).unwrap(),
+            vec![1.5, 2.5, 3.5]
+        );
+    }
+
+    #[test]
+    fn test_location_from_str() {
+        assert_eq!("cell".parse::<FieldLocation>(), Ok(FieldLocation::Cell));
+        assert_eq!("Point".parse::<FieldLocation>(), Ok(FieldLocation::Point));
+        assert!("face".parse::<FieldLocation>().is_err());
+        assert_eq!(FieldLocation::Point.to_string(), "point");
+    }
 }
@@ -0,0 +1,228 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use super::field_manager::FieldLocation;
+use super::locator::PointLocator;
+use super::locator::SurfaceLocator;
+use super::surface_mesh::SurfaceMesh;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MapMethod {
+    /// The value of the nearest source cell, by centroid, or of the
+    /// nearest source point for point fields
+    #[default]
+    Nearest,
+    /// The value at the closest point of the source surface: that of the
+    /// cell holding it, or interpolated from the triangle's points for
+    /// point fields
+    ClosestPoint,
+}
+
+impl FromStr for MapMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "nearest" | "nearest-cell" => Ok(MapMethod::Nearest),
+            "closest-point" | "closest" => Ok(MapMethod::ClosestPoint),
+            _ => Err(format!(
+                "Unknown mapping method '{}', expected nearest or closest-point",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for MapMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MapMethod::Nearest => write!(f, "nearest"),
+            MapMethod::ClosestPoint => write!(f, "closest-point"),
+        }
+    }
+}
+
+/// Where each target cell or point takes its values from on the source
+/// surface, built once and applied to any number of fields.
+#[derive(Debug, Clone)]
+pub struct FieldMapping {
+    location: FieldLocation,
+    sources: Vec<Option<Source>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    Index(usize),
+    Interpolated([usize; 3], [f64; 3]),
+}
+
+impl FieldMapping {
+    /// Maps the cells (by centroid) or points of `target` onto `source`.
+    /// Targets further than `max_distance` from their source get no value.
+    pub fn new(
+        source: &SurfaceMesh,
+        target: &SurfaceMesh,
+        location: FieldLocation,
+        method: MapMethod,
+        max_distance: Option<f64>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let queries = match location {
+            FieldLocation::Cell => target.centroids(),
+            FieldLocation::Point => target.points(),
+        };
+        let within = |distance: f64| max_distance.is_none_or(|max| distance <= max);
+
+        let sources = match method {
+            MapMethod::Nearest => {
+                let locator = match location {
+                    FieldLocation::Cell => PointLocator::new(source.centroids()),
+                    FieldLocation::Point => PointLocator::new(source.points()),
+                };
+                queries
+                    .iter()
+                    .map(|&q| {
+                        locator
+                            .nearest(q)
+                            .filter(|&(_, distance)| within(distance))
+                            .map(|(i, _)| Source::Index(i))
+                    })
+                    .collect()
+            }
+            MapMethod::ClosestPoint => {
+                let locator = SurfaceLocator::new(source);
+                if locator.is_empty() {
+                    return Err("The source has no polygons or strips to map from".into());
+                }
+                queries
+                    .iter()
+                    .map(|&q| {
+                        let hit = locator.closest_point(q).filter(|h| within(h.distance))?;
+                        Some(match location {
+                            FieldLocation::Cell => Source::Index(hit.cell),
+                            FieldLocation::Point => Source::Interpolated(hit.triangle, hit.weights),
+                        })
+                    })
+                    .collect()
+            }
+        };
+        Ok(FieldMapping { location, sources })
+    }
+
+    pub fn location(&self) -> FieldLocation {
+        self.location
+    }
+
+    /// Number of targets without a source within the maximum distance
+    pub fn num_unmapped(&self) -> usize {
+        self.sources.iter().filter(|s| s.is_none()).count()
+    }
+
+    /// Maps source values with `num_comp` components onto the targets,
+    /// giving NaN for unmapped targets.
+    pub fn apply(&self, values: &[f64], num_comp: usize) -> Vec<f64> {
+        let tuple = |i: usize| &values[i * num_comp..(i + 1) * num_comp];
+        let mut mapped = Vec::with_capacity(self.sources.len() * num_comp);
+        for source in &self.sources {
+            match *source {
+                Some(Source::Index(i)) => mapped.extend_from_slice(tuple(i)),
+                Some(Source::Interpolated(points, weights)) => {
+                    mapped.extend((0..num_comp).map(|c| {
+                        points
+                            .iter()
+                            .zip(weights)
+                            .map(|(&i, w)| w * tuple(i)[c])
+                            .sum::<f64>()
+                    }))
+                }
+                None => mapped.extend(std::iter::repeat_n(f64::NAN, num_comp)),
+            }
+        }
+        mapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vtk::geometry::CellKind;
+
+    /// Unit squares in a row along x, shifted by `dx`
+    fn row(n: usize, dx: f64) -> SurfaceMesh {
+        let mut points = Vec::new();
+        for j in 0..2 {
+            for i in 0..=n {
+                points.push([i as f64 + dx, j as f64, 0.0]);
+            }
+        }
+        let connectivity = (0..n)
+            .flat_map(|i| [i, i + 1, i + n + 2, i + n + 1])
+            .collect();
+        let offsets = (1..=n).map(|c| 4 * c).collect();
+        SurfaceMesh::new(points, connectivity, offsets, vec![CellKind::Polygon; n]).unwrap()
+    }
+
+    #[test]
+    fn test_nearest_cells() {
+        let source = row(4, 0.0);
+        let target = row(3, 0.6);
+        let mapping = FieldMapping::new(
+            &source,
+            &target,
+            FieldLocation::Cell,
+            MapMethod::Nearest,
+            None,
+        )
+        .unwrap();
+        let mapped = mapping.apply(&[0.0, 1.0, 10.0, 11.0, 20.0, 21.0, 30.0, 31.0], 2);
+        assert_eq!(mapped, vec![10.0, 11.0, 20.0, 21.0, 30.0, 31.0]);
+    }
+
+    #[test]
+    fn test_closest_point_interpolates_points() {
+        let source = row(4, 0.0);
+        let target = row(2, 0.5);
+        let x: Vec<f64> = source.points().iter().map(|p| p[0]).collect();
+        let mapping = FieldMapping::new(
+            &source,
+            &target,
+            FieldLocation::Point,
+            MapMethod::ClosestPoint,
+            None,
+        )
+        .unwrap();
+        let mapped = mapping.apply(&x, 1);
+        let expected: Vec<f64> = target.points().iter().map(|p| p[0]).collect();
+        for (m, e) in mapped.iter().zip(expected) {
+            assert!((m - e).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_max_distance() {
+        let source = row(2, 0.0);
+        let target = row(2, 1.5);
+        let mapping = FieldMapping::new(
+            &source,
+            &target,
+            FieldLocation::Cell,
+            MapMethod::ClosestPoint,
+            Some(0.1),
+        )
+        .unwrap();
+        assert_eq!(mapping.num_unmapped(), 1);
+        let mapped = mapping.apply(&[1.0, 2.0], 1);
+        assert_eq!(mapped[0], 2.0);
+        assert!(mapped[1].is_nan());
+    }
+
+    #[test]
+    fn test_method_from_str() {
+        assert_eq!("nearest".parse::<MapMethod>().unwrap(), MapMethod::Nearest);
+        assert_eq!(
+            "Closest-Point".parse::<MapMethod>().unwrap(),
+            MapMethod::ClosestPoint
+        );
+        assert!("linear".parse::<MapMethod>().is_err());
+    }
+}
@@ -0,0 +1,277 @@
+use std::error::Error;
+
+use super::surface_mesh::SurfaceMesh;
+use crate::coordinates::vector::add;
+use crate::coordinates::vector::cross;
+use crate::coordinates::vector::dot;
+use crate::coordinates::vector::scale;
+use crate::coordinates::vector::sub;
+use crate::coordinates::vector::unit;
+
+/// Settings matching those of OpenFOAM's `forces` function object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForceOptions {
+    /// Density the pressure and wall shear stress are multiplied by; 1 when
+    /// they are already in Pa (`rho rho`), the freestream density when they
+    /// are kinematic (`rho rhoInf`)
+    pub rho: f64,
+    /// Reference pressure, in the units of the pressure field
+    pub p_ref: f64,
+    /// Centre of rotation the moments are taken about
+    pub cof_r: [f64; 3],
+    /// Use when the surface normals point into the fluid
+    pub flip_normals: bool,
+}
+
+impl Default for ForceOptions {
+    fn default() -> Self {
+        ForceOptions {
+            rho: 1.0,
+            p_ref: 0.0,
+            cof_r: [0.0; 3],
+            flip_normals: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Forces {
+    pub pressure: [f64; 3],
+    pub viscous: [f64; 3],
+    pub pressure_moment: [f64; 3],
+    pub viscous_moment: [f64; 3],
+}
+
+impl Forces {
+    pub fn total(&self) -> [f64; 3] {
+        add(self.pressure, self.viscous)
+    }
+
+    pub fn total_moment(&self) -> [f64; 3] {
+        add(self.pressure_moment, self.viscous_moment)
+    }
+}
+
+/// Reference values of OpenFOAM's `forceCoeffs` function object.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoefficientOptions {
+    pub u_inf: f64,
+    pub a_ref: f64,
+    pub l_ref: f64,
+    /// Freestream density, 1 for kinematic forces
+    pub rho_inf: f64,
+    pub drag_dir: [f64; 3],
+    pub lift_dir: [f64; 3],
+    pub pitch_axis: [f64; 3],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coefficients {
+    /// Total force over dynamic pressure times reference area
+    pub force: [f64; 3],
+    /// Total moment over dynamic pressure, reference area and length
+    pub moment: [f64; 3],
+    pub cd: f64,
+    pub cl: f64,
+    pub cm_pitch: f64,
+}
+
+/// Integrates the pressure force `rho (p - pRef) A n` and, given the wall
+/// shear stress, the viscous force over the cells of `mesh`.
+///
+/// OpenFOAM's `wallShearStress` is defined with the face normal pointing out
+/// of the fluid, so the force it exerts on the wall is `-rho wss A`.
+pub fn integrate_forces(
+    mesh: &SurfaceMesh,
+    p: &[f64],
+    wall_shear_stress: Option<&[[f64; 3]]>,
+    options: &ForceOptions,
+) -> Result<Forces, Box<dyn Error>> {
+    let num_cells = mesh.num_cells();
+    if p.len() != num_cells {
+        return Err(format!(
+            "Pressure field has {} values for {} cells",
+            p.len(),
+            num_cells
+        )
+        .into());
+    }
+    if let Some(wss) = wall_shear_stress {
+        if wss.len() != num_cells {
+            return Err(format!(
+                "Wall shear stress field has {} values for {} cells",
+                wss.len(),
+                num_cells
+            )
+            .into());
+        }
+    }
+
+    let sign = if options.flip_normals { -1.0 } else { 1.0 };
+    let mut forces = Forces::default();
+
+    for cell in 0..num_cells {
+        let area = mesh.areas()[cell];
+        let normal = mesh.normals()[cell];
+        let arm = sub(mesh.centroids()[cell], options.cof_r);
+
+        let pressure = scale(
+            normal,
+            sign * options.rho * (p[cell] - options.p_ref) * area,
+        );
+        forces.pressure = add(forces.pressure, pressure);
+        forces.pressure_moment = add(forces.pressure_moment, cross(arm, pressure));
+
+        if let Some(wss) = wall_shear_stress {
+            let viscous = scale(wss[cell], -options.rho * area);
+            forces.viscous = add(forces.viscous, viscous);
+            forces.viscous_moment = add(forces.viscous_moment, cross(arm, viscous));
+        }
+    }
+
+    Ok(forces)
+}
+
+/// Force and moment coefficients as OpenFOAM's `forceCoeffs` defines them.
+pub fn force_coefficients(
+    forces: &Forces,
+    options: &CoefficientOptions,
+) -> Result<Coefficients, Box<dyn Error>> {
+    let force_scale = 0.5 * options.rho_inf * options.u_inf.powi(2) * options.a_ref;
+    let moment_scale = force_scale * options.l_ref;
+    if force_scale <= 0.0 || moment_scale <= 0.0 {
+        return Err("Uinf, Aref, lRef and rhoInf must be positive".into());
+    }
+
+    let force = scale(forces.total(), 1.0 / force_scale);
+    let moment = scale(forces.total_moment(), 1.0 / moment_scale);
+    Ok(Coefficients {
+        force,
+        moment,
+        cd: dot(force, unit(options.drag_dir)),
+        cl: dot(force, unit(options.lift_dir)),
+        cm_pitch: dot(moment, unit(options.pitch_axis)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vtk::geometry::CellKind;
+
+    /// A unit cube with outward normals, i.e. pointing out of a fluid
+    /// enclosed by the cube into the walls
+    fn cube() -> SurfaceMesh {
+        let points = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [0.0, 1.0, 1.0],
+        ];
+        let faces = [
+            [0, 3, 2, 1], // z = 0
+            [4, 5, 6, 7], // z = 1
+            [0, 1, 5, 4], // y = 0
+            [2, 3, 7, 6], // y = 1
+            [0, 4, 7, 3], // x = 0
+            [1, 2, 6, 5], // x = 1
+        ];
+        SurfaceMesh::new(
+            points,
+            faces.as_flattened().to_vec(),
+            vec![4, 8, 12, 16, 20, 24],
+            vec![CellKind::Polygon; 6],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_uniform_pressure_cancels() {
+        let forces = integrate_forces(&cube(), &[5.0; 6], None, &ForceOptions::default()).unwrap();
+        assert!(forces.total().iter().all(|f| f.abs() < 1e-12));
+        assert!(forces.total_moment().iter().all(|m| m.abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_pressure_force_and_moment() {
+        // Pressure on the x = 1 face only
+        let p = [0.0, 0.0, 0.0, 0.0, 0.0, 2.0];
+        let options = ForceOptions {
+            rho: 1.5,
+            ..Default::default()
+        };
+        let forces = integrate_forces(&cube(), &p, None, &options).unwrap();
+
+        assert_eq!(forces.pressure, [3.0, 0.0, 0.0]);
+        // Acting at (1, 0.5, 0.5) about the origin
+        assert_eq!(forces.pressure_moment, [0.0, 1.5, -1.5]);
+
+        let flipped = ForceOptions {
+            flip_normals: true,
+            ..options
+        };
+        let forces = integrate_forces(&cube(), &p, None, &flipped).unwrap();
+        assert_eq!(forces.pressure, [-3.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_reference_pressure() {
+        let p = [1.0, 1.0, 1.0, 1.0, 1.0, 3.0];
+        let options = ForceOptions {
+            p_ref: 1.0,
+            cof_r: [1.0, 0.5, 0.5],
+            ..Default::default()
+        };
+        let forces = integrate_forces(&cube(), &p, None, &options).unwrap();
+        assert_eq!(forces.pressure, [2.0, 0.0, 0.0]);
+        assert_eq!(forces.pressure_moment, [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_viscous_force() {
+        let wss = [[-1.0, 0.0, 0.0]; 6];
+        let forces =
+            integrate_forces(&cube(), &[0.0; 6], Some(&wss), &ForceOptions::default()).unwrap();
+        assert_eq!(forces.viscous, [6.0, 0.0, 0.0]);
+        assert_eq!(forces.total(), [6.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_field_length_mismatch() {
+        assert!(integrate_forces(&cube(), &[0.0; 5], None, &ForceOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_coefficients() {
+        let forces = Forces {
+            pressure: [2.0, 0.0, 1.0],
+            viscous: [2.0, 0.0, 0.0],
+            pressure_moment: [0.0, 8.0, 0.0],
+            viscous_moment: [0.0; 3],
+        };
+        let options = CoefficientOptions {
+            u_inf: 2.0,
+            a_ref: 1.0,
+            l_ref: 2.0,
+            rho_inf: 1.0,
+            drag_dir: [2.0, 0.0, 0.0],
+            lift_dir: [0.0, 0.0, 1.0],
+            pitch_axis: [0.0, 1.0, 0.0],
+        };
+        let coefficients = force_coefficients(&forces, &options).unwrap();
+
+        assert_eq!(coefficients.cd, 2.0);
+        assert_eq!(coefficients.cl, 0.5);
+        assert_eq!(coefficients.cm_pitch, 2.0);
+
+        let invalid = CoefficientOptions {
+            u_inf: 0.0,
+            ..options
+        };
+        assert!(force_coefficients(&forces, &invalid).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VtkFormat {
+    Xml,
+    Legacy,
+}
+
+impl VtkFormat {
+    /// Detects the format of an existing file from its header, falling back
+    /// to the extension when the header is not recognised.
+    pub fn detect(path: &str) -> Result<Self, Box<dyn Error>> {
+        let mut header = Vec::with_capacity(64);
+        File::open(path)?.take(64).read_to_end(&mut header)?;
+
+        // https://docs.vtk.org/en/latest/design_documents/VTKFileFormats.html
+        // Legacy files start with "# vtk DataFile Version x.x"
+        let text = String::from_utf8_lossy(&header);
+        let text = text.trim_start_matches('\u{feff}').trim_start();
+        if text.starts_with("# vtk DataFile") {
+            Ok(VtkFormat::Legacy)
+        } else if text.starts_with("<?xml") || text.starts_with("<VTKFile") {
+            Ok(VtkFormat::Xml)
+        } else {
+            Self::from_extension(path)
+                .ok_or_else(|| format!("Unrecognised VTK file format for '{}'", path).into())
+        }
+    }
+
+    pub fn from_extension(path: &str) -> Option<Self> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("vtk") => Some(VtkFormat::Legacy),
+            Some("vtp" | "pvtp" | "vtm") => Some(VtkFormat::Xml),
+            _ => None,
+        }
+    }
+
+    pub fn is_multiblock(path: &str) -> bool {
+        Self::has_extension(Path::new(path), &["vtm"])
+    }
+
+    /// Whether `path` holds a volume (non-surface) dataset, judging by the
+    /// extension.
+    pub fn is_volume(path: &Path) -> bool {
+        Self::has_extension(
+            path,
+            &["vtu", "pvtu", "vti", "pvti", "vts", "pvts", "vtr", "pvtr"],
+        )
+    }
+
+    fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| extensions.contains(&ext.to_ascii_lowercase().as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_detect_by_header() {
+        let dir = tempdir().unwrap();
+
+        let legacy = dir.path().join("surface.dat");
+        fs::write(&legacy, "# vtk DataFile Version 2.0\ntitle\nASCII\n").unwrap();
+        assert_eq!(
+            VtkFormat::detect(legacy.to_str().unwrap()).unwrap(),
+            VtkFormat::Legacy
+        );
+
+        let xml = dir.path().join("surface.vtk");
+        fs::write(&xml, "<?xml version=\"1.0\"?>\n<VTKFile type=\"PolyData\">").unwrap();
+        assert_eq!(
+            VtkFormat::detect(xml.to_str().unwrap()).unwrap(),
+            VtkFormat::Xml
+        );
+    }
+
+    #[test]
+    fn test_detect_falls_back_to_extension() {
+        let dir = tempdir().unwrap();
+
+        let path = dir.path().join("surface.vtp");
+        fs::write(&path, "").unwrap();
+        assert_eq!(
+            VtkFormat::detect(path.to_str().unwrap()).unwrap(),
+            VtkFormat::Xml
+        );
+
+        let path = dir.path().join("surface.txt");
+        fs::write(&path, "").unwrap();
+        assert!(VtkFormat::detect(path.to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_from_extension() {
+        assert_eq!(VtkFormat::from_extension("a.VTK"), Some(VtkFormat::Legacy));
+        assert_eq!(VtkFormat::from_extension("a.vtp"), Some(VtkFormat::Xml));
+        assert_eq!(VtkFormat::from_extension("a.vtm"), Some(VtkFormat::Xml));
+        assert_eq!(VtkFormat::from_extension("a.stl"), None);
+    }
+
+    #[test]
+    fn test_dataset_kinds() {
+        assert!(VtkFormat::is_multiblock("case.vtm"));
+        assert!(!VtkFormat::is_multiblock("case.vtp"));
+        assert!(VtkFormat::is_volume(Path::new("internal.vtu")));
+        assert!(!VtkFormat::is_volume(Path::new("wall.vtp")));
+    }
+}
@@ -1,43 +1,111 @@
 use std::error::Error;
 
 use vtkio::model::IOBuffer;
+use vtkio::model::PolyDataPiece;
 use vtkio::Vtk;
 
+use super::data_type::buffer_to_f64;
 use super::reader::get_poly_data;
+use super::surface_mesh::SurfaceMesh;
 
-pub type GeometryResult = Result<(Vec<f64>, Vec<usize>, Vec<usize>), Box<dyn Error>>;
+/// The four PolyData cell kinds, in the order VTK numbers cells (and so
+/// orders cell data): all verts, then lines, polys and strips.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellKind {
+    Vertex,
+    Line,
+    Polygon,
+    Strip,
+}
 
 pub struct GeometryExtractor;
 
 impl GeometryExtractor {
-    pub fn extract_geometry(vtk: &Vtk) -> GeometryResult {
+    /// The points and every cell of the surface, in VTK cell order.
+    pub fn extract_geometry(vtk: &Vtk) -> Result<SurfaceMesh, Box<dyn Error>> {
         let poly_data = get_poly_data(vtk)?;
 
         let points = Self::extract_points(&poly_data.points)?;
-        let (connectivity, offsets) = Self::extract_connectivity(poly_data);
+        let (connectivity, offsets, kinds) = Self::extract_connectivity(poly_data);
+
+        SurfaceMesh::new(points, connectivity, offsets, kinds)
+    }
 
-        Ok((points, connectivity, offsets))
+    fn extract_points(points_buffer: &IOBuffer) -> Result<Vec<[f64; 3]>, Box<dyn Error>> {
+        let points = buffer_to_f64(points_buffer).ok_or("Unsupported point data format")?;
+        Ok(points.as_chunks::<3>().0.to_vec())
     }
 
-    fn extract_points(points_buffer: &IOBuffer) -> Result<Vec<f64>, Box<dyn Error>> {
-        match points_buffer {
-            IOBuffer::F32(data) => Ok(data.iter().map(|&x| x as f64).collect()),
-            IOBuffer::F64(data) => Ok(data.clone()),
-            _ => Err("Unsupported point data format".into()),
+    fn extract_connectivity(poly_data: &PolyDataPiece) -> (Vec<usize>, Vec<usize>, Vec<CellKind>) {
+        let mut connectivity = Vec::new();
+        let mut offsets = Vec::new();
+        let mut kinds = Vec::new();
+
+        let cells = [
+            (&poly_data.verts, CellKind::Vertex),
+            (&poly_data.lines, CellKind::Line),
+            (&poly_data.polys, CellKind::Polygon),
+            (&poly_data.strips, CellKind::Strip),
+        ];
+        for (cells, kind) in cells {
+            let Some(cells) = cells else {
+                continue;
+            };
+            let (conn_data, offs_data) = cells.clone().into_xml();
+            let base = connectivity.len();
+            connectivity.extend(conn_data.iter().map(|&i| i as usize));
+            offsets.extend(offs_data.iter().map(|&i| base + i as usize));
+            kinds.extend(std::iter::repeat_n(kind, offs_data.len()));
         }
+
+        (connectivity, offsets, kinds)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use vtkio::model::Attributes;
+    use vtkio::model::VertexNumbers;
 
-    fn extract_connectivity(poly_data: &vtkio::model::PolyDataPiece) -> (Vec<usize>, Vec<usize>) {
-        poly_data
-            .polys
-            .as_ref()
-            .map(|polys| {
-                let (conn_data, offs_data) = polys.clone().into_xml();
-                (
-                    conn_data.iter().map(|&i| i as usize).collect(),
-                    offs_data.iter().map(|&i| i as usize).collect(),
-                )
+    use super::*;
+
+    #[test]
+    fn test_cells_in_vtk_order() {
+        let cells = |connectivity: Vec<u64>, offsets: Vec<u64>| {
+            Some(VertexNumbers::XML {
+                connectivity,
+                offsets,
             })
-            .unwrap_or_default()
+        };
+        let piece = PolyDataPiece {
+            points: IOBuffer::F32(vec![0.0; 15]),
+            verts: cells(vec![4], vec![1]),
+            lines: None,
+            polys: cells(vec![0, 1, 2], vec![3]),
+            strips: cells(vec![0, 1, 2, 3], vec![4]),
+            data: Attributes::new(),
+        };
+        let legacy_lines = VertexNumbers::Legacy {
+            num_cells: 2,
+            vertices: vec![2, 0, 1, 3, 1, 2, 3],
+        };
+        let piece = PolyDataPiece {
+            lines: Some(legacy_lines),
+            ..piece
+        };
+
+        let (connectivity, offsets, kinds) = GeometryExtractor::extract_connectivity(&piece);
+        assert_eq!(connectivity, vec![4, 0, 1, 1, 2, 3, 0, 1, 2, 0, 1, 2, 3]);
+        assert_eq!(offsets, vec![1, 3, 6, 9, 13]);
+        assert_eq!(
+            kinds,
+            vec![
+                CellKind::Vertex,
+                CellKind::Line,
+                CellKind::Line,
+                CellKind::Polygon,
+                CellKind::Strip
+            ]
+        );
     }
 }
@@ -0,0 +1,433 @@
+use super::geometry::CellKind;
+use super::surface_mesh::SurfaceMesh;
+use crate::coordinates::vector::dot;
+use crate::coordinates::vector::sub;
+use crate::surface::fan_triangulate;
+use crate::surface::strip_triangulate;
+
+const LEAF_SIZE: usize = 8;
+
+/// Axis-aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bounds {
+    min: [f64; 3],
+    max: [f64; 3],
+}
+
+impl Bounds {
+    const EMPTY: Bounds = Bounds {
+        min: [f64::INFINITY; 3],
+        max: [f64::NEG_INFINITY; 3],
+    };
+
+    fn of_points(points: &[[f64; 3]]) -> Self {
+        points
+            .iter()
+            .fold(Self::EMPTY, |b, p| b.union(&Bounds { min: *p, max: *p }))
+    }
+
+    fn union(&self, other: &Bounds) -> Self {
+        Bounds {
+            min: [0, 1, 2].map(|i| self.min[i].min(other.min[i])),
+            max: [0, 1, 2].map(|i| self.max[i].max(other.max[i])),
+        }
+    }
+
+    fn centre(&self, axis: usize) -> f64 {
+        0.5 * (self.min[axis] + self.max[axis])
+    }
+
+    /// Squared distance from `p` to the box, zero inside
+    fn distance_squared(&self, p: [f64; 3]) -> f64 {
+        (0..3)
+            .map(|i| {
+                (self.min[i] - p[i])
+                    .max(p[i] - self.max[i])
+                    .max(0.0)
+                    .powi(2)
+            })
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Leaf {
+        bounds: Bounds,
+        start: usize,
+        end: usize,
+    },
+    Branch {
+        bounds: Bounds,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl Node {
+    fn bounds(&self) -> &Bounds {
+        match self {
+            Node::Leaf { bounds, .. } | Node::Branch { bounds, .. } => bounds,
+        }
+    }
+}
+
+/// Bounding volume hierarchy over boxed items, answering nearest-item
+/// queries by branch and bound.
+#[derive(Debug, Clone)]
+struct BoxTree {
+    nodes: Vec<Node>,
+    items: Vec<usize>,
+}
+
+impl BoxTree {
+    fn new(boxes: &[Bounds]) -> Self {
+        let mut tree = BoxTree {
+            nodes: Vec::new(),
+            items: (0..boxes.len()).collect(),
+        };
+        if !boxes.is_empty() {
+            tree.build(boxes, 0, boxes.len());
+        }
+        tree
+    }
+
+    /// Adds the node for items `start..end`, returning its index
+    fn build(&mut self, boxes: &[Bounds], start: usize, end: usize) -> usize {
+        let bounds = self.items[start..end]
+            .iter()
+            .fold(Bounds::EMPTY, |b, &i| b.union(&boxes[i]));
+        let index = self.nodes.len();
+        if end - start <= LEAF_SIZE {
+            self.nodes.push(Node::Leaf { bounds, start, end });
+            return index;
+        }
+
+        // Split at the median of the box centres along the longest axis
+        let axis = (0..3)
+            .max_by(|&a, &b| {
+                (bounds.max[a] - bounds.min[a]).total_cmp(&(bounds.max[b] - bounds.min[b]))
+            })
+            .unwrap();
+        let middle = (start + end) / 2;
+        self.items[start..end].select_nth_unstable_by(middle - start, |&a, &b| {
+            boxes[a].centre(axis).total_cmp(&boxes[b].centre(axis))
+        });
+
+        self.nodes.push(Node::Leaf { bounds, start, end });
+        let left = self.build(boxes, start, middle);
+        let right = self.build(boxes, middle, end);
+        self.nodes[index] = Node::Branch {
+            bounds,
+            left,
+            right,
+        };
+        index
+    }
+
+    /// The item minimising `distance_squared`, which must be no less than
+    /// the squared distance from `p` to the item's box.
+    fn nearest(
+        &self,
+        p: [f64; 3],
+        distance_squared: impl Fn(usize) -> f64,
+    ) -> Option<(usize, f64)> {
+        let mut best: Option<(usize, f64)> = None;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if best.is_some_and(|(_, d)| node.bounds().distance_squared(p) >= d) {
+                continue;
+            }
+            match *node {
+                Node::Leaf { start, end, .. } => {
+                    for &item in &self.items[start..end] {
+                        let d = distance_squared(item);
+                        if best.is_none_or(|(_, best)| d < best) {
+                            best = Some((item, d));
+                        }
+                    }
+                }
+                Node::Branch { left, right, .. } => {
+                    // Visit the nearer child first
+                    let dl = self.nodes[left].bounds().distance_squared(p);
+                    let dr = self.nodes[right].bounds().distance_squared(p);
+                    if dl < dr {
+                        stack.extend([right, left]);
+                    } else {
+                        stack.extend([left, right]);
+                    }
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Nearest-neighbour search over a set of points.
+#[derive(Debug, Clone)]
+pub struct PointLocator {
+    points: Vec<[f64; 3]>,
+    tree: BoxTree,
+}
+
+impl PointLocator {
+    pub fn new(points: &[[f64; 3]]) -> Self {
+        let boxes: Vec<Bounds> = points.iter().map(|&p| Bounds { min: p, max: p }).collect();
+        PointLocator {
+            points: points.to_vec(),
+            tree: BoxTree::new(&boxes),
+        }
+    }
+
+    /// Index of the point nearest `p` and its distance, `None` if there
+    /// are no points.
+    pub fn nearest(&self, p: [f64; 3]) -> Option<(usize, f64)> {
+        self.tree
+            .nearest(p, |i| distance_squared(self.points[i], p))
+            .map(|(i, d)| (i, d.sqrt()))
+    }
+}
+
+/// The closest point of a surface to a query point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfaceHit {
+    pub cell: usize,
+    /// The mesh points of the triangle of `cell` holding the closest point
+    pub triangle: [usize; 3],
+    /// Barycentric weights of the closest point in `triangle`
+    pub weights: [f64; 3],
+    pub point: [f64; 3],
+    pub distance: f64,
+}
+
+impl SurfaceHit {
+    /// Interpolates point values with `num_comp` components at the hit.
+    pub fn interpolate(&self, values: &[f64], num_comp: usize) -> Vec<f64> {
+        (0..num_comp)
+            .map(|c| {
+                self.triangle
+                    .iter()
+                    .zip(self.weights)
+                    .map(|(&i, w)| w * values[i * num_comp + c])
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// Closest-point search over the polygons and strips of a surface, which
+/// are triangulated as for their areas.
+#[derive(Debug, Clone)]
+pub struct SurfaceLocator {
+    points: Vec<[f64; 3]>,
+    triangles: Vec<[usize; 3]>,
+    /// Cell each triangle comes from
+    cells: Vec<usize>,
+    tree: BoxTree,
+}
+
+impl SurfaceLocator {
+    pub fn new(mesh: &SurfaceMesh) -> Self {
+        let mut triangles = Vec::new();
+        let mut cells = Vec::new();
+        for (index, cell) in mesh.cells().enumerate() {
+            let cell_triangles = match cell.kind {
+                CellKind::Vertex | CellKind::Line => continue,
+                CellKind::Polygon => fan_triangulate(cell.point_ids),
+                CellKind::Strip => strip_triangulate(cell.point_ids),
+            };
+            cells.extend(std::iter::repeat_n(index, cell_triangles.len()));
+            triangles.extend(cell_triangles);
+        }
+
+        let points = mesh.points().to_vec();
+        let boxes: Vec<Bounds> = triangles
+            .iter()
+            .map(|t| Bounds::of_points(&t.map(|i| points[i])))
+            .collect();
+        SurfaceLocator {
+            tree: BoxTree::new(&boxes),
+            points,
+            triangles,
+            cells,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    /// The closest point of the surface to `p`, `None` if the surface has
+    /// no polygons or strips.
+    pub fn closest_point(&self, p: [f64; 3]) -> Option<SurfaceHit> {
+        let closest = |i: usize| {
+            let [a, b, c] = self.triangles[i].map(|j| self.points[j]);
+            closest_point_on_triangle(p, a, b, c)
+        };
+        let (triangle, _) = self.tree.nearest(p, |i| {
+            let (q, _) = closest(i);
+            distance_squared(q, p)
+        })?;
+        let (point, weights) = closest(triangle);
+        Some(SurfaceHit {
+            cell: self.cells[triangle],
+            triangle: self.triangles[triangle],
+            weights,
+            point,
+            distance: distance_squared(point, p).sqrt(),
+        })
+    }
+}
+
+fn distance_squared(a: [f64; 3], b: [f64; 3]) -> f64 {
+    let d = sub(a, b);
+    dot(d, d)
+}
+
+/// The point of triangle `abc` closest to `p`, with its barycentric
+/// weights, by the Voronoi region tests of Ericson's Real-Time Collision
+/// Detection (5.1.5).
+fn closest_point_on_triangle(
+    p: [f64; 3],
+    a: [f64; 3],
+    b: [f64; 3],
+    c: [f64; 3],
+) -> ([f64; 3], [f64; 3]) {
+    let at = |w: [f64; 3]| [0, 1, 2].map(|i| w[0] * a[i] + w[1] * b[i] + w[2] * c[i]);
+    let (ab, ac, ap) = (sub(b, a), sub(c, a), sub(p, a));
+
+    let (d1, d2) = (dot(ab, ap), dot(ac, ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return (a, [1.0, 0.0, 0.0]);
+    }
+
+    let bp = sub(p, b);
+    let (d3, d4) = (dot(ab, bp), dot(ac, bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return (b, [0.0, 1.0, 0.0]);
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        let w = [1.0 - v, v, 0.0];
+        return (at(w), w);
+    }
+
+    let cp = sub(p, c);
+    let (d5, d6) = (dot(ab, cp), dot(ac, cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return (c, [0.0, 0.0, 1.0]);
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        let w = [1.0 - w, 0.0, w];
+        return (at(w), w);
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        let w = [0.0, 1.0 - w, w];
+        return (at(w), w);
+    }
+
+    let denom = va + vb + vc;
+    if denom == 0.0 {
+        // Degenerate triangle, all points collinear
+        return (a, [1.0, 0.0, 0.0]);
+    }
+    let (v, w) = (vb / denom, vc / denom);
+    let w = [1.0 - v - w, v, w];
+    (at(w), w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(n: usize) -> SurfaceMesh {
+        let mut points = Vec::new();
+        for j in 0..=n {
+            for i in 0..=n {
+                points.push([i as f64, j as f64, 0.0]);
+            }
+        }
+        let mut connectivity = Vec::new();
+        for j in 0..n {
+            for i in 0..n {
+                let p = j * (n + 1) + i;
+                connectivity.extend([p, p + 1, p + n + 2, p + n + 1]);
+            }
+        }
+        let offsets = (1..=n * n).map(|c| 4 * c).collect();
+        SurfaceMesh::new(
+            points,
+            connectivity,
+            offsets,
+            vec![CellKind::Polygon; n * n],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_closest_point_on_triangle() {
+        let (a, b, c) = ([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let (q, w) = closest_point_on_triangle([0.25, 0.25, 1.0], a, b, c);
+        assert_eq!(q, [0.25, 0.25, 0.0]);
+        assert_eq!(w, [0.5, 0.25, 0.25]);
+
+        let (q, w) = closest_point_on_triangle([2.0, -1.0, 0.0], a, b, c);
+        assert_eq!((q, w), (b, [0.0, 1.0, 0.0]));
+
+        let (q, _) = closest_point_on_triangle([1.0, 1.0, 0.0], a, b, c);
+        assert_eq!(q, [0.5, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_point_locator_matches_brute_force() {
+        let points: Vec<[f64; 3]> = (0..200)
+            .map(|i| {
+                let t = i as f64;
+                [(t * 0.37).sin() * 5.0, (t * 0.71).cos() * 3.0, t * 0.01]
+            })
+            .collect();
+        let locator = PointLocator::new(&points);
+        for q in [[0.0, 0.0, 0.0], [4.0, -2.0, 1.0], [-10.0, 5.0, 3.0]] {
+            let brute = (0..points.len())
+                .min_by(|&a, &b| {
+                    distance_squared(points[a], q).total_cmp(&distance_squared(points[b], q))
+                })
+                .unwrap();
+            assert_eq!(locator.nearest(q).unwrap().0, brute);
+        }
+        assert!(PointLocator::new(&[]).nearest([0.0; 3]).is_none());
+    }
+
+    #[test]
+    fn test_surface_locator() {
+        let mesh = grid(10);
+        let locator = SurfaceLocator::new(&mesh);
+
+        let hit = locator.closest_point([3.5, 7.25, 2.0]).unwrap();
+        assert_eq!(hit.cell, 73);
+        assert_eq!(hit.point, [3.5, 7.25, 0.0]);
+        assert_eq!(hit.distance, 2.0);
+
+        // Off the edge of the grid
+        let hit = locator.closest_point([-1.0, 4.5, 0.0]).unwrap();
+        assert_eq!(hit.cell, 40);
+        assert_eq!(hit.distance, 1.0);
+
+        // Interpolating x recovers the closest point's x
+        let x: Vec<f64> = mesh.points().iter().map(|p| p[0]).collect();
+        let hit = locator.closest_point([2.3, 5.6, -1.0]).unwrap();
+        assert!((hit.interpolate(&x, 1)[0] - 2.3).abs() < 1e-12);
+    }
+}
@@ -1,54 +1,334 @@
 pub mod polygon_areas;
 pub use polygon_areas::calculate_polygon_areas;
+pub mod polygon_centroids;
+pub use polygon_centroids::calculate_polygon_centroids;
+pub mod polygon_normals;
+pub use polygon_normals::calculate_polygon_normals;
+pub mod cell_areas;
+pub use cell_areas::calculate_cell_areas;
 
+pub mod atomic_write;
+pub mod clip;
+pub mod compressed_arrays;
+pub mod connected_regions;
+pub mod data_type;
+pub mod extract_cells;
 pub mod field_manager;
+pub mod field_mapping;
+pub mod forces;
+pub mod format;
 pub mod geometry;
+pub mod locator;
+pub mod multiblock;
+pub mod pieces;
+pub mod point_cell_averaging;
 pub mod reader;
+pub mod region;
+pub mod slice;
+pub mod surface_conversion;
+pub mod surface_mesh;
+pub mod xml_writer;
 
+use std::cell::OnceCell;
 use std::error::Error;
 
+use clip::clip_poly_data;
+use clip::ClipMode;
+use data_type::DataType;
+use extract_cells::extract_cells;
+use field_manager::Component;
+use field_manager::FieldLocation;
 use field_manager::FieldManager;
 use geometry::GeometryExtractor;
-use geometry::GeometryResult;
+use multiblock::MultiBlock;
+use reader::get_poly_data;
 use reader::VtkReader;
+use region::inside_all;
+use region::Region;
+use surface_conversion::SurfaceConverter;
+use surface_mesh::SurfaceMesh;
+use vtkio::model::DataSet;
+use vtkio::model::Piece;
+use vtkio::model::PolyDataPiece;
+use vtkio::Vtk;
+use xml_writer::WriteOptions;
 
 pub struct VtpProcessor {
     reader: VtkReader,
+    // Field changes leave the geometry alone, so it is extracted once
+    mesh: OnceCell<SurfaceMesh>,
 }
 
 impl VtpProcessor {
     pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
-        let reader = VtkReader::from_file(path)?;
-        Ok(VtpProcessor { reader })
+        Ok(Self::new(VtkReader::from_file(path)?))
     }
 
-    pub fn geometry(&self) -> GeometryResult {
-        GeometryExtractor::extract_geometry(self.reader.vtk())
+    pub fn from_block(path: &str, name: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::new(VtkReader::from_block(path, name)?))
+    }
+
+    pub fn block_names(path: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        Ok(MultiBlock::from_file(path)?.names())
+    }
+
+    pub fn from_vtk(vtk: Vtk) -> Self {
+        Self::new(VtkReader::from_vtk(vtk))
+    }
+
+    fn new(reader: VtkReader) -> Self {
+        VtpProcessor {
+            reader,
+            mesh: OnceCell::new(),
+        }
+    }
+
+    pub fn from_triangles(
+        vertices: &[[f32; 3]],
+        region_ids: Option<&[i32]>,
+        with_normals: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        let vtk = SurfaceConverter::from_triangles(vertices, region_ids, with_normals)?;
+        Ok(Self::from_vtk(vtk))
+    }
+
+    pub fn from_polylines(points: &[[f64; 3]], lines: &[Vec<usize>]) -> Self {
+        Self::from_vtk(SurfaceConverter::from_polylines(points, lines))
+    }
+
+    /// The surface mesh, extracted on first use and cached afterwards.
+    pub fn geometry(&self) -> Result<SurfaceMesh, Box<dyn Error>> {
+        Ok(self.mesh()?.clone())
+    }
+
+    fn mesh(&self) -> Result<&SurfaceMesh, Box<dyn Error>> {
+        if let Some(mesh) = self.mesh.get() {
+            return Ok(mesh);
+        }
+        let mesh = GeometryExtractor::extract_geometry(self.reader.vtk())?;
+        Ok(self.mesh.get_or_init(|| mesh))
+    }
+
+    /// A new surface holding only the cells whose entry in `keep` is true
+    /// and the points they use, with all fields carried over.
+    pub fn extract_cells(&self, keep: &[bool]) -> Result<Self, Box<dyn Error>> {
+        let vtk = self.reader.vtk();
+        let piece = extract_cells(get_poly_data(vtk)?, keep)?;
+        Ok(Self::from_vtk(Self::with_piece(vtk, piece)))
+    }
+
+    /// Restricts the surface to the intersection of `regions`, or with
+    /// `invert` to the part outside all of them.
+    ///
+    /// Exact clipping of boxes cuts along their faces; inverted boxes are
+    /// cut along the interpolated distance to the box, which rounds off
+    /// edges that cross two faces.
+    pub fn clip(
+        &self,
+        regions: &[Region],
+        mode: ClipMode,
+        invert: bool,
+    ) -> Result<Self, Box<dyn Error>> {
+        let sign = if invert { -1.0 } else { 1.0 };
+        match mode {
+            ClipMode::Centroid => {
+                let keep: Vec<bool> = self
+                    .mesh()?
+                    .centroids()
+                    .iter()
+                    .map(|&c| inside_all(regions, c, invert))
+                    .collect();
+                self.extract_cells(&keep)
+            }
+            ClipMode::Exact => {
+                let vtk = self.reader.vtk();
+                let mut piece = get_poly_data(vtk)?.clone();
+                for region in regions {
+                    let planes = match region.planes() {
+                        Some(planes) if !invert => planes,
+                        _ => vec![*region],
+                    };
+                    for plane in planes {
+                        piece = clip_poly_data(&piece, |p| sign * plane.value(p))?;
+                    }
+                }
+                Ok(Self::from_vtk(Self::with_piece(vtk, piece)))
+            }
+        }
+    }
+
+    pub fn triangles(&self) -> Result<Vec<[f32; 3]>, Box<dyn Error>> {
+        SurfaceConverter::to_triangles(self.reader.vtk())
     }
 
     pub fn field(&self, field_name: &str) -> Result<Vec<f64>, Box<dyn Error>> {
         FieldManager::get_field(self.reader.vtk(), field_name)
     }
 
+    pub fn field_at(
+        &self,
+        field_name: &str,
+        location: FieldLocation,
+    ) -> Result<Vec<f64>, Box<dyn Error>> {
+        FieldManager::get_field_at(self.reader.vtk(), field_name, location)
+    }
+
+    /// One value per point or cell. Multi-component fields need a
+    /// `component` to reduce them to scalars.
+    pub fn scalar_field_at(
+        &self,
+        field_name: &str,
+        component: Option<Component>,
+        location: FieldLocation,
+    ) -> Result<Vec<f64>, Box<dyn Error>> {
+        match component {
+            Some(component) => self.component_at(field_name, component, location),
+            None => {
+                let num_comp = self.num_components_at(field_name, location)?;
+                if num_comp > 1 {
+                    return Err(format!(
+                        "Field '{}' has {} components, select one or the magnitude",
+                        field_name, num_comp
+                    )
+                    .into());
+                }
+                self.field_at(field_name, location)
+            }
+        }
+    }
+
+    /// One value per cell; point fields are averaged onto the cells.
+    pub fn cell_field(
+        &self,
+        field_name: &str,
+        component: Option<Component>,
+        location: FieldLocation,
+    ) -> Result<Vec<f64>, Box<dyn Error>> {
+        let values = self.scalar_field_at(field_name, component, location)?;
+        match location {
+            FieldLocation::Cell => Ok(values),
+            FieldLocation::Point => Ok(self.mesh()?.point_to_cell(&values, 1)),
+        }
+    }
+
+    pub fn num_components_at(
+        &self,
+        field_name: &str,
+        location: FieldLocation,
+    ) -> Result<usize, Box<dyn Error>> {
+        FieldManager::num_components_at(self.reader.vtk(), field_name, location)
+    }
+
+    pub fn vectors_at(
+        &self,
+        field_name: &str,
+        location: FieldLocation,
+    ) -> Result<Vec<[f64; 3]>, Box<dyn Error>> {
+        FieldManager::get_vectors_at(self.reader.vtk(), field_name, location)
+    }
+
+    pub fn component_at(
+        &self,
+        field_name: &str,
+        component: Component,
+        location: FieldLocation,
+    ) -> Result<Vec<f64>, Box<dyn Error>> {
+        FieldManager::get_component_at(self.reader.vtk(), field_name, component, location)
+    }
+
     pub fn list_fields(&self) -> Result<Vec<String>, Box<dyn Error>> {
         FieldManager::list_fields(self.reader.vtk())
     }
 
+    pub fn list_fields_at(&self, location: FieldLocation) -> Result<Vec<String>, Box<dyn Error>> {
+        FieldManager::list_fields_at(self.reader.vtk(), location)
+    }
+
     pub fn field_exists(&self, field_name: &str) -> Result<bool, Box<dyn Error>> {
         FieldManager::field_exists(self.reader.vtk(), field_name)
     }
 
+    pub fn field_exists_at(
+        &self,
+        field_name: &str,
+        location: FieldLocation,
+    ) -> Result<bool, Box<dyn Error>> {
+        FieldManager::field_exists_at(self.reader.vtk(), field_name, location)
+    }
+
     pub fn remove_field(mut self, field_name: &str) -> Result<Self, Box<dyn Error>> {
         FieldManager::remove_field(self.reader.vtk_mut(), field_name)?;
         Ok(self)
     }
 
+    pub fn remove_field_at(
+        mut self,
+        field_name: &str,
+        location: FieldLocation,
+    ) -> Result<Self, Box<dyn Error>> {
+        FieldManager::remove_field_at(self.reader.vtk_mut(), field_name, location)?;
+        Ok(self)
+    }
+
     pub fn add_field(mut self, field_name: &str, data: &[f64]) -> Result<Self, Box<dyn Error>> {
         FieldManager::add_field(self.reader.vtk_mut(), field_name, data)?;
         Ok(self)
     }
 
+    pub fn add_field_at(
+        mut self,
+        field_name: &str,
+        data: &[f64],
+        location: FieldLocation,
+    ) -> Result<Self, Box<dyn Error>> {
+        FieldManager::add_field_at(self.reader.vtk_mut(), field_name, data, location)?;
+        Ok(self)
+    }
+
+    pub fn add_field_as(
+        mut self,
+        field_name: &str,
+        data: &[f64],
+        data_type: DataType,
+        location: FieldLocation,
+    ) -> Result<Self, Box<dyn Error>> {
+        FieldManager::add_field_as(self.reader.vtk_mut(), field_name, data, data_type, location)?;
+        Ok(self)
+    }
+
+    pub fn add_vector_field_at(
+        mut self,
+        field_name: &str,
+        data: &[[f64; 3]],
+        location: FieldLocation,
+    ) -> Result<Self, Box<dyn Error>> {
+        FieldManager::add_vector_field_at(self.reader.vtk_mut(), field_name, data, location)?;
+        Ok(self)
+    }
+
+    /// A copy of `vtk`'s header around a new PolyData piece
+    fn with_piece(vtk: &Vtk, piece: PolyDataPiece) -> Vtk {
+        Vtk {
+            version: vtk.version,
+            title: vtk.title.clone(),
+            byte_order: vtk.byte_order,
+            file_path: None,
+            data: DataSet::PolyData {
+                meta: None,
+                pieces: vec![Piece::Inline(Box::new(piece))],
+            },
+        }
+    }
+
     pub fn write_to_file(self, path: &str) -> Result<(), Box<dyn Error>> {
         self.reader.write_to_file(path)
     }
+
+    pub fn write_to_file_with(
+        self,
+        path: &str,
+        options: &WriteOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        self.reader.write_to_file_with(path, options)
+    }
 }
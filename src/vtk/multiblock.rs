@@ -0,0 +1,159 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    /// Block names from the outermost `<Block>` down to the `<DataSet>`,
+    /// joined with '/', e.g. "boundary/inlet"
+    pub name: String,
+    pub file: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiBlock {
+    pub blocks: Vec<Block>,
+}
+
+impl MultiBlock {
+    /// Reads a `.vtm` descriptor. Only the file references are read; block
+    /// files are resolved relative to the descriptor's directory.
+    pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        let base_dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        Self::parse(&content, base_dir)
+    }
+
+    pub fn parse(content: &str, base_dir: &Path) -> Result<Self, Box<dyn Error>> {
+        if !content.contains("vtkMultiBlockDataSet") {
+            return Err("Expected a vtkMultiBlockDataSet".into());
+        }
+
+        let mut blocks = Vec::new();
+        let mut stack: Vec<String> = Vec::new();
+
+        for tag in content.split('<').skip(1) {
+            let tag = tag.split('>').next().unwrap_or("").trim();
+            let self_closing = tag.ends_with('/');
+            let tag = tag.trim_end_matches('/');
+            let tag_name = tag.split_whitespace().next().unwrap_or("");
+
+            match tag_name {
+                "Block" if !self_closing => {
+                    let name = Self::attribute(tag, "name")
+                        .or_else(|| Self::attribute(tag, "index").map(|i| format!("block{}", i)))
+                        .unwrap_or_default();
+                    stack.push(name);
+                }
+                "/Block" => {
+                    stack.pop();
+                }
+                "DataSet" => {
+                    // Empty datasets (e.g. patches without faces) have no file
+                    let Some(file) = Self::attribute(tag, "file") else {
+                        continue;
+                    };
+                    let name = Self::attribute(tag, "name").unwrap_or_else(|| {
+                        Path::new(&file)
+                            .file_stem()
+                            .and_then(|stem| stem.to_str())
+                            .unwrap_or_default()
+                            .to_string()
+                    });
+                    let mut full_name = stack.clone();
+                    full_name.push(name);
+                    blocks.push(Block {
+                        name: full_name.join("/"),
+                        file: base_dir.join(file),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Ok(MultiBlock { blocks })
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.blocks.iter().map(|b| b.name.clone()).collect()
+    }
+
+    /// Blocks matching `name`, either by full name, by their own name, or by
+    /// the name of an enclosing group (so "boundary" selects every patch).
+    pub fn select(&self, name: &str) -> Vec<&Block> {
+        self.blocks
+            .iter()
+            .filter(|b| b.name == name || b.name.split('/').any(|part| part == name))
+            .collect()
+    }
+
+    fn attribute(tag: &str, key: &str) -> Option<String> {
+        for quote in ['"', '\''] {
+            let pattern = format!("{}={}", key, quote);
+            let mut search = tag;
+            while let Some(pos) = search.find(&pattern) {
+                let preceded_by_space =
+                    search[..pos].chars().last().is_none_or(char::is_whitespace);
+                let rest = &search[pos + pattern.len()..];
+                if preceded_by_space {
+                    return rest.split(quote).next().map(|s| s.to_string());
+                }
+                search = rest;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VTM: &str = r#"<?xml version='1.0'?>
+<VTKFile type='vtkMultiBlockDataSet' version='1.0' byte_order='LittleEndian'>
+  <vtkMultiBlockDataSet>
+    <DataSet index='0' name='internal' file='case_100/internal.vtu' />
+    <Block index='1' name='boundary'>
+      <DataSet index='0' name='inlet' file='case_100/boundary/inlet.vtp' />
+      <DataSet index='1' name='wall' file="case_100/boundary/wall.vtp"/>
+      <DataSet index='2' name='empty' />
+    </Block>
+  </vtkMultiBlockDataSet>
+</VTKFile>"#;
+
+    #[test]
+    fn test_parse_blocks() {
+        let multi_block = MultiBlock::parse(VTM, Path::new("post")).unwrap();
+
+        assert_eq!(
+            multi_block.names(),
+            vec!["internal", "boundary/inlet", "boundary/wall"]
+        );
+        assert_eq!(
+            multi_block.blocks[2].file,
+            PathBuf::from("post/case_100/boundary/wall.vtp")
+        );
+    }
+
+    #[test]
+    fn test_select() {
+        let multi_block = MultiBlock::parse(VTM, Path::new("")).unwrap();
+
+        assert_eq!(multi_block.select("wall").len(), 1);
+        assert_eq!(multi_block.select("boundary/wall").len(), 1);
+        assert_eq!(multi_block.select("boundary").len(), 2);
+        assert!(multi_block.select("outlet").is_empty());
+    }
+
+    #[test]
+    fn test_attribute_does_not_match_suffix() {
+        let tag = "DataSet index='0' myname='x' name='y'";
+        assert_eq!(MultiBlock::attribute(tag, "name"), Some("y".to_string()));
+    }
+
+    #[test]
+    fn test_not_a_multiblock() {
+        assert!(MultiBlock::parse("<VTKFile type='PolyData'>", Path::new("")).is_err());
+    }
+}
@@ -0,0 +1,457 @@
+use std::error::Error;
+use std::ops::Range;
+use std::path::Path;
+
+use vtkio::model::Attribute;
+use vtkio::model::Attributes;
+use vtkio::model::DataArray;
+use vtkio::model::DataSet;
+use vtkio::model::ElementType;
+use vtkio::model::IOBuffer;
+use vtkio::model::Piece;
+use vtkio::model::PolyDataPiece;
+use vtkio::model::VertexNumbers;
+use vtkio::Vtk;
+
+use super::data_type::buffer_to_f64;
+use super::reader::VtkReader;
+
+macro_rules! for_each_buffer {
+    ($macro:ident, $($args:tt)*) => {
+        $macro!($($args)*; Bit, U8, I8, U16, I16, U32, I32, U64, I64, F32, F64)
+    };
+}
+
+macro_rules! slice_buffer_impl {
+    ($buffer:expr, $range:expr; $($variant:ident),*) => {
+        match $buffer {
+            $(IOBuffer::$variant(v) => v.get($range).map(|s| IOBuffer::$variant(s.to_vec())),)*
+        }
+    };
+}
+
+macro_rules! gather_buffer_impl {
+    ($buffer:expr, $indices:expr, $num_comp:expr; Bit, $($variant:ident),*) => {
+        match $buffer {
+            IOBuffer::Bit(_) => None,
+            $(IOBuffer::$variant(v) => {
+                let mut out = Vec::with_capacity($indices.len() * $num_comp);
+                for &i in $indices {
+                    out.extend_from_slice(v.get(i * $num_comp..(i + 1) * $num_comp)?);
+                }
+                Some(IOBuffer::$variant(out))
+            })*
+        }
+    };
+}
+
+macro_rules! concat_buffers_impl {
+    ($buffers:expr; $($variant:ident),*) => {{
+        let mut iter = $buffers.into_iter();
+        match iter.next() {
+            $(Some(IOBuffer::$variant(mut out)) => {
+                for buffer in iter {
+                    match buffer {
+                        IOBuffer::$variant(v) => out.extend(v),
+                        _ => return None,
+                    }
+                }
+                Some(IOBuffer::$variant(out))
+            })*
+            None => None,
+        }
+    }};
+}
+
+/// Copies `range` of a buffer, keeping its element type.
+fn slice_buffer(buffer: &IOBuffer, range: Range<usize>) -> Option<IOBuffer> {
+    for_each_buffer!(slice_buffer_impl, buffer, range)
+}
+
+/// Copies the tuples at `indices` of a buffer with `num_comp` components,
+/// keeping its element type. Bit buffers are not supported.
+pub(crate) fn gather_buffer(
+    buffer: &IOBuffer,
+    indices: &[usize],
+    num_comp: usize,
+) -> Option<IOBuffer> {
+    for_each_buffer!(gather_buffer_impl, buffer, indices, num_comp)
+}
+
+/// Concatenates buffers of the same element type.
+fn concat_buffers(buffers: Vec<IOBuffer>) -> Option<IOBuffer> {
+    for_each_buffer!(concat_buffers_impl, buffers)
+}
+
+/// Collects the PolyData pieces of `vtk`, loading pieces that reference
+/// other files (as written in `.pvtp` files) relative to `base_dir`, and
+/// replaces them with a single inline piece.
+pub fn merge_pieces(vtk: &mut Vtk, base_dir: &Path) -> Result<(), Box<dyn Error>> {
+    let pieces = match &mut vtk.data {
+        DataSet::PolyData { pieces, .. } => std::mem::take(pieces),
+        _ => return Err("Expected PolyData".into()),
+    };
+
+    let mut inline = Vec::new();
+    for piece in pieces {
+        collect_piece(piece, base_dir, &mut inline)?;
+    }
+    let merged = merge_poly_pieces(inline)?;
+
+    if let DataSet::PolyData { pieces, .. } = &mut vtk.data {
+        *pieces = vec![Piece::Inline(Box::new(merged))];
+    }
+    Ok(())
+}
+
+fn collect_piece(
+    piece: Piece<PolyDataPiece>,
+    base_dir: &Path,
+    out: &mut Vec<PolyDataPiece>,
+) -> Result<(), Box<dyn Error>> {
+    match piece {
+        Piece::Inline(data) => out.push(*data),
+        Piece::Loaded(data_set) => match *data_set {
+            DataSet::PolyData { pieces, .. } => {
+                for piece in pieces {
+                    collect_piece(piece, base_dir, out)?;
+                }
+            }
+            _ => return Err("Expected PolyData".into()),
+        },
+        Piece::Source(source, _) => {
+            let path = base_dir.join(source);
+            let path = path.to_str().ok_or("Invalid piece source path")?;
+            let reader = VtkReader::from_file(path)?;
+            let vtk = reader.into_vtk();
+            if let DataSet::PolyData { pieces, .. } = vtk.data {
+                for piece in pieces {
+                    collect_piece(piece, base_dir, out)?;
+                }
+            } else {
+                return Err(format!("Expected PolyData in piece '{}'", path).into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Merges PolyData pieces into one. Points and point data are concatenated
+/// in piece order; cells are grouped by kind (verts, lines, polys, strips)
+/// as VTK orders them within a piece, and the cell data is reordered to
+/// match. Arrays missing from some pieces are filled with NaN there.
+pub fn merge_poly_pieces(mut pieces: Vec<PolyDataPiece>) -> Result<PolyDataPiece, Box<dyn Error>> {
+    if pieces.len() == 1 {
+        return Ok(pieces.remove(0));
+    }
+    if pieces.is_empty() {
+        return Err("No pieces found".into());
+    }
+
+    let point_counts: Vec<usize> = pieces.iter().map(|p| p.points.len() / 3).collect();
+    let points = concat_or_f64(pieces.iter().map(|p| p.points.clone()).collect())
+        .ok_or("Unsupported point data format")?;
+
+    // Cell counts per piece and per kind, in VTK order
+    let cell_counts: Vec<[usize; 4]> = pieces
+        .iter()
+        .map(|p| kinds(p).map(|k| k.map(|k| k.num_cells()).unwrap_or(0)))
+        .collect();
+
+    let mut merged_kinds: [Option<VertexNumbers>; 4] = [None, None, None, None];
+    for (k, merged) in merged_kinds.iter_mut().enumerate() {
+        if pieces.iter().all(|p| kinds(p)[k].is_none()) {
+            continue;
+        }
+        let mut connectivity = Vec::new();
+        let mut offsets = Vec::new();
+        let mut point_offset = 0;
+        for (piece, &n_points) in pieces.iter().zip(&point_counts) {
+            if let Some(cells) = kinds(piece)[k] {
+                let (conn, offs) = cells.clone().into_xml();
+                let base = connectivity.len() as u64;
+                connectivity.extend(conn.iter().map(|&i| i + point_offset));
+                offsets.extend(offs.iter().map(|&o| o + base));
+            }
+            point_offset += n_points as u64;
+        }
+        *merged = Some(VertexNumbers::XML {
+            connectivity,
+            offsets,
+        });
+    }
+
+    let point_data = merge_attributes(
+        &pieces,
+        |p| &p.data.point,
+        |i| std::iter::once(0..point_counts[i]).collect(),
+    );
+    let cell_data = merge_attributes(
+        &pieces,
+        |p| &p.data.cell,
+        |i| {
+            let counts = cell_counts[i];
+            let mut start = 0;
+            counts
+                .iter()
+                .map(|&n| {
+                    start += n;
+                    start - n..start
+                })
+                .collect()
+        },
+    );
+
+    // Cell data has to follow the merged cell order: every piece's verts,
+    // then every piece's lines, and so on
+    let cell_data = cell_data
+        .into_iter()
+        .filter_map(|(name, elem, per_piece)| {
+            let mut chunks = Vec::new();
+            for k in 0..4 {
+                for chunks_of_piece in &per_piece {
+                    chunks.push(chunks_of_piece[k].clone());
+                }
+            }
+            concat_or_f64(chunks).map(|data| (name, elem, data))
+        })
+        .map(to_attribute)
+        .collect();
+    let point_data = point_data
+        .into_iter()
+        .filter_map(|(name, elem, per_piece)| {
+            concat_or_f64(per_piece.into_iter().flatten().collect()).map(|data| (name, elem, data))
+        })
+        .map(to_attribute)
+        .collect();
+
+    let [verts, lines, polys, strips] = merged_kinds;
+    Ok(PolyDataPiece {
+        points,
+        verts,
+        lines,
+        polys,
+        strips,
+        data: Attributes {
+            point: point_data,
+            cell: cell_data,
+        },
+    })
+}
+
+fn kinds(piece: &PolyDataPiece) -> [Option<&VertexNumbers>; 4] {
+    [
+        piece.verts.as_ref(),
+        piece.lines.as_ref(),
+        piece.polys.as_ref(),
+        piece.strips.as_ref(),
+    ]
+}
+
+type PieceChunks = (String, ElementType, Vec<Vec<IOBuffer>>);
+
+/// For every array of any piece, splits each piece's data into the element
+/// ranges given by `ranges`. Pieces without the array get NaN.
+fn merge_attributes(
+    pieces: &[PolyDataPiece],
+    attributes: impl Fn(&PolyDataPiece) -> &Vec<Attribute>,
+    ranges: impl Fn(usize) -> Vec<Range<usize>>,
+) -> Vec<PieceChunks> {
+    let arrays_of = |piece: &PolyDataPiece| -> Vec<(String, ElementType, IOBuffer)> {
+        attributes(piece)
+            .iter()
+            .flat_map(|attr| match attr {
+                Attribute::DataArray(arr) => {
+                    vec![(arr.name.clone(), arr.elem.clone(), arr.data.clone())]
+                }
+                Attribute::Field { data_array, .. } => data_array
+                    .iter()
+                    .map(|arr| {
+                        let elem = ElementType::Scalars {
+                            num_comp: arr.elem,
+                            lookup_table: None,
+                        };
+                        (arr.name.clone(), elem, arr.data.clone())
+                    })
+                    .collect(),
+            })
+            .collect()
+    };
+    let all_arrays: Vec<_> = pieces.iter().map(arrays_of).collect();
+
+    // Every array name once, in the order the pieces first use it
+    let mut names: Vec<(&String, &ElementType, usize)> = Vec::new();
+    for (i, arrays) in all_arrays.iter().enumerate() {
+        for (name, elem, _) in arrays {
+            if !names.iter().any(|(n, _, _)| *n == name) {
+                names.push((name, elem, i));
+            }
+        }
+    }
+    let num_comp = |i: usize, data: &IOBuffer| {
+        let total: usize = ranges(i).iter().map(|r| r.len()).sum();
+        data.len().checked_div(total).unwrap_or(0)
+    };
+
+    names
+        .into_iter()
+        .filter_map(|(name, elem, first)| {
+            let find = |arrays: &[(String, ElementType, IOBuffer)]| {
+                arrays
+                    .iter()
+                    .find(|(n, _, _)| n == name)
+                    .map(|(_, _, data)| data.clone())
+            };
+            let num_comp = num_comp(first, &find(&all_arrays[first])?);
+            let mut per_piece = Vec::new();
+            for (i, arrays) in all_arrays.iter().enumerate() {
+                let ranges = ranges(i);
+                let chunks = match find(arrays) {
+                    Some(data) => ranges
+                        .into_iter()
+                        .map(|r| slice_buffer(&data, r.start * num_comp..r.end * num_comp))
+                        .collect::<Option<Vec<_>>>()?,
+                    None => ranges
+                        .into_iter()
+                        .map(|r| IOBuffer::F64(vec![f64::NAN; r.len() * num_comp]))
+                        .collect(),
+                };
+                per_piece.push(chunks);
+            }
+            Some((name.clone(), elem.clone(), per_piece))
+        })
+        .collect()
+}
+
+fn concat_or_f64(buffers: Vec<IOBuffer>) -> Option<IOBuffer> {
+    concat_buffers(buffers.clone()).or_else(|| {
+        let mut out = Vec::new();
+        for buffer in &buffers {
+            out.extend(buffer_to_f64(buffer)?);
+        }
+        Some(IOBuffer::F64(out))
+    })
+}
+
+fn to_attribute((name, elem, data): (String, ElementType, IOBuffer)) -> Attribute {
+    Attribute::DataArray(DataArray { name, elem, data })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(name: &str, data: IOBuffer) -> Attribute {
+        Attribute::DataArray(DataArray {
+            name: name.to_string(),
+            elem: ElementType::Scalars {
+                num_comp: 1,
+                lookup_table: None,
+            },
+            data,
+        })
+    }
+
+    fn triangle_piece(z: f32, with_line: bool, cell_values: Vec<f64>) -> PolyDataPiece {
+        PolyDataPiece {
+            points: IOBuffer::F32(vec![0.0, 0.0, z, 1.0, 0.0, z, 0.0, 1.0, z]),
+            verts: None,
+            lines: with_line.then(|| VertexNumbers::XML {
+                connectivity: vec![0, 1],
+                offsets: vec![2],
+            }),
+            polys: Some(VertexNumbers::XML {
+                connectivity: vec![0, 1, 2],
+                offsets: vec![3],
+            }),
+            strips: None,
+            data: Attributes {
+                point: vec![scalar("t", IOBuffer::F32(vec![z; 3]))],
+                cell: vec![
+                    scalar("p", IOBuffer::F64(cell_values)),
+                    scalar(
+                        "only_here",
+                        IOBuffer::F32(vec![1.0; 1 + with_line as usize]),
+                    ),
+                ],
+            },
+        }
+    }
+
+    #[test]
+    fn test_single_piece_is_unchanged() {
+        let piece = triangle_piece(0.0, false, vec![1.0]);
+        let merged = merge_poly_pieces(vec![piece.clone()]).unwrap();
+        assert_eq!(merged, piece);
+    }
+
+    #[test]
+    fn test_merge_two_pieces() {
+        let mut second = triangle_piece(1.0, true, vec![20.0, 21.0]);
+        second.data.cell.pop();
+        let merged =
+            merge_poly_pieces(vec![triangle_piece(0.0, false, vec![10.0]), second]).unwrap();
+
+        assert_eq!(merged.points.len(), 18);
+        assert!(matches!(merged.points, IOBuffer::F32(_)));
+
+        let (conn, offs) = merged.polys.unwrap().into_xml();
+        assert_eq!(conn, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(offs, vec![3, 6]);
+
+        let (conn, offs) = merged.lines.unwrap().into_xml();
+        assert_eq!(conn, vec![3, 4]);
+        assert_eq!(offs, vec![2]);
+
+        // The second piece's line comes before all polygons
+        assert_eq!(
+            merged.data.cell[0],
+            scalar("p", IOBuffer::F64(vec![20.0, 10.0, 21.0]))
+        );
+        // An array missing from the second piece is filled with NaN there
+        let Attribute::DataArray(only_here) = &merged.data.cell[1] else {
+            panic!("expected a data array");
+        };
+        assert_eq!(only_here.name, "only_here");
+        let IOBuffer::F64(values) = &only_here.data else {
+            panic!("expected f64 values");
+        };
+        assert!(values[0].is_nan() && values[2].is_nan());
+        assert_eq!(values[1], 1.0);
+        assert_eq!(
+            merged.data.point,
+            vec![scalar(
+                "t",
+                IOBuffer::F32(vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0])
+            )]
+        );
+    }
+
+    #[test]
+    fn test_mixed_precision_points() {
+        let first = triangle_piece(0.0, false, vec![1.0]);
+        let mut second = triangle_piece(1.0, false, vec![2.0]);
+        second.points = IOBuffer::F64(vec![0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 1.0, 1.0]);
+
+        let merged = merge_poly_pieces(vec![first, second]).unwrap();
+        assert!(matches!(merged.points, IOBuffer::F64(ref v) if v.len() == 18));
+    }
+
+    #[test]
+    fn test_mixed_type_cell_data() {
+        let first = triangle_piece(0.0, false, vec![1.5]);
+        let mut second = triangle_piece(1.0, false, vec![2.0]);
+        second.data.cell[0] = scalar("p", IOBuffer::I32(vec![2]));
+
+        let merged = merge_poly_pieces(vec![first, second]).unwrap();
+        assert_eq!(
+            merged.data.cell[0],
+            scalar("p", IOBuffer::F64(vec![1.5, 2.0]))
+        );
+    }
+
+    #[test]
+    fn test_no_pieces() {
+        assert!(merge_poly_pieces(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+/// Cell values as the unweighted average of the values at each cell's points.
+/// `values` holds `num_comp` components per point.
+pub fn point_to_cell(
+    values: &[f64],
+    num_comp: usize,
+    connectivity: &[usize],
+    offsets: &[usize],
+) -> Vec<f64> {
+    let mut cell_values = Vec::with_capacity(offsets.len() * num_comp);
+    let mut conn_idx = 0;
+
+    for &offset in offsets {
+        let cell_indices = &connectivity[conn_idx..offset];
+        conn_idx = offset;
+
+        let mut sum = vec![0.0; num_comp];
+        for &point in cell_indices {
+            for (c, s) in sum.iter_mut().enumerate() {
+                *s += values[point * num_comp + c];
+            }
+        }
+        let count = cell_indices.len().max(1) as f64;
+        cell_values.extend(sum.iter().map(|s| s / count));
+    }
+
+    cell_values
+}
+
+/// Point values as the unweighted average of the values of every cell using
+/// the point. Points not used by any cell get zero.
+pub fn cell_to_point(
+    values: &[f64],
+    num_comp: usize,
+    connectivity: &[usize],
+    offsets: &[usize],
+    num_points: usize,
+) -> Vec<f64> {
+    let mut point_values = vec![0.0; num_points * num_comp];
+    let mut counts = vec![0usize; num_points];
+    let mut conn_idx = 0;
+
+    for (cell, &offset) in offsets.iter().enumerate() {
+        for &point in &connectivity[conn_idx..offset] {
+            for c in 0..num_comp {
+                point_values[point * num_comp + c] += values[cell * num_comp + c];
+            }
+            counts[point] += 1;
+        }
+        conn_idx = offset;
+    }
+
+    for (point, &count) in counts.iter().enumerate() {
+        if count > 0 {
+            for value in &mut point_values[point * num_comp..(point + 1) * num_comp] {
+                *value /= count as f64;
+            }
+        }
+    }
+
+    point_values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two triangles sharing the edge 1-2
+    const CONNECTIVITY: [usize; 6] = [0, 1, 2, 1, 3, 2];
+    const OFFSETS: [usize; 2] = [3, 6];
+
+    #[test]
+    fn test_point_to_cell() {
+        let values = [0.0, 3.0, 6.0, 9.0];
+        let cells = point_to_cell(&values, 1, &CONNECTIVITY, &OFFSETS);
+        assert_eq!(cells, vec![3.0, 6.0]);
+    }
+
+    #[test]
+    fn test_cell_to_point() {
+        let values = [1.0, 3.0];
+        let points = cell_to_point(&values, 1, &CONNECTIVITY, &OFFSETS, 5);
+        assert_eq!(points, vec![1.0, 2.0, 2.0, 3.0, 0.0]);
+    }
+
+    #[test]
+    fn test_multi_component() {
+        let values = [1.0, 10.0, 3.0, 30.0];
+        let points = cell_to_point(&values, 2, &CONNECTIVITY, &OFFSETS, 4);
+        assert_eq!(points, vec![1.0, 10.0, 2.0, 20.0, 2.0, 20.0, 3.0, 30.0]);
+
+        let cells = point_to_cell(&points, 2, &CONNECTIVITY, &OFFSETS);
+        assert_eq!(cells[0..2], [5.0 / 3.0, 50.0 / 3.0]);
+    }
+}
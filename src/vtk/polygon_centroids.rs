@@ -0,0 +1,78 @@
+use std::error::Error;
+
+use super::surface_mesh::SurfaceMesh;
+
+/// Area-weighted centroid of each polygon, from its fan triangulation.
+/// Polygons without area use the mean of their points.
+///
+/// Fails if `offsets` do not match `connectivity` or it refers to missing
+/// points.
+pub fn calculate_polygon_centroids(
+    points: &[f64],
+    connectivity: &[usize],
+    offsets: &[usize],
+) -> Result<Vec<[f64; 3]>, Box<dyn Error>> {
+    Ok(SurfaceMesh::from_polygons(points, connectivity, offsets)?
+        .centroids()
+        .to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triangle_centroid() {
+        let points = vec![
+            0.0, 0.0, 0.0, // vertex 0
+            3.0, 0.0, 0.0, // vertex 1
+            0.0, 3.0, 0.0, // vertex 2
+        ];
+
+        let centroids = calculate_polygon_centroids(&points, &[0, 1, 2], &[3]).unwrap();
+        assert_eq!(centroids, vec![[1.0, 1.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_area_weighting() {
+        // An L-shaped hexagon: a 2x1 rectangle with a 1x1 square on top
+        let points = vec![
+            0.0, 0.0, 0.0, // vertex 0
+            2.0, 0.0, 0.0, // vertex 1
+            2.0, 1.0, 0.0, // vertex 2
+            1.0, 1.0, 0.0, // vertex 3
+            1.0, 2.0, 0.0, // vertex 4
+            0.0, 2.0, 0.0, // vertex 5
+        ];
+
+        let centroids = calculate_polygon_centroids(&points, &[0, 1, 2, 3, 4, 5], &[6]).unwrap();
+        let expected = 5.0 / 6.0;
+        assert!((centroids[0][0] - expected).abs() < 1e-12);
+        assert!((centroids[0][1] - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_concave_fan() {
+        // The L-shaped hexagon fanned from a corner it cannot see all of
+        let points = vec![
+            2.0, 0.0, 0.0, // vertex 0
+            2.0, 1.0, 0.0, // vertex 1
+            1.0, 1.0, 0.0, // vertex 2
+            1.0, 2.0, 0.0, // vertex 3
+            0.0, 2.0, 0.0, // vertex 4
+            0.0, 0.0, 0.0, // vertex 5
+        ];
+
+        let centroids = calculate_polygon_centroids(&points, &[0, 1, 2, 3, 4, 5], &[6]).unwrap();
+        let expected = 5.0 / 6.0;
+        assert!((centroids[0][0] - expected).abs() < 1e-12);
+        assert!((centroids[0][1] - expected).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_degenerate_polygon() {
+        let points = vec![0.0, 0.0, 0.0, 2.0, 0.0, 0.0];
+        let centroids = calculate_polygon_centroids(&points, &[0, 1], &[2]).unwrap();
+        assert_eq!(centroids, vec![[1.0, 0.0, 0.0]]);
+    }
+}
@@ -0,0 +1,59 @@
+use std::error::Error;
+
+use super::surface_mesh::SurfaceMesh;
+
+/// Unit normal of each polygon by Newell's method, which averages the
+/// orientation of non-planar polygons. Degenerate polygons get a zero normal.
+///
+/// Fails if `offsets` do not match `connectivity` or it refers to missing
+/// points.
+pub fn calculate_polygon_normals(
+    points: &[f64],
+    connectivity: &[usize],
+    offsets: &[usize],
+) -> Result<Vec<[f64; 3]>, Box<dyn Error>> {
+    Ok(SurfaceMesh::from_polygons(points, connectivity, offsets)?
+        .normals()
+        .to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triangle_normal() {
+        let points = vec![
+            0.0, 0.0, 0.0, // vertex 0
+            1.0, 0.0, 0.0, // vertex 1
+            0.0, 1.0, 0.0, // vertex 2
+        ];
+
+        let normals = calculate_polygon_normals(&points, &[0, 1, 2], &[3]).unwrap();
+        assert_eq!(normals, vec![[0.0, 0.0, 1.0]]);
+
+        let normals = calculate_polygon_normals(&points, &[0, 2, 1], &[3]).unwrap();
+        assert_eq!(normals, vec![[0.0, 0.0, -1.0]]);
+    }
+
+    #[test]
+    fn test_non_planar_quad() {
+        // Opposite corners lifted; the averaged normal stays along z
+        let points = vec![
+            0.0, 0.0, 0.1, // vertex 0
+            1.0, 0.0, -0.1, // vertex 1
+            1.0, 1.0, 0.1, // vertex 2
+            0.0, 1.0, -0.1, // vertex 3
+        ];
+
+        let normals = calculate_polygon_normals(&points, &[0, 1, 2, 3], &[4]).unwrap();
+        assert!((normals[0][2] - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_degenerate_polygon() {
+        let points = vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+        let normals = calculate_polygon_normals(&points, &[0, 1], &[2]).unwrap();
+        assert_eq!(normals, vec![[0.0; 3]]);
+    }
+}
@@ -1,24 +1,103 @@
 use std::error::Error;
+use std::fs;
 use std::fs::File;
 use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
 
 use vtkio::model::DataSet;
 use vtkio::model::Piece;
 use vtkio::model::PolyDataPiece;
 use vtkio::Vtk;
 
+use super::atomic_write::write_atomic;
+use super::compressed_arrays::decompress_inline_arrays;
+use super::format::VtkFormat;
+use super::multiblock::Block;
+use super::multiblock::MultiBlock;
+use super::pieces::merge_pieces;
+use super::pieces::merge_poly_pieces;
+use super::xml_writer::write_poly_data;
+use super::xml_writer::Compression;
+use super::xml_writer::Encoding;
+use super::xml_writer::WriteOptions;
+
 pub struct VtkReader {
     vtk: Vtk,
 }
 
 impl VtkReader {
+    /// Reads a PolyData file, merging all of its pieces. For a `.vtm`
+    /// multiblock file every PolyData block is loaded and merged.
     pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        let vtk = Vtk::parse_xml(reader)?;
+        if VtkFormat::is_multiblock(path) {
+            let multi_block = MultiBlock::from_file(path)?;
+            // Volume blocks such as OpenFOAM's internal.vtu are not surfaces
+            let blocks: Vec<&Block> = multi_block
+                .blocks
+                .iter()
+                .filter(|b| !VtkFormat::is_volume(&b.file))
+                .collect();
+            return Self::from_blocks(&blocks)
+                .map_err(|e| format!("Failed to load '{}': {}", path, e).into());
+        }
+
+        let format = VtkFormat::detect(path)?;
+        // Legacy binary data is always big-endian
+        let mut vtk = match format {
+            VtkFormat::Xml => Vtk::parse_xml(&*decompress_inline_arrays(&fs::read(path)?)?)?,
+            VtkFormat::Legacy => Vtk::parse_legacy_be(BufReader::new(File::open(path)?))?,
+        };
+        let base_dir = Path::new(path).parent().unwrap_or(Path::new(""));
+        merge_pieces(&mut vtk, base_dir)?;
         Ok(VtkReader { vtk })
     }
 
+    /// Reads the blocks of a `.vtm` multiblock file matching `name` (see
+    /// [`MultiBlock::select`]), merged into a single piece.
+    pub fn from_block(path: &str, name: &str) -> Result<Self, Box<dyn Error>> {
+        let multi_block = MultiBlock::from_file(path)?;
+        let blocks = multi_block.select(name);
+        if blocks.is_empty() {
+            return Err(format!(
+                "Block '{}' not found in '{}', available blocks: {}",
+                name,
+                path,
+                multi_block.names().join(", ")
+            )
+            .into());
+        }
+        Self::from_blocks(&blocks)
+    }
+
+    fn from_blocks(blocks: &[&Block]) -> Result<Self, Box<dyn Error>> {
+        let mut vtks = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let path = block.file.to_str().ok_or("Invalid block file path")?;
+            vtks.push(Self::from_file(path)?.into_vtk());
+        }
+
+        let mut vtk = vtks.first().cloned().ok_or("No PolyData blocks found")?;
+        let pieces = vtks
+            .into_iter()
+            .map(|vtk| get_poly_data(&vtk).cloned())
+            .collect::<Result<Vec<_>, _>>()?;
+        vtk.data = DataSet::PolyData {
+            meta: None,
+            pieces: vec![Piece::Inline(Box::new(merge_poly_pieces(pieces)?))],
+        };
+        Ok(VtkReader { vtk })
+    }
+
+    pub fn from_vtk(vtk: Vtk) -> Self {
+        VtkReader { vtk }
+    }
+
+    pub fn into_vtk(self) -> Vtk {
+        self.vtk
+    }
+
     pub fn vtk(&self) -> &Vtk {
         &self.vtk
     }
@@ -27,9 +106,59 @@ impl VtkReader {
         &mut self.vtk
     }
 
+    /// Writes legacy `.vtk` files as ascii and XML files with the default
+    /// [`WriteOptions`].
     pub fn write_to_file(self, path: &str) -> Result<(), Box<dyn Error>> {
-        let mut file = File::create(path)?;
-        self.vtk.write_xml(&mut file)?;
+        let options = match VtkFormat::from_extension(path) {
+            Some(VtkFormat::Legacy) => WriteOptions {
+                encoding: Encoding::Ascii,
+                ..WriteOptions::default()
+            },
+            _ => WriteOptions::default(),
+        };
+        self.write_to_file_with(path, &options)
+    }
+
+    /// Writes with the given encoding. Legacy `.vtk` files can only be
+    /// written as uncompressed ascii or binary.
+    pub fn write_to_file_with(
+        self,
+        path: &str,
+        options: &WriteOptions,
+    ) -> Result<(), Box<dyn Error>> {
+        Self::check_writable(path)?;
+        if VtkFormat::from_extension(path) == Some(VtkFormat::Legacy) {
+            if options.compression != Compression::None {
+                return Err("Legacy .vtk files cannot be compressed".into());
+            }
+            return match options.encoding {
+                Encoding::Ascii => {
+                    let mut text = String::new();
+                    self.vtk.write_legacy_ascii(&mut text)?;
+                    write_atomic(path, |file| Ok(file.write_all(text.as_bytes())?))
+                }
+                Encoding::Binary => write_atomic(path, |file| Ok(self.vtk.write_legacy(file)?)),
+                Encoding::Appended => Err("Legacy .vtk files cannot use appended data".into()),
+            };
+        }
+
+        let piece = get_poly_data(&self.vtk)?;
+        write_atomic(path, |file| {
+            let mut writer = BufWriter::new(file);
+            write_poly_data(piece, options, &mut writer)?;
+            writer.flush()?;
+            Ok(())
+        })
+    }
+
+    fn check_writable(path: &str) -> Result<(), Box<dyn Error>> {
+        if VtkFormat::is_multiblock(path) {
+            return Err(format!(
+                "Cannot write multiblock file '{}', write to a .vtp instead",
+                path
+            )
+            .into());
+        }
         Ok(())
     }
 }
@@ -0,0 +1,242 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::coordinates::parse_vector;
+use crate::coordinates::vector::dot;
+use crate::coordinates::vector::length;
+use crate::coordinates::vector::sub;
+use crate::coordinates::vector::unit;
+
+/// An implicit primitive selecting part of space. Each has a signed
+/// distance-like function that is negative inside.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Region {
+    /// The half-space behind the plane, i.e. opposite to its normal
+    Plane {
+        origin: [f64; 3],
+        normal: [f64; 3],
+    },
+    /// An axis-aligned box
+    Box {
+        min: [f64; 3],
+        max: [f64; 3],
+    },
+    Sphere {
+        centre: [f64; 3],
+        radius: f64,
+    },
+    /// An infinite cylinder around the axis through `point`
+    Cylinder {
+        point: [f64; 3],
+        axis: [f64; 3],
+        radius: f64,
+    },
+}
+
+impl Region {
+    /// Negative inside the region, zero on its boundary and positive
+    /// outside. Exact distance for planes, spheres and cylinders; for boxes
+    /// the largest distance beyond any of the faces.
+    pub fn value(&self, p: [f64; 3]) -> f64 {
+        match *self {
+            Region::Plane { origin, normal } => dot(sub(p, origin), unit(normal)),
+            Region::Box { min, max } => (0..3)
+                .map(|i| (min[i] - p[i]).max(p[i] - max[i]))
+                .fold(f64::NEG_INFINITY, f64::max),
+            Region::Sphere { centre, radius } => length(sub(p, centre)) - radius,
+            Region::Cylinder {
+                point,
+                axis,
+                radius,
+            } => {
+                let axis = unit(axis);
+                let d = sub(p, point);
+                let along = dot(d, axis);
+                length(sub(d, axis.map(|a| a * along))) - radius
+            }
+        }
+    }
+
+    pub fn contains(&self, p: [f64; 3]) -> bool {
+        self.value(p) <= 0.0
+    }
+
+    /// The region as an intersection of half-spaces, for regions bounded by
+    /// planes.
+    pub fn planes(&self) -> Option<Vec<Region>> {
+        match *self {
+            Region::Plane { .. } => Some(vec![*self]),
+            Region::Box { min, max } => Some(
+                (0..3)
+                    .flat_map(|i| {
+                        let mut normal = [0.0; 3];
+                        normal[i] = 1.0;
+                        [
+                            Region::Plane {
+                                origin: max,
+                                normal,
+                            },
+                            Region::Plane {
+                                origin: min,
+                                normal: normal.map(|n| -n),
+                            },
+                        ]
+                    })
+                    .collect(),
+            ),
+            Region::Sphere { .. } | Region::Cylinder { .. } => None,
+        }
+    }
+}
+
+/// Whether `p` is inside all of `regions`, or with `invert` outside all of
+/// them.
+pub fn inside_all(regions: &[Region], p: [f64; 3], invert: bool) -> bool {
+    let sign = if invert { -1.0 } else { 1.0 };
+    regions.iter().all(|r| sign * r.value(p) <= 0.0)
+}
+
+impl FromStr for Region {
+    type Err = String;
+
+    /// Parses `plane:ORIGIN:NORMAL`, `box:MIN:MAX`, `sphere:CENTRE:RADIUS`
+    /// or `cylinder:POINT:AXIS:RADIUS`, with vectors given as `x,y,z`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').map(str::trim).collect();
+        let scalar = |v: &str| {
+            v.parse::<f64>()
+                .map_err(|_| format!("Invalid number '{}' in '{}'", v, s))
+        };
+
+        let region = match parts.as_slice() {
+            [kind, origin, normal] if kind.eq_ignore_ascii_case("plane") => Region::Plane {
+                origin: parse_vector(origin)?,
+                normal: parse_vector(normal)?,
+            },
+            [kind, min, max] if kind.eq_ignore_ascii_case("box") => Region::Box {
+                min: parse_vector(min)?,
+                max: parse_vector(max)?,
+            },
+            [kind, centre, radius] if kind.eq_ignore_ascii_case("sphere") => Region::Sphere {
+                centre: parse_vector(centre)?,
+                radius: scalar(radius)?,
+            },
+            [kind, point, axis, radius] if kind.eq_ignore_ascii_case("cylinder") => {
+                Region::Cylinder {
+                    point: parse_vector(point)?,
+                    axis: parse_vector(axis)?,
+                    radius: scalar(radius)?,
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "Unknown region '{}', expected plane:ORIGIN:NORMAL, box:MIN:MAX, \
+                     sphere:CENTRE:RADIUS or cylinder:POINT:AXIS:RADIUS",
+                    s
+                ))
+            }
+        };
+
+        match region {
+            Region::Plane { normal, .. } | Region::Cylinder { axis: normal, .. }
+                if length(normal) == 0.0 =>
+            {
+                Err(format!("Zero direction vector in '{}'", s))
+            }
+            Region::Box { min, max } if (0..3).any(|i| min[i] > max[i]) => {
+                Err(format!("Box minimum exceeds its maximum in '{}'", s))
+            }
+            Region::Sphere { radius, .. } | Region::Cylinder { radius, .. } if radius < 0.0 => {
+                Err(format!("Negative radius in '{}'", s))
+            }
+            region => Ok(region),
+        }
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let v = |v: [f64; 3]| format!("{},{},{}", v[0], v[1], v[2]);
+        match *self {
+            Region::Plane { origin, normal } => write!(f, "plane:{}:{}", v(origin), v(normal)),
+            Region::Box { min, max } => write!(f, "box:{}:{}", v(min), v(max)),
+            Region::Sphere { centre, radius } => write!(f, "sphere:{}:{}", v(centre), radius),
+            Region::Cylinder {
+                point,
+                axis,
+                radius,
+            } => write!(f, "cylinder:{}:{}:{}", v(point), v(axis), radius),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values() {
+        let plane: Region = "plane:0,0,1:0,0,2".parse().unwrap();
+        assert_eq!(plane.value([5.0, 5.0, 3.0]), 2.0);
+        assert!(plane.contains([0.0, 0.0, 0.5]));
+
+        let sphere: Region = "sphere:1,0,0:2".parse().unwrap();
+        assert_eq!(sphere.value([1.0, 3.0, 0.0]), 1.0);
+
+        let cylinder: Region = "cylinder:0,0,0:0,0,5:1".parse().unwrap();
+        assert_eq!(cylinder.value([3.0, 0.0, 100.0]), 2.0);
+        assert!(cylinder.contains([0.5, 0.5, -100.0]));
+
+        let cube: Region = "box:0,0,0:1,1,1".parse().unwrap();
+        assert_eq!(cube.value([0.5, 0.5, 0.5]), -0.5);
+        assert_eq!(cube.value([0.5, 3.0, 0.5]), 2.0);
+    }
+
+    #[test]
+    fn test_inside_all() {
+        let regions: Vec<Region> = ["sphere:0,0,0:2", "plane:0,0,0:1,0,0"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        assert!(inside_all(&regions, [-1.0, 0.0, 0.0], false));
+        assert!(!inside_all(&regions, [1.0, 0.0, 0.0], false));
+        assert!(inside_all(&regions, [3.0, 0.0, 0.0], true));
+        assert!(!inside_all(&regions, [-3.0, 0.0, 0.0], true));
+    }
+
+    #[test]
+    fn test_box_planes() {
+        let cube: Region = "box:0,0,0:1,1,2".parse().unwrap();
+        let planes = cube.planes().unwrap();
+        assert_eq!(planes.len(), 6);
+        for p in [[0.5, 0.5, 1.5], [2.0, 0.5, 0.5], [0.5, 0.5, -1.0]] {
+            assert_eq!(planes.iter().all(|r| r.contains(p)), cube.contains(p));
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "Sphere:(0 0 0):1.5".parse::<Region>().unwrap(),
+            Region::Sphere {
+                centre: [0.0; 3],
+                radius: 1.5
+            }
+        );
+        for invalid in [
+            "plane:0,0,0:0,0,0",
+            "box:1,0,0:0,1,1",
+            "sphere:0,0,0:-1",
+            "cone:0,0,0:1",
+            "sphere:0,0,0",
+        ] {
+            assert!(invalid.parse::<Region>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let cylinder: Region = "cylinder:1,2,3:0,0,1:0.5".parse().unwrap();
+        assert_eq!(cylinder.to_string().parse::<Region>().unwrap(), cylinder);
+    }
+}
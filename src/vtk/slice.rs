@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+
+use super::geometry::CellKind;
+use super::surface_mesh::SurfaceMesh;
+use crate::surface::fan_triangulate;
+use crate::surface::strip_triangulate;
+
+/// A point of a slice, on the edge between mesh points `a` and `b` at
+/// parameter `t` from `a`. Mesh points lying on the plane have `a == b`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgePoint {
+    pub a: usize,
+    pub b: usize,
+    pub t: f64,
+}
+
+impl EdgePoint {
+    pub fn position(&self, points: &[[f64; 3]]) -> [f64; 3] {
+        let (pa, pb) = (points[self.a], points[self.b]);
+        [0, 1, 2].map(|i| pa[i] + self.t * (pb[i] - pa[i]))
+    }
+
+    /// Linear interpolation of point values with `num_comp` components.
+    pub fn interpolate(&self, values: &[f64], num_comp: usize) -> Vec<f64> {
+        let tuple = |i: usize| &values[i * num_comp..(i + 1) * num_comp];
+        tuple(self.a)
+            .iter()
+            .zip(tuple(self.b))
+            .map(|(va, vb)| va + self.t * (vb - va))
+            .collect()
+    }
+}
+
+/// A connected part of a slice. Closed loops repeat their first point at
+/// the end.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polyline {
+    pub points: Vec<EdgePoint>,
+}
+
+impl Polyline {
+    pub fn is_closed(&self) -> bool {
+        self.points.len() > 2 && self.points.first() == self.points.last()
+    }
+
+    /// Distance along the polyline to each of its points.
+    pub fn arc_lengths(&self, points: &[[f64; 3]]) -> Vec<f64> {
+        let positions: Vec<[f64; 3]> = self.points.iter().map(|p| p.position(points)).collect();
+        let mut length = 0.0;
+        let mut lengths = Vec::with_capacity(positions.len());
+        for (i, p) in positions.iter().enumerate() {
+            if i > 0 {
+                let q = positions[i - 1];
+                length +=
+                    ((p[0] - q[0]).powi(2) + (p[1] - q[1]).powi(2) + (p[2] - q[2]).powi(2)).sqrt();
+            }
+            lengths.push(length);
+        }
+        lengths
+    }
+}
+
+/// Where the plane crosses the mesh: a mesh point on the plane or an edge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Node {
+    Point(usize),
+    Edge(usize, usize),
+}
+
+/// Intersects the polygons and strips of `mesh` with the plane through
+/// `origin` with `normal`, chaining the cut segments into polylines.
+///
+/// Open polylines run from their lexicographically smaller end (by x, then
+/// y, then z) and are sorted by their start, so repeated slices of similar
+/// meshes come out alike.
+pub fn slice_mesh(mesh: &SurfaceMesh, origin: [f64; 3], normal: [f64; 3]) -> Vec<Polyline> {
+    let distances: Vec<f64> = mesh
+        .points()
+        .iter()
+        .map(|p| (0..3).map(|i| (p[i] - origin[i]) * normal[i]).sum())
+        .collect();
+    // Points on the plane count as above it, so that every triangle is
+    // cut zero or two times
+    let above = |i: usize| distances[i] >= 0.0;
+
+    let mut edge_points = HashMap::new();
+    let mut node = |a: usize, b: usize| -> Node {
+        let (a, b) = if a < b { (a, b) } else { (b, a) };
+        let t = distances[a] / (distances[a] - distances[b]);
+        let node = if t <= 0.0 {
+            Node::Point(a)
+        } else if t >= 1.0 {
+            Node::Point(b)
+        } else {
+            Node::Edge(a, b)
+        };
+        edge_points.entry(node).or_insert_with(|| match node {
+            Node::Point(i) => EdgePoint { a: i, b: i, t: 0.0 },
+            Node::Edge(..) => EdgePoint { a, b, t },
+        });
+        node
+    };
+
+    let mut segments = Vec::new();
+    for cell in mesh.cells() {
+        let triangles = match cell.kind {
+            CellKind::Vertex | CellKind::Line => continue,
+            CellKind::Polygon => fan_triangulate(cell.point_ids),
+            CellKind::Strip => strip_triangulate(cell.point_ids),
+        };
+        for triangle in triangles {
+            let crossings: Vec<Node> = (0..3)
+                .map(|j| (triangle[j], triangle[(j + 1) % 3]))
+                .filter(|&(a, b)| above(a) != above(b))
+                .map(|(a, b)| node(a, b))
+                .collect();
+            if let [first, second] = crossings[..] {
+                if first != second {
+                    segments.push((first.min(second), first.max(second)));
+                }
+            }
+        }
+    }
+    // Edges in the plane are cut for the triangles on both sides
+    segments.sort();
+    segments.dedup();
+
+    let mut polylines: Vec<Polyline> = chain(&segments)
+        .into_iter()
+        .map(|nodes| Polyline {
+            points: nodes.iter().map(|n| edge_points[n]).collect(),
+        })
+        .collect();
+
+    let start = |polyline: &Polyline| polyline.points[0].position(mesh.points());
+    for polyline in &mut polylines {
+        let last = polyline.points.last().unwrap().position(mesh.points());
+        if !polyline.is_closed() && lexicographic(last, start(polyline)).is_lt() {
+            polyline.points.reverse();
+        }
+    }
+    polylines.sort_by(|a, b| lexicographic(start(a), start(b)));
+    polylines
+}
+
+/// Walks the segments into chains, starting from the ends of open chains
+/// and then around closed loops.
+fn chain(segments: &[(Node, Node)]) -> Vec<Vec<Node>> {
+    let mut adjacent: HashMap<Node, Vec<usize>> = HashMap::new();
+    for (i, &(a, b)) in segments.iter().enumerate() {
+        adjacent.entry(a).or_default().push(i);
+        adjacent.entry(b).or_default().push(i);
+    }
+
+    let mut starts: Vec<Node> = adjacent
+        .iter()
+        .filter(|(_, s)| s.len() % 2 == 1)
+        .map(|(&n, _)| n)
+        .collect();
+    starts.sort();
+    let mut rest: Vec<Node> = segments.iter().map(|&(a, _)| a).collect();
+    rest.sort();
+    starts.extend(rest);
+
+    let mut used = vec![false; segments.len()];
+    let mut chains = Vec::new();
+    for start in starts {
+        let mut current = start;
+        let mut nodes = vec![start];
+        while let Some(&i) = adjacent[&current].iter().find(|&&i| !used[i]) {
+            used[i] = true;
+            let (a, b) = segments[i];
+            current = if a == current { b } else { a };
+            nodes.push(current);
+        }
+        if nodes.len() > 1 {
+            chains.push(nodes);
+        }
+    }
+    chains
+}
+
+fn lexicographic(a: [f64; 3], b: [f64; 3]) -> std::cmp::Ordering {
+    a[0].total_cmp(&b[0])
+        .then(a[1].total_cmp(&b[1]))
+        .then(a[2].total_cmp(&b[2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two unit squares side by side in the z = 0 plane
+    fn strip_of_squares() -> SurfaceMesh {
+        let points = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [2.0, 1.0, 0.0],
+        ];
+        SurfaceMesh::new(
+            points,
+            vec![0, 1, 4, 3, 1, 2, 5, 4],
+            vec![4, 8],
+            vec![CellKind::Polygon; 2],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_open_slice() {
+        let mesh = strip_of_squares();
+        let polylines = slice_mesh(&mesh, [0.0, 0.25, 0.0], [0.0, 1.0, 0.0]);
+        assert_eq!(polylines.len(), 1);
+
+        let polyline = &polylines[0];
+        assert!(!polyline.is_closed());
+        let positions: Vec<[f64; 3]> = polyline
+            .points
+            .iter()
+            .map(|p| p.position(mesh.points()))
+            .collect();
+        assert_eq!(positions.first(), Some(&[0.0, 0.25, 0.0]));
+        assert_eq!(positions.last(), Some(&[2.0, 0.25, 0.0]));
+        assert_eq!(polyline.arc_lengths(mesh.points()).last(), Some(&2.0));
+    }
+
+    #[test]
+    fn test_slice_through_points() {
+        let mesh = strip_of_squares();
+        let polylines = slice_mesh(&mesh, [1.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        assert_eq!(polylines.len(), 1);
+        let points = &polylines[0].points;
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0], EdgePoint { a: 1, b: 1, t: 0.0 });
+        assert_eq!(points[1], EdgePoint { a: 4, b: 4, t: 0.0 });
+    }
+
+    #[test]
+    fn test_closed_loop() {
+        // The side faces of a unit cube
+        let points = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 0.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [0.0, 1.0, 1.0],
+        ];
+        let faces = [[0, 1, 5, 4], [1, 2, 6, 5], [2, 3, 7, 6], [3, 0, 4, 7]];
+        let mesh = SurfaceMesh::new(
+            points,
+            faces.as_flattened().to_vec(),
+            vec![4, 8, 12, 16],
+            vec![CellKind::Polygon; 4],
+        )
+        .unwrap();
+
+        let polylines = slice_mesh(&mesh, [0.0, 0.0, 0.5], [0.0, 0.0, 1.0]);
+        assert_eq!(polylines.len(), 1);
+        assert!(polylines[0].is_closed());
+        assert_eq!(polylines[0].arc_lengths(mesh.points()).last(), Some(&4.0));
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let point = EdgePoint {
+            a: 0,
+            b: 1,
+            t: 0.25,
+        };
+        assert_eq!(point.interpolate(&[0.0, 10.0, 4.0, 2.0], 2), vec![1.0, 8.0]);
+    }
+
+    #[test]
+    fn test_no_intersection() {
+        let polylines = slice_mesh(&strip_of_squares(), [0.0, 0.0, 1.0], [0.0, 0.0, 1.0]);
+        assert!(polylines.is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+use std::error::Error;
+
+use vtkio::model::Attribute;
+use vtkio::model::Attributes;
+use vtkio::model::ByteOrder;
+use vtkio::model::DataArray;
+use vtkio::model::DataSet;
+use vtkio::model::ElementType;
+use vtkio::model::IOBuffer;
+use vtkio::model::Piece;
+use vtkio::model::PolyDataPiece;
+use vtkio::model::Version;
+use vtkio::model::VertexNumbers;
+use vtkio::Vtk;
+
+use super::geometry::CellKind;
+use super::geometry::GeometryExtractor;
+use crate::surface::fan_triangulate;
+use crate::surface::index_triangles;
+use crate::surface::strip_triangulate;
+use crate::surface::triangle_normal;
+
+pub const REGION_ID_FIELD: &str = "regionId";
+pub const NORMALS_FIELD: &str = "normals";
+
+pub struct SurfaceConverter;
+
+impl SurfaceConverter {
+    /// Builds a PolyData surface from a triangle soup (three vertices per
+    /// triangle). `region_ids`, one per triangle, and unit facet normals are
+    /// added as cell fields when given.
+    pub fn from_triangles(
+        vertices: &[[f32; 3]],
+        region_ids: Option<&[i32]>,
+        with_normals: bool,
+    ) -> Result<Vtk, Box<dyn Error>> {
+        let (points, triangles) = index_triangles(vertices);
+
+        let mut cell_data = Vec::new();
+        if let Some(ids) = region_ids {
+            if ids.len() != triangles.len() {
+                return Err(
+                    format!("Expected {} region ids, got {}", triangles.len(), ids.len()).into(),
+                );
+            }
+            cell_data.push(Attribute::DataArray(DataArray {
+                name: REGION_ID_FIELD.to_string(),
+                elem: ElementType::Scalars {
+                    num_comp: 1,
+                    lookup_table: None,
+                },
+                data: IOBuffer::I32(ids.to_vec()),
+            }));
+        }
+        if with_normals {
+            let (soup, _) = vertices.as_chunks::<3>();
+            let normals = soup.iter().flat_map(triangle_normal).collect();
+            cell_data.push(Attribute::DataArray(DataArray {
+                name: NORMALS_FIELD.to_string(),
+                elem: ElementType::Normals,
+                data: IOBuffer::F32(normals),
+            }));
+        }
+
+        let piece = PolyDataPiece {
+            points: IOBuffer::F32(points.iter().flatten().copied().collect()),
+            verts: None,
+            lines: None,
+            polys: Some(VertexNumbers::XML {
+                connectivity: triangles.iter().flatten().map(|&i| i as u64).collect(),
+                offsets: (1..=triangles.len() as u64).map(|i| 3 * i).collect(),
+            }),
+            strips: None,
+            data: Attributes {
+                point: Vec::new(),
+                cell: cell_data,
+            },
+        };
+
+        Ok(Vtk {
+            version: Version::new((1, 0)),
+            title: String::new(),
+            byte_order: ByteOrder::LittleEndian,
+            file_path: None,
+            data: DataSet::PolyData {
+                meta: None,
+                pieces: vec![Piece::Inline(Box::new(piece))],
+            },
+        })
+    }
+
+    /// Builds a PolyData of polylines, each a list of indices into `points`.
+    pub fn from_polylines(points: &[[f64; 3]], lines: &[Vec<usize>]) -> Vtk {
+        let mut offsets = Vec::with_capacity(lines.len());
+        let mut end = 0;
+        for line in lines {
+            end += line.len() as u64;
+            offsets.push(end);
+        }
+
+        let piece = PolyDataPiece {
+            points: IOBuffer::F64(points.iter().flatten().copied().collect()),
+            verts: None,
+            lines: Some(VertexNumbers::XML {
+                connectivity: lines.iter().flatten().map(|&i| i as u64).collect(),
+                offsets,
+            }),
+            polys: None,
+            strips: None,
+            data: Attributes::new(),
+        };
+
+        Vtk {
+            version: Version::new((1, 0)),
+            title: String::new(),
+            byte_order: ByteOrder::LittleEndian,
+            file_path: None,
+            data: DataSet::PolyData {
+                meta: None,
+                pieces: vec![Piece::Inline(Box::new(piece))],
+            },
+        }
+    }
+
+    /// Extracts the polygons and strips of a PolyData surface as a triangle
+    /// soup, fan-triangulating quads and larger polygons. Vertices and lines
+    /// are skipped.
+    pub fn to_triangles(vtk: &Vtk) -> Result<Vec<[f32; 3]>, Box<dyn Error>> {
+        let mesh = GeometryExtractor::extract_geometry(vtk)?;
+        let points = mesh.points();
+
+        let mut vertices = Vec::new();
+        for cell in mesh.cells() {
+            let triangles = match cell.kind {
+                CellKind::Vertex | CellKind::Line => continue,
+                CellKind::Polygon => fan_triangulate(cell.point_ids),
+                CellKind::Strip => strip_triangulate(cell.point_ids),
+            };
+            for triangle in triangles {
+                for i in triangle {
+                    vertices.push(points[i].map(|x| x as f32));
+                }
+            }
+        }
+
+        Ok(vertices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vtk::field_manager::FieldManager;
+    use crate::vtk::reader::get_poly_data;
+
+    const QUAD: [[f32; 3]; 6] = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0],
+    ];
+
+    #[test]
+    fn test_round_trip() {
+        let vtk = SurfaceConverter::from_triangles(&QUAD, None, false).unwrap();
+        let mesh = GeometryExtractor::extract_geometry(&vtk).unwrap();
+
+        assert_eq!(mesh.num_points(), 4);
+        assert_eq!(mesh.connectivity(), &[0, 1, 2, 0, 2, 3]);
+        assert_eq!(mesh.offsets(), &[3, 6]);
+
+        assert_eq!(SurfaceConverter::to_triangles(&vtk).unwrap(), QUAD);
+    }
+
+    #[test]
+    fn test_region_ids_and_normals() {
+        let vtk = SurfaceConverter::from_triangles(&QUAD, Some(&[0, 1]), true).unwrap();
+
+        let cell_data = &get_poly_data(&vtk).unwrap().data.cell;
+        assert!(cell_data.iter().any(|attr| matches!(
+            attr,
+            Attribute::DataArray(arr)
+                if arr.name == REGION_ID_FIELD && arr.data == IOBuffer::I32(vec![0, 1])
+        )));
+        assert_eq!(
+            FieldManager::get_field(&vtk, NORMALS_FIELD).unwrap(),
+            vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn test_region_id_count_mismatch() {
+        assert!(SurfaceConverter::from_triangles(&QUAD, Some(&[0]), false).is_err());
+    }
+}
@@ -0,0 +1,322 @@
+use std::cell::OnceCell;
+use std::error::Error;
+
+use super::cell_areas::calculate_cell_areas;
+use super::geometry::CellKind;
+use super::point_cell_averaging::cell_to_point;
+use super::point_cell_averaging::point_to_cell;
+use crate::coordinates::vector::cross;
+use crate::coordinates::vector::dot;
+use crate::coordinates::vector::sub;
+use crate::coordinates::vector::unit;
+use crate::surface::fan_triangulate;
+use crate::surface::strip_triangulate;
+
+/// Points and cells of a PolyData surface, with cell geometry computed on
+/// first use.
+#[derive(Debug, Clone)]
+pub struct SurfaceMesh {
+    points: Vec<[f64; 3]>,
+    connectivity: Vec<usize>,
+    offsets: Vec<usize>,
+    kinds: Vec<CellKind>,
+    areas: OnceCell<Vec<f64>>,
+    normals: OnceCell<Vec<[f64; 3]>>,
+    centroids: OnceCell<Vec<[f64; 3]>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell<'a> {
+    pub kind: CellKind,
+    pub point_ids: &'a [usize],
+}
+
+impl SurfaceMesh {
+    /// Builds a mesh from VTK XML style connectivity, where `offsets` holds
+    /// the end of each cell in `connectivity`.
+    pub fn new(
+        points: Vec<[f64; 3]>,
+        connectivity: Vec<usize>,
+        offsets: Vec<usize>,
+        kinds: Vec<CellKind>,
+    ) -> Result<Self, Box<dyn Error>> {
+        if offsets.len() != kinds.len() {
+            return Err(format!(
+                "Got {} offsets but {} cell kinds",
+                offsets.len(),
+                kinds.len()
+            )
+            .into());
+        }
+        if offsets.windows(2).any(|w| w[1] < w[0]) {
+            return Err("Cell offsets are not increasing".into());
+        }
+        if offsets.last().copied().unwrap_or(0) != connectivity.len() {
+            return Err("Last cell offset does not match the connectivity length".into());
+        }
+        if let Some(&index) = connectivity.iter().find(|&&i| i >= points.len()) {
+            return Err(format!(
+                "Point index {} out of range for {} points",
+                index,
+                points.len()
+            )
+            .into());
+        }
+
+        Ok(SurfaceMesh {
+            points,
+            connectivity,
+            offsets,
+            kinds,
+            areas: OnceCell::new(),
+            normals: OnceCell::new(),
+            centroids: OnceCell::new(),
+        })
+    }
+
+    /// Builds a mesh of polygons from flat `x, y, z` point coordinates.
+    pub fn from_polygons(
+        points: &[f64],
+        connectivity: &[usize],
+        offsets: &[usize],
+    ) -> Result<Self, Box<dyn Error>> {
+        Self::new(
+            points.as_chunks::<3>().0.to_vec(),
+            connectivity.to_vec(),
+            offsets.to_vec(),
+            vec![CellKind::Polygon; offsets.len()],
+        )
+    }
+
+    pub fn points(&self) -> &[[f64; 3]] {
+        &self.points
+    }
+
+    pub fn connectivity(&self) -> &[usize] {
+        &self.connectivity
+    }
+
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    pub fn kinds(&self) -> &[CellKind] {
+        &self.kinds
+    }
+
+    pub fn num_points(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn num_cells(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn cell(&self, index: usize) -> Cell<'_> {
+        let start = index.checked_sub(1).map_or(0, |i| self.offsets[i]);
+        Cell {
+            kind: self.kinds[index],
+            point_ids: &self.connectivity[start..self.offsets[index]],
+        }
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = Cell<'_>> {
+        (0..self.num_cells()).map(|i| self.cell(i))
+    }
+
+    /// Cell areas; zero for vertices and lines.
+    pub fn areas(&self) -> &[f64] {
+        self.areas.get_or_init(|| {
+            calculate_cell_areas(
+                self.points.as_flattened(),
+                &self.connectivity,
+                &self.offsets,
+                &self.kinds,
+            )
+        })
+    }
+
+    pub fn total_area(&self) -> f64 {
+        self.areas().iter().sum()
+    }
+
+    /// Unit cell normals following the right-hand rule on the point order;
+    /// zero for vertices, lines and degenerate cells.
+    pub fn normals(&self) -> &[[f64; 3]] {
+        self.normals.get_or_init(|| {
+            self.cells()
+                .map(|cell| unit(self.area_vector(cell)))
+                .collect()
+        })
+    }
+
+    /// Area-weighted cell centroids. Cells without area use the mean of
+    /// their points.
+    pub fn centroids(&self) -> &[[f64; 3]] {
+        self.centroids
+            .get_or_init(|| self.cells().map(|cell| self.centroid(cell)).collect())
+    }
+
+    /// Averages point values onto the cells, see [`point_to_cell`].
+    pub fn point_to_cell(&self, values: &[f64], num_comp: usize) -> Vec<f64> {
+        point_to_cell(values, num_comp, &self.connectivity, &self.offsets)
+    }
+
+    /// Averages cell values onto the points, see [`cell_to_point`].
+    pub fn cell_to_point(&self, values: &[f64], num_comp: usize) -> Vec<f64> {
+        cell_to_point(
+            values,
+            num_comp,
+            &self.connectivity,
+            &self.offsets,
+            self.num_points(),
+        )
+    }
+
+    fn triangles(cell: Cell) -> Vec<[usize; 3]> {
+        match cell.kind {
+            CellKind::Vertex | CellKind::Line => Vec::new(),
+            CellKind::Polygon => fan_triangulate(cell.point_ids),
+            CellKind::Strip => strip_triangulate(cell.point_ids),
+        }
+    }
+
+    /// Twice the area times the unit normal. Polygons use Newell's method so
+    /// that non-planar polygons get an averaged normal.
+    fn area_vector(&self, cell: Cell) -> [f64; 3] {
+        match cell.kind {
+            CellKind::Vertex | CellKind::Line => [0.0; 3],
+            CellKind::Polygon => newell_vector(&self.points, cell.point_ids),
+            CellKind::Strip => Self::triangles(cell)
+                .iter()
+                .map(|triangle| newell_vector(&self.points, triangle))
+                .fold([0.0; 3], |sum, v| {
+                    [sum[0] + v[0], sum[1] + v[1], sum[2] + v[2]]
+                }),
+        }
+    }
+
+    fn centroid(&self, cell: Cell) -> [f64; 3] {
+        let normal = unit(self.area_vector(cell));
+        triangles_centroid(&self.points, &Self::triangles(cell), cell.point_ids, normal)
+    }
+}
+
+/// Sum of the edge cross terms of Newell's method; twice the polygon area
+/// times its unit normal.
+fn newell_vector(points: &[[f64; 3]], poly_indices: &[usize]) -> [f64; 3] {
+    let mut sum = [0.0; 3];
+    for (i, &index) in poly_indices.iter().enumerate() {
+        let a = points[index];
+        let b = points[poly_indices[(i + 1) % poly_indices.len()]];
+        sum[0] += (a[1] - b[1]) * (a[2] + b[2]);
+        sum[1] += (a[2] - b[2]) * (a[0] + b[0]);
+        sum[2] += (a[0] - b[0]) * (a[1] + b[1]);
+    }
+    sum
+}
+
+/// Area-weighted centroid of `triangles`, falling back to the mean of
+/// `point_indices` when they have no area. Areas are signed against the
+/// unit `normal`, so that fan triangles reaching outside a concave polygon
+/// cancel out.
+fn triangles_centroid(
+    points: &[[f64; 3]],
+    triangles: &[[usize; 3]],
+    point_indices: &[usize],
+    normal: [f64; 3],
+) -> [f64; 3] {
+    let mut weighted = [0.0; 3];
+    let mut total = 0.0;
+    for triangle in triangles {
+        let p = triangle.map(|i| points[i]);
+        let area = dot(cross(sub(p[1], p[0]), sub(p[2], p[0])), normal);
+        for (k, w) in weighted.iter_mut().enumerate() {
+            *w += area * (p[0][k] + p[1][k] + p[2][k]) / 3.0;
+        }
+        total += area;
+    }
+    if total > 0.0 {
+        return weighted.map(|w| w / total);
+    }
+
+    let count = point_indices.len().max(1) as f64;
+    let mut mean = [0.0; 3];
+    for &index in point_indices {
+        for (k, m) in mean.iter_mut().enumerate() {
+            *m += points[index][k] / count;
+        }
+    }
+    mean
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square_and_line() -> SurfaceMesh {
+        let points = vec![
+            [0.0, 0.0, 0.0],
+            [2.0, 0.0, 0.0],
+            [2.0, 2.0, 0.0],
+            [0.0, 2.0, 0.0],
+        ];
+        SurfaceMesh::new(
+            points,
+            vec![0, 1, 0, 1, 2, 3],
+            vec![2, 6],
+            vec![CellKind::Line, CellKind::Polygon],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_cells() {
+        let mesh = square_and_line();
+        assert_eq!(mesh.num_cells(), 2);
+        assert_eq!(mesh.cell(0).point_ids, &[0, 1]);
+        assert_eq!(mesh.cell(1).kind, CellKind::Polygon);
+        assert_eq!(mesh.cells().count(), 2);
+    }
+
+    #[test]
+    fn test_cell_geometry() {
+        let mesh = square_and_line();
+        assert_eq!(mesh.areas(), &[0.0, 4.0]);
+        assert_eq!(mesh.total_area(), 4.0);
+        assert_eq!(mesh.normals(), &[[0.0; 3], [0.0, 0.0, 1.0]]);
+        assert_eq!(mesh.centroids(), &[[1.0, 0.0, 0.0], [1.0, 1.0, 0.0]]);
+    }
+
+    #[test]
+    fn test_strip_normal_and_centroid() {
+        let points = vec![
+            [0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [1.0, 1.0, 0.0],
+        ];
+        let mesh =
+            SurfaceMesh::new(points, vec![0, 1, 2, 3], vec![4], vec![CellKind::Strip]).unwrap();
+        assert_eq!(mesh.normals(), &[[0.0, 0.0, -1.0]]);
+        assert_eq!(mesh.centroids(), &[[0.5, 0.5, 0.0]]);
+    }
+
+    #[test]
+    fn test_validation() {
+        let points = vec![[0.0; 3]; 3];
+        let polygon = vec![CellKind::Polygon];
+
+        assert!(SurfaceMesh::new(points.clone(), vec![0, 1, 2], vec![3], polygon.clone()).is_ok());
+        assert!(SurfaceMesh::new(points.clone(), vec![0, 1, 3], vec![3], polygon.clone()).is_err());
+        assert!(SurfaceMesh::new(points.clone(), vec![0, 1, 2], vec![2], polygon.clone()).is_err());
+        assert!(SurfaceMesh::new(points.clone(), vec![0, 1, 2], vec![3], Vec::new()).is_err());
+        assert!(SurfaceMesh::new(
+            points,
+            vec![0, 1, 2],
+            vec![3, 2],
+            vec![CellKind::Polygon; 2]
+        )
+        .is_err());
+    }
+}
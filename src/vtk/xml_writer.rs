@@ -0,0 +1,532 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::str::FromStr;
+
+use vtkio::model::Attribute;
+use vtkio::model::ElementType;
+use vtkio::model::IOBuffer;
+use vtkio::model::PolyDataPiece;
+
+use super::data_type::DataType;
+use super::field_manager::FieldManager;
+
+/// Uncompressed size of the blocks data is split into before compression,
+/// as used by VTK
+const BLOCK_SIZE: usize = 1 << 15;
+
+/// How data arrays are stored in a `.vtp` file.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Encoding {
+    Ascii,
+    /// base64 encoded inside each `<DataArray>`
+    #[default]
+    Binary,
+    /// Raw bytes in a single `<AppendedData>` section
+    Appended,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Compression {
+    #[default]
+    None,
+    Zlib,
+    Lz4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WriteOptions {
+    pub encoding: Encoding,
+    pub compression: Compression,
+    /// zlib compression level (0-9)
+    pub level: u32,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        WriteOptions {
+            encoding: Encoding::default(),
+            compression: Compression::default(),
+            level: 6,
+        }
+    }
+}
+
+/// Writes a single PolyData piece as a `.vtp` file.
+pub fn write_poly_data(
+    piece: &PolyDataPiece,
+    options: &WriteOptions,
+    mut writer: impl Write,
+) -> Result<(), Box<dyn Error>> {
+    if options.encoding == Encoding::Ascii && options.compression != Compression::None {
+        return Err("ASCII encoding cannot be compressed".into());
+    }
+
+    let cells = [
+        ("Verts", &piece.verts),
+        ("Lines", &piece.lines),
+        ("Strips", &piece.strips),
+        ("Polys", &piece.polys),
+    ];
+
+    let mut arrays = ArrayWriter {
+        options,
+        xml: String::new(),
+        appended: Vec::new(),
+    };
+    let compressor = match options.compression {
+        Compression::None => String::new(),
+        Compression::Zlib => " compressor=\"vtkZLibDataCompressor\"".to_string(),
+        Compression::Lz4 => " compressor=\"vtkLZ4DataCompressor\"".to_string(),
+    };
+    writeln!(arrays.xml, "<?xml version=\"1.0\"?>")?;
+    writeln!(
+        arrays.xml,
+        "<VTKFile type=\"PolyData\" version=\"1.0\" byte_order=\"LittleEndian\" \
+         header_type=\"UInt64\"{}>",
+        compressor
+    )?;
+    writeln!(arrays.xml, "  <PolyData>")?;
+    write!(
+        arrays.xml,
+        "    <Piece NumberOfPoints=\"{}\"",
+        piece.points.len() / 3
+    )?;
+    for (tag, cells) in cells {
+        let count = cells.as_ref().map_or(0, |c| c.num_cells());
+        write!(arrays.xml, " NumberOf{}=\"{}\"", tag, count)?;
+    }
+    writeln!(arrays.xml, ">")?;
+
+    arrays.attributes("PointData", &piece.data.point)?;
+    arrays.attributes("CellData", &piece.data.cell)?;
+
+    writeln!(arrays.xml, "      <Points>")?;
+    arrays.data_array("Points", 3, &piece.points)?;
+    writeln!(arrays.xml, "      </Points>")?;
+
+    for (tag, cells) in cells {
+        let Some(cells) = cells else {
+            continue;
+        };
+        let (connectivity, offsets) = cells.clone().into_xml();
+        let to_i64 = |v: Vec<u64>| IOBuffer::I64(v.into_iter().map(|i| i as i64).collect());
+        writeln!(arrays.xml, "      <{}>", tag)?;
+        arrays.data_array("connectivity", 1, &to_i64(connectivity))?;
+        arrays.data_array("offsets", 1, &to_i64(offsets))?;
+        writeln!(arrays.xml, "      </{}>", tag)?;
+    }
+
+    writeln!(arrays.xml, "    </Piece>")?;
+    writeln!(arrays.xml, "  </PolyData>")?;
+
+    writer.write_all(arrays.xml.as_bytes())?;
+    if options.encoding == Encoding::Appended {
+        writer.write_all(b"  <AppendedData encoding=\"raw\">\n   _")?;
+        writer.write_all(&arrays.appended)?;
+        writer.write_all(b"\n  </AppendedData>\n")?;
+    }
+    writer.write_all(b"</VTKFile>\n")?;
+    Ok(())
+}
+
+struct ArrayWriter<'a> {
+    options: &'a WriteOptions,
+    xml: String,
+    appended: Vec<u8>,
+}
+
+impl ArrayWriter<'_> {
+    fn attributes(&mut self, tag: &str, attributes: &[Attribute]) -> Result<(), Box<dyn Error>> {
+        if attributes.is_empty() {
+            return Ok(());
+        }
+
+        // Marks the active arrays so readers restore their element type
+        let mut active: Vec<(&str, &str)> = Vec::new();
+        for attr in attributes {
+            if let Attribute::DataArray(arr) = attr {
+                let kind = match arr.elem {
+                    ElementType::Scalars { .. } => "Scalars",
+                    ElementType::Vectors => "Vectors",
+                    ElementType::Normals => "Normals",
+                    ElementType::TCoords(_) => "TCoords",
+                    ElementType::Tensors => "Tensors",
+                    _ => continue,
+                };
+                if !active.iter().any(|(k, _)| *k == kind) {
+                    active.push((kind, &arr.name));
+                }
+            }
+        }
+
+        write!(self.xml, "      <{}", tag)?;
+        for (kind, name) in active {
+            write!(self.xml, " {}=\"{}\"", kind, escape(name))?;
+        }
+        writeln!(self.xml, ">")?;
+        for attr in attributes {
+            match attr {
+                Attribute::DataArray(arr) => {
+                    let num_comp = FieldManager::num_components(&arr.elem);
+                    self.data_array(&arr.name, num_comp, &arr.data)?;
+                }
+                Attribute::Field { data_array, .. } => {
+                    for arr in data_array {
+                        self.data_array(&arr.name, arr.elem.max(1) as usize, &arr.data)?;
+                    }
+                }
+            }
+        }
+        writeln!(self.xml, "      </{}>", tag)?;
+        Ok(())
+    }
+
+    fn data_array(
+        &mut self,
+        name: &str,
+        num_comp: usize,
+        buffer: &IOBuffer,
+    ) -> Result<(), Box<dyn Error>> {
+        let data_type =
+            DataType::of(buffer).ok_or_else(|| format!("Cannot write bit array '{}'", name))?;
+        write!(
+            self.xml,
+            "        <DataArray type=\"{}\" Name=\"{}\" NumberOfComponents=\"{}\"",
+            data_type,
+            escape(name),
+            num_comp
+        )?;
+
+        match self.options.encoding {
+            Encoding::Ascii => {
+                writeln!(self.xml, " format=\"ascii\">")?;
+                for line in ascii_values(buffer).chunks(6) {
+                    writeln!(self.xml, "          {}", line.join(" "))?;
+                }
+            }
+            Encoding::Binary => {
+                writeln!(self.xml, " format=\"binary\">")?;
+                let bytes = to_le_bytes(buffer);
+                let encoded = match self.options.compression {
+                    Compression::None => {
+                        let mut data = (bytes.len() as u64).to_le_bytes().to_vec();
+                        data.extend(bytes);
+                        base64(&data)
+                    }
+                    // Header and blocks form one base64 stream
+                    _ => {
+                        let (mut data, blocks) = self.compress(&bytes)?;
+                        data.extend(blocks);
+                        base64(&data)
+                    }
+                };
+                writeln!(self.xml, "          {}", encoded)?;
+            }
+            Encoding::Appended => {
+                writeln!(
+                    self.xml,
+                    " format=\"appended\" offset=\"{}\"/>",
+                    self.appended.len()
+                )?;
+                let bytes = to_le_bytes(buffer);
+                match self.options.compression {
+                    Compression::None => {
+                        self.appended.extend((bytes.len() as u64).to_le_bytes());
+                        self.appended.extend(bytes);
+                    }
+                    _ => {
+                        let (header, blocks) = self.compress(&bytes)?;
+                        self.appended.extend(header);
+                        self.appended.extend(blocks);
+                    }
+                }
+                return Ok(());
+            }
+        }
+        writeln!(self.xml, "        </DataArray>")?;
+        Ok(())
+    }
+
+    /// Splits `bytes` into blocks and compresses each. The header lists the
+    /// number of blocks, the block size, the size of the last partial block
+    /// (zero if there is none) and the compressed size of each block.
+    fn compress(&self, bytes: &[u8]) -> Result<(Vec<u8>, Vec<u8>), Box<dyn Error>> {
+        let mut sizes = vec![
+            bytes.len().div_ceil(BLOCK_SIZE) as u64,
+            BLOCK_SIZE as u64,
+            (bytes.len() % BLOCK_SIZE) as u64,
+        ];
+        let mut blocks = Vec::new();
+        for block in bytes.chunks(BLOCK_SIZE) {
+            let compressed = match self.options.compression {
+                Compression::Zlib => {
+                    let level = flate2::Compression::new(self.options.level.min(9));
+                    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), level);
+                    encoder.write_all(block)?;
+                    encoder.finish()?
+                }
+                Compression::Lz4 => lz4_flex::block::compress(block),
+                Compression::None => block.to_vec(),
+            };
+            sizes.push(compressed.len() as u64);
+            blocks.extend(compressed);
+        }
+        let header = sizes.iter().flat_map(|s| s.to_le_bytes()).collect();
+        Ok((header, blocks))
+    }
+}
+
+macro_rules! for_each_numeric {
+    ($buffer:expr, $data:ident => $body:expr) => {
+        match $buffer {
+            IOBuffer::Bit($data) | IOBuffer::U8($data) => $body,
+            IOBuffer::I8($data) => $body,
+            IOBuffer::U16($data) => $body,
+            IOBuffer::I16($data) => $body,
+            IOBuffer::U32($data) => $body,
+            IOBuffer::I32($data) => $body,
+            IOBuffer::U64($data) => $body,
+            IOBuffer::I64($data) => $body,
+            IOBuffer::F32($data) => $body,
+            IOBuffer::F64($data) => $body,
+        }
+    };
+}
+
+fn to_le_bytes(buffer: &IOBuffer) -> Vec<u8> {
+    for_each_numeric!(buffer, data => data.iter().flat_map(|v| v.to_le_bytes()).collect())
+}
+
+fn ascii_values(buffer: &IOBuffer) -> Vec<String> {
+    for_each_numeric!(buffer, data => data.iter().map(|v| v.to_string()).collect())
+}
+
+pub(super) fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ascii" => Ok(Encoding::Ascii),
+            "binary" | "base64" => Ok(Encoding::Binary),
+            "appended" | "raw" => Ok(Encoding::Appended),
+            _ => Err(format!(
+                "Unknown encoding '{}', expected ascii, binary or appended",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Encoding::Ascii => write!(f, "ascii"),
+            Encoding::Binary => write!(f, "binary"),
+            Encoding::Appended => write!(f, "appended"),
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" => Ok(Compression::None),
+            "zlib" => Ok(Compression::Zlib),
+            "lz4" => Ok(Compression::Lz4),
+            _ => Err(format!(
+                "Unknown compression '{}', expected none, zlib or lz4",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Compression::None => write!(f, "none"),
+            Compression::Zlib => write!(f, "zlib"),
+            Compression::Lz4 => write!(f, "lz4"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vtkio::model::Attributes;
+    use vtkio::model::DataArray;
+    use vtkio::model::VertexNumbers;
+
+    use super::*;
+
+    fn triangle() -> PolyDataPiece {
+        PolyDataPiece {
+            points: IOBuffer::F32(vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]),
+            verts: None,
+            lines: None,
+            polys: Some(VertexNumbers::XML {
+                connectivity: vec![0, 1, 2],
+                offsets: vec![3],
+            }),
+            strips: None,
+            data: Attributes {
+                point: Vec::new(),
+                cell: vec![Attribute::DataArray(DataArray {
+                    name: "p".to_string(),
+                    elem: ElementType::Scalars {
+                        num_comp: 1,
+                        lookup_table: None,
+                    },
+                    data: IOBuffer::F64(vec![1.5]),
+                })],
+            },
+        }
+    }
+
+    fn write(options: WriteOptions) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_poly_data(&triangle(), &options, &mut out).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"M"), "TQ==");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(&[0xff, 0xfe, 0xfd, 0xfc]), "//79/A==");
+    }
+
+    #[test]
+    fn test_ascii() {
+        let options = WriteOptions {
+            encoding: Encoding::Ascii,
+            ..Default::default()
+        };
+        let text = String::from_utf8(write(options)).unwrap();
+
+        assert!(text.contains("NumberOfPoints=\"3\""));
+        assert!(text.contains("NumberOfPolys=\"1\""));
+        assert!(text.contains("<CellData Scalars=\"p\">"));
+        assert!(
+            text.contains(
+                "<DataArray type=\"Float64\" Name=\"p\" NumberOfComponents=\"1\" \
+                 format=\"ascii\">\n          1.5\n"
+            )
+        );
+        assert!(text.contains("          0 1 2\n"));
+        assert!(!text.contains("AppendedData"));
+    }
+
+    #[test]
+    fn test_binary() {
+        let text = String::from_utf8(write(WriteOptions::default())).unwrap();
+
+        // 8 byte length header followed by the f64 value 1.5
+        let mut expected = 8u64.to_le_bytes().to_vec();
+        expected.extend(1.5f64.to_le_bytes());
+        assert!(text.contains(&format!(
+            "format=\"binary\">\n          {}\n",
+            base64(&expected)
+        )));
+    }
+
+    #[test]
+    fn test_appended() {
+        let options = WriteOptions {
+            encoding: Encoding::Appended,
+            ..Default::default()
+        };
+        let out = write(options);
+        let text = String::from_utf8_lossy(&out);
+
+        assert!(
+            text.contains("Name=\"p\" NumberOfComponents=\"1\" format=\"appended\" offset=\"0\"/>")
+        );
+        // p (8 + 8 bytes) precedes the points
+        assert!(text.contains(
+            "Name=\"Points\" NumberOfComponents=\"3\" format=\"appended\" offset=\"16\"/>"
+        ));
+
+        let marker = b"<AppendedData encoding=\"raw\">\n   _";
+        let start = out.windows(marker.len()).position(|w| w == marker).unwrap() + marker.len();
+        assert_eq!(out[start..start + 8], 8u64.to_le_bytes());
+        assert_eq!(out[start + 8..start + 16], 1.5f64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_compression_header() {
+        let options = WriteOptions {
+            compression: Compression::Lz4,
+            ..Default::default()
+        };
+        let writer = ArrayWriter {
+            options: &options,
+            xml: String::new(),
+            appended: Vec::new(),
+        };
+        let bytes = vec![7u8; BLOCK_SIZE + 10];
+        let (header, blocks) = writer.compress(&bytes).unwrap();
+
+        let header: Vec<u64> = header
+            .as_chunks::<8>()
+            .0
+            .iter()
+            .map(|b| u64::from_le_bytes(*b))
+            .collect();
+        assert_eq!(header[..3], [2, BLOCK_SIZE as u64, 10]);
+        assert_eq!(header.len(), 5);
+        assert_eq!(header[3] + header[4], blocks.len() as u64);
+    }
+
+    #[test]
+    fn test_ascii_cannot_be_compressed() {
+        let options = WriteOptions {
+            encoding: Encoding::Ascii,
+            compression: Compression::Zlib,
+            level: 6,
+        };
+        assert!(write_poly_data(&triangle(), &options, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("raw".parse::<Encoding>(), Ok(Encoding::Appended));
+        assert_eq!("ZLIB".parse::<Compression>(), Ok(Compression::Zlib));
+        assert!("gzip".parse::<Compression>().is_err());
+    }
+}
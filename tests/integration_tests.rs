@@ -1,6 +1,13 @@
 use std::fs;
 use std::path::Path;
 
+use autofoam::vtk::clip::ClipMode;
+use autofoam::vtk::field_manager::FieldLocation;
+use autofoam::vtk::region::Region;
+use autofoam::vtk::slice::slice_mesh;
+use autofoam::vtk::xml_writer::Compression;
+use autofoam::vtk::xml_writer::Encoding;
+use autofoam::vtk::xml_writer::WriteOptions;
 use autofoam::vtk::VtpProcessor;
 
 #[cfg(test)]
@@ -43,6 +50,28 @@ mod tests {
         test_file
     }
 
+    fn create_test_legacy_vtk_file() -> String {
+        let test_data = "# vtk DataFile Version 2.0
+sampled surface
+ASCII
+DATASET POLYDATA
+POINTS 4 float
+0 0 0 1 0 0 1 1 0 0 1 0
+POLYGONS 2 8
+3 0 1 2
+3 0 2 3
+CELL_DATA 2
+FIELD attributes 1
+p 1 2 float
+1.5 2.5
+";
+
+        let uuid = uuid::Uuid::new_v4();
+        let test_file = format!("test_data_{}.vtk", uuid);
+        fs::write(&test_file, test_data).unwrap();
+        test_file
+    }
+
     fn cleanup_test_file(path: &str) {
         if Path::new(path).exists() {
             fs::remove_file(path).unwrap();
@@ -67,10 +96,11 @@ mod tests {
         let result = reader.geometry();
         assert!(result.is_ok());
 
-        let (points, connectivity, offsets) = result.unwrap();
-        assert_eq!(points.len(), 12); // 4 points * 3 coordinates
-        assert_eq!(connectivity, vec![0, 1, 2, 3]);
-        assert_eq!(offsets, vec![4]);
+        let mesh = result.unwrap();
+        assert_eq!(mesh.num_points(), 4);
+        assert_eq!(mesh.connectivity(), &[0, 1, 2, 3]);
+        assert_eq!(mesh.offsets(), &[4]);
+        assert_eq!(mesh.areas(), &[1.0]);
 
         cleanup_test_file(&test_file);
     }
@@ -137,4 +167,196 @@ mod tests {
 
         cleanup_test_file(&test_file);
     }
+
+    #[test]
+    fn test_legacy_vtk_reading() {
+        let test_file = create_test_legacy_vtk_file();
+        let reader = VtpProcessor::from_file(&test_file).unwrap();
+
+        let mesh = reader.geometry().unwrap();
+        assert_eq!(mesh.num_points(), 4);
+        assert_eq!(mesh.connectivity(), &[0, 1, 2, 0, 2, 3]);
+        assert_eq!(mesh.offsets(), &[3, 6]);
+
+        assert!(reader.field_exists("p").unwrap());
+        assert_eq!(reader.field("p").unwrap(), vec![1.5, 2.5]);
+
+        cleanup_test_file(&test_file);
+    }
+
+    #[test]
+    fn test_legacy_vtk_round_trip() {
+        let test_file = create_test_legacy_vtk_file();
+        let output_file = format!("test_output_{}.vtk", uuid::Uuid::new_v4());
+
+        let reader = VtpProcessor::from_file(&test_file).unwrap();
+        let reader = reader.remove_field("p").unwrap();
+        assert!(!reader.field_exists("p").unwrap());
+        reader
+            .add_field("q", &[3.0, 4.0])
+            .unwrap()
+            .write_to_file(&output_file)
+            .unwrap();
+
+        let written = fs::read_to_string(&output_file).unwrap();
+        assert!(written.starts_with("# vtk DataFile"));
+
+        let reader = VtpProcessor::from_file(&output_file).unwrap();
+        assert_eq!(reader.field("q").unwrap(), vec![3.0, 4.0]);
+
+        cleanup_test_file(&test_file);
+        cleanup_test_file(&output_file);
+    }
+
+    #[test]
+    fn test_extract_cells() {
+        let test_file = create_test_legacy_vtk_file();
+        let output_file = format!("test_output_{}.vtp", uuid::Uuid::new_v4());
+
+        let reader = VtpProcessor::from_file(&test_file).unwrap();
+        let extracted = reader.extract_cells(&[false, true]).unwrap();
+        let mesh = extracted.geometry().unwrap();
+        assert_eq!(mesh.num_points(), 3);
+        assert_eq!(mesh.connectivity(), &[0, 1, 2]);
+        assert_eq!(extracted.field("p").unwrap(), vec![2.5]);
+
+        extracted.write_to_file(&output_file).unwrap();
+        let reader = VtpProcessor::from_file(&output_file).unwrap();
+        assert_eq!(reader.field("p").unwrap(), vec![2.5]);
+        assert_eq!(reader.geometry().unwrap().total_area(), 0.5);
+
+        cleanup_test_file(&test_file);
+        cleanup_test_file(&output_file);
+    }
+
+    #[test]
+    fn test_clip() {
+        let test_file = create_test_legacy_vtk_file();
+        let reader = VtpProcessor::from_file(&test_file).unwrap();
+        let half: Region = "plane:0.5,0,0:1,0,0".parse().unwrap();
+
+        let clipped = reader.clip(&[half], ClipMode::Centroid, false).unwrap();
+        assert_eq!(clipped.field("p").unwrap(), vec![2.5]);
+
+        let clipped = reader.clip(&[half], ClipMode::Exact, false).unwrap();
+        assert_eq!(clipped.field("p").unwrap(), vec![1.5, 2.5]);
+        assert_eq!(clipped.geometry().unwrap().total_area(), 0.5);
+
+        let clipped = reader.clip(&[half], ClipMode::Exact, true).unwrap();
+        assert_eq!(clipped.geometry().unwrap().total_area(), 0.5);
+
+        cleanup_test_file(&test_file);
+    }
+
+    #[test]
+    fn test_slice() {
+        let test_file = create_test_legacy_vtk_file();
+        let output_file = format!("test_output_{}.vtp", uuid::Uuid::new_v4());
+
+        let reader = VtpProcessor::from_file(&test_file).unwrap();
+        let mesh = reader.geometry().unwrap();
+        let polylines = slice_mesh(&mesh, [0.0, 0.5, 0.0], [0.0, 1.0, 0.0]);
+        assert_eq!(polylines.len(), 1);
+
+        let positions: Vec<[f64; 3]> = polylines[0]
+            .points
+            .iter()
+            .map(|p| p.position(mesh.points()))
+            .collect();
+        assert_eq!(positions.first(), Some(&[0.0, 0.5, 0.0]));
+        assert_eq!(positions.last(), Some(&[1.0, 0.5, 0.0]));
+
+        let lines = vec![(0..positions.len()).collect()];
+        let arc_lengths = polylines[0].arc_lengths(mesh.points());
+        VtpProcessor::from_polylines(&positions, &lines)
+            .add_field_at("arc_length", &arc_lengths, FieldLocation::Point)
+            .unwrap()
+            .write_to_file(&output_file)
+            .unwrap();
+
+        let reader = VtpProcessor::from_file(&output_file).unwrap();
+        assert_eq!(reader.geometry().unwrap().num_points(), positions.len());
+        assert_eq!(
+            reader.field_at("arc_length", FieldLocation::Point).unwrap(),
+            arc_lengths
+        );
+
+        cleanup_test_file(&test_file);
+        cleanup_test_file(&output_file);
+    }
+
+    #[test]
+    fn test_multiblock_reading() {
+        let first = create_test_vtp_file();
+        let second = create_test_vtp_file();
+        let vtm_file = format!("test_data_{}.vtm", uuid::Uuid::new_v4());
+        let vtm_data = format!(
+            r#"<?xml version="1.0"?>
+<VTKFile type="vtkMultiBlockDataSet" version="1.0">
+  <vtkMultiBlockDataSet>
+    <Block index="0" name="boundary">
+      <DataSet index="0" name="first" file="{}"/>
+      <DataSet index="1" name="second" file="{}"/>
+    </Block>
+  </vtkMultiBlockDataSet>
+</VTKFile>"#,
+            first, second
+        );
+        fs::write(&vtm_file, vtm_data).unwrap();
+
+        assert_eq!(
+            VtpProcessor::block_names(&vtm_file).unwrap(),
+            vec!["boundary/first", "boundary/second"]
+        );
+
+        let merged = VtpProcessor::from_file(&vtm_file).unwrap();
+        let mesh = merged.geometry().unwrap();
+        assert_eq!(mesh.num_points(), 8);
+        assert_eq!(mesh.connectivity(), &[0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(mesh.offsets(), &[4, 8]);
+        assert_eq!(merged.field("test_field").unwrap(), vec![42.0, 42.0]);
+
+        let block = VtpProcessor::from_block(&vtm_file, "second").unwrap();
+        assert_eq!(block.field("test_field").unwrap(), vec![42.0]);
+
+        assert!(VtpProcessor::from_block(&vtm_file, "missing").is_err());
+
+        cleanup_test_file(&first);
+        cleanup_test_file(&second);
+        cleanup_test_file(&vtm_file);
+    }
+
+    #[test]
+    fn test_write_encodings() {
+        let test_file = create_test_vtp_file();
+
+        for encoding in [Encoding::Ascii, Encoding::Binary, Encoding::Appended] {
+            for compression in [Compression::None, Compression::Zlib, Compression::Lz4] {
+                if encoding == Encoding::Ascii && compression != Compression::None {
+                    continue;
+                }
+                let output_file = format!("test_output_{}.vtp", uuid::Uuid::new_v4());
+                let options = WriteOptions {
+                    encoding,
+                    compression,
+                    level: 6,
+                };
+                VtpProcessor::from_file(&test_file)
+                    .unwrap()
+                    .write_to_file_with(&output_file, &options)
+                    .unwrap();
+
+                let reader = VtpProcessor::from_file(&output_file).unwrap();
+                let mesh = reader.geometry().unwrap();
+                assert_eq!(mesh.num_points(), 4);
+                assert_eq!(mesh.connectivity(), &[0, 1, 2, 3]);
+                assert_eq!(mesh.offsets(), &[4]);
+                assert_eq!(reader.field("test_field").unwrap(), vec![42.0]);
+
+                cleanup_test_file(&output_file);
+            }
+        }
+
+        cleanup_test_file(&test_file);
+    }
 }